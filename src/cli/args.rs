@@ -0,0 +1,260 @@
+//! Command-line argument parsing
+
+use std::path::PathBuf;
+
+use fontenum::{Column, EnumMode, ExportFormat, FontFaceOptions};
+
+pub const USAGE: &str = "\
+Usage: fontenum-cli [OPTIONS] <COMMAND>
+
+Commands:
+  list               List every enumerated font
+  search <query>     List fonts matching <query>: family/style text plus
+                     key:value terms, e.g. 'width:condensed weight:600-900'
+  show <family>      Show all details of every face in a family
+  covers <text>      List fonts that can render <text>, with the characters
+                     each one is missing; <text> may also be code point
+                     ranges, e.g. 'U+0400-04FF,U+20AC'
+  match <font>       Pick the face a browser would use for a CSS font
+                     request (the `font` shorthand without the size), e.g.
+                     'italic 600 condensed Segoe UI, Arial', and explain why
+  fallback <font> <text>
+                     Split <text> into runs by script and coverage and show
+                     which face renders each run, starting from the <font>
+                     request (as for match), with each script's fallback chain
+  audit [query]      Group fonts (optionally those matching [query]) by
+                     fsType embedding permission, with their license URLs
+  export [query]     Write every font (or those matching [query]) with all
+                     fields as JSON, plus the source, timestamp and host;
+                     or the --columns as CSV/TSV
+  fontface <family>  Write CSS @font-face rules for self-hosting a family:
+                     weight/stretch/style ranges, src with format() hints
+                     and unicode-range from each face's cmap
+  substitute <font> [family]
+                     Rank installed families as metric-compatible fallbacks
+                     for the face <font> (a request, as for match) picks, or
+                     compare [family] only; prints the size-adjust and
+                     ascent/descent/line-gap overrides and the best rule
+  specimen [query]   Write an HTML specimen page of every font (or those
+                     matching [query]), loading each face from its file
+  snapshot [query]   Write a snapshot of every font (or those matching
+                     [query]) for diff: names, versions, paths, weights,
+                     axes and file hashes
+  diff <old> <new>   Compare two snapshot files by family: faces added,
+                     removed and changed; exits with 1 if they differ
+  validate [PATH]... Check font files (or directories of them) for corrupt
+                     tables; defaults to --dir or the system font directories
+
+Options:
+  -s, --source <MODE>  Enumeration source: gdi, directwrite, fontset, directory
+                       (default: fontset on Windows, directory elsewhere)
+  -d, --dir <PATH>     Directory to scan (implies --source directory; repeatable)
+  -i, --instances      List each named instance of a variable font as its own row
+  -p, --percent <N>    covers: minimum share of <text> a font must cover (default 100)
+  -f, --format <FMT>   export: json (default), ndjson (one font per line),
+                       csv or tsv; diff: json instead of text
+  -c, --columns <LIST> Columns of list, search and CSV/TSV export, e.g.
+                       'family,style,weight,psname', or 'all'
+  --inline             specimen: embed the font files (base64) instead of
+                       linking them, so the page works on other machines
+  --base-url <URL>     fontface: prefix of the font file names in url()
+  --local              fontface: try installed copies first with local()
+  --overrides          fontface: add ascent/descent/line-gap overrides from
+                       the font's own metrics, for the same line layout everywhere
+  -h, --help           Print this help";
+
+/// A parsed subcommand
+#[derive(Debug)]
+pub enum Command {
+    List,
+    Search(String),
+    Show(String),
+    Covers(String),
+    Match(String),
+    Fallback(String, String),
+    Audit(Option<String>),
+    Export(Option<String>),
+    FontFace(String),
+    Substitute(String, Option<String>),
+    Specimen(Option<String>),
+    Snapshot(Option<String>),
+    Diff(PathBuf, PathBuf),
+    Validate(Vec<PathBuf>),
+    Help,
+}
+
+/// All parsed command-line options
+#[derive(Debug)]
+pub struct Args {
+    pub command: Command,
+    pub mode: EnumMode,
+    pub dirs: Vec<PathBuf>,
+    pub instances: bool,
+    pub min_percent: f32,
+    pub format: Option<ExportFormat>, // None for each command's default
+    pub columns: Option<Vec<Column>>, // None for the default columns
+    pub inline: bool,
+    pub font_face: FontFaceOptions,
+}
+
+/// Parses the arguments following the program name
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut mode: Option<EnumMode> = None;
+    let mut dirs = Vec::new();
+    let mut instances = false;
+    let mut min_percent = 100.0;
+    let mut format = None;
+    let mut columns = None;
+    let mut inline = false;
+    let mut font_face = FontFaceOptions::default();
+    let mut positional = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                return Ok(Args {
+                    command: Command::Help,
+                    mode: EnumMode::None,
+                    dirs,
+                    instances,
+                    min_percent,
+                    format,
+                    columns,
+                    inline,
+                    font_face,
+                });
+            }
+            "-s" | "--source" => {
+                let value = args.next().ok_or("--source requires a value")?;
+                mode = Some(
+                    EnumMode::from_name(&value)
+                        .ok_or_else(|| format!("unknown source '{}'", value))?,
+                );
+            }
+            "-d" | "--dir" => {
+                let value = args.next().ok_or("--dir requires a value")?;
+                dirs.push(PathBuf::from(value));
+            }
+            "-i" | "--instances" => instances = true,
+            "--inline" => inline = true,
+            "--base-url" => {
+                font_face.base_url = args.next().ok_or("--base-url requires a value")?
+            }
+            "--local" => font_face.local = true,
+            "--overrides" => font_face.metric_overrides = true,
+            "-p" | "--percent" => {
+                let value = args.next().ok_or("--percent requires a value")?;
+                min_percent = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .ok_or_else(|| format!("invalid percentage '{}'", value))?;
+            }
+            "-f" | "--format" => {
+                let value = args.next().ok_or("--format requires a value")?;
+                format = Some(
+                    ExportFormat::from_name(&value)
+                        .ok_or_else(|| format!("unknown format '{}'", value))?,
+                );
+            }
+            "-c" | "--columns" => {
+                let value = args.next().ok_or("--columns requires a value")?;
+                columns = Some(parse_columns(&value)?);
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option '{}'", arg));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    // --dir only makes sense for directory scanning
+    let mode = match mode {
+        Some(mode) if !dirs.is_empty() && mode != EnumMode::Directory => {
+            return Err("--dir can only be used with --source directory".to_string());
+        }
+        Some(mode) => mode,
+        None if !dirs.is_empty() => EnumMode::Directory,
+        None => default_mode(),
+    };
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("list") => Command::List,
+        Some("search") => Command::Search(positional.next().ok_or("search requires a query")?),
+        Some("show") => Command::Show(positional.next().ok_or("show requires a family name")?),
+        Some("covers") => Command::Covers(positional.next().ok_or("covers requires text")?),
+        Some("match") => Command::Match(positional.next().ok_or("match requires a font request")?),
+        Some("fallback") => {
+            let font = positional
+                .next()
+                .ok_or("fallback requires a font request")?;
+            Command::Fallback(font, positional.next().ok_or("fallback requires text")?)
+        }
+        Some("audit") => Command::Audit(positional.next()),
+        Some("export") => Command::Export(positional.next()),
+        Some("fontface") => {
+            Command::FontFace(positional.next().ok_or("fontface requires a family name")?)
+        }
+        Some("substitute") => {
+            let font = positional
+                .next()
+                .ok_or("substitute requires a font request")?;
+            Command::Substitute(font, positional.next())
+        }
+        Some("specimen") => Command::Specimen(positional.next()),
+        Some("snapshot") => Command::Snapshot(positional.next()),
+        Some("diff") => {
+            let old = positional
+                .next()
+                .ok_or("diff requires two snapshot files")?;
+            Command::Diff(
+                old.into(),
+                positional
+                    .next()
+                    .ok_or("diff requires two snapshot files")?
+                    .into(),
+            )
+        }
+        Some("validate") => Command::Validate(positional.by_ref().map(PathBuf::from).collect()),
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+        None => return Err("no command given".to_string()),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+
+    Ok(Args {
+        command,
+        mode,
+        dirs,
+        instances,
+        min_percent,
+        format,
+        columns,
+        inline,
+        font_face,
+    })
+}
+
+/// Parses a comma-separated list of column names, or `all`
+fn parse_columns(list: &str) -> Result<Vec<Column>, String> {
+    if list.eq_ignore_ascii_case("all") {
+        return Ok(Column::ALL.to_vec());
+    }
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| Column::from_name(name).ok_or_else(|| format!("unknown column '{}'", name)))
+        .collect()
+}
+
+/// The richest source available on this platform
+fn default_mode() -> EnumMode {
+    if cfg!(windows) {
+        EnumMode::FontSet
+    } else {
+        EnumMode::Directory
+    }
+}
@@ -0,0 +1,594 @@
+//! fontenum-cli - headless front end for the `fontenum` library
+//!
+//! Runs one enumeration source and prints the resulting `FontInfo` rows,
+//! so the inventory can be used from scripts and CI:
+//! - `list` - every font as an aligned table
+//! - `search <query>` - fonts matching `apply_filter` (same as the GUI filter box)
+//! - `covers <text>` - fonts whose cmap covers the text (or a share of it)
+//! - `show <family>` - every field of each face in one family, including
+//!   the OS/2 table, variation axes, named instances, layout features,
+//!   the table directory and all name records
+//! - `match <font>` - the face CSS font matching selects for a request,
+//!   with the reasoning of each step
+//! - `fallback <font> <text>` - the face rendering each script/coverage run
+//!   of the text, and the fallback chain of each script
+//! - `audit [query]` - fonts grouped by embedding permission, with the
+//!   fsType restrictions and license URL of each
+//! - `export [query]` - the (filtered) inventory as JSON or NDJSON, or its
+//!   columns as CSV/TSV
+//! - `fontface <family>` - CSS @font-face rules for a family
+//! - `substitute <font> [family]` - local fonts ranked as metric-compatible
+//!   fallbacks for a web font, with their @font-face overrides
+//! - `specimen [query]` - an HTML specimen page of the (filtered) fonts
+//! - `snapshot [query]` - a snapshot file of the (filtered) inventory
+//! - `diff <old> <new>` - faces added, removed and changed between two
+//!   snapshots, grouped by family; exits with 1 if there are any
+//! - `validate [path]...` - structural checks of font files; exits with 1
+//!   if any font has errors
+//!
+//! With `--instances`, `list` and `search` show one row per named instance
+//! of each variable font; `--columns` picks the columns of `list`, `search`
+//! and CSV/TSV exports.
+
+mod args;
+mod table;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use args::{Args, Command};
+use fontenum::{
+    apply_filter, diff_snapshots, embedding_audit, expand_named_instances, fallback_chain,
+    font_face_rules, fonts_covering, is_codepoint_list, match_font, open_source, parse_codepoints,
+    rank_metric_fallbacks, text_characters, validate_paths, write_delimited, write_specimen,
+    AuditGroup, Column, CoverageMatch, DirectorySource, EmbeddingRights, ExportFormat, Fallback,
+    FontInfo, FontMatch, FontReport, FontRequest, FontStyle, Inventory, MetricFallback, Severity,
+    Snapshot, SnapshotDiff, SpecimenFonts,
+};
+
+/// Rows of the substitute ranking
+const SUBSTITUTES_SHOWN: usize = 10;
+
+fn main() -> ExitCode {
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, args::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the selected command, returning the process exit code
+fn run(args: &Args) -> Result<ExitCode, String> {
+    if let Command::Help = args.command {
+        println!("{}", args::USAGE);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Command::Validate(paths) = &args.command {
+        return validate(args, paths);
+    }
+    if let Command::Diff(old, new) = &args.command {
+        return diff(args, old, new);
+    }
+
+    let source = open_source(args.mode, &args.dirs).map_err(|e| e.to_string())?;
+    let mut fonts = source.enumerate().map_err(|e| e.to_string())?;
+
+    let mut columns = args
+        .columns
+        .clone()
+        .unwrap_or_else(|| Column::DEFAULT.to_vec());
+    if args.instances {
+        fonts = expand_named_instances(&fonts);
+        if args.columns.is_none() {
+            columns.push(Column::Instance);
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = match &args.command {
+        Command::List => table::print_table(&mut out, &columns, &fonts),
+        Command::Search(query) => {
+            let matches = apply_filter(&fonts, query);
+            table::print_table(&mut out, &columns, matches.iter().map(|&i| &fonts[i]))
+        }
+        Command::Show(family) => {
+            let faces: Vec<&FontInfo> = fonts
+                .iter()
+                .filter(|f| f.family_name.eq_ignore_ascii_case(family))
+                .collect();
+            if faces.is_empty() {
+                return Err(format!("no font family named '{}'", family));
+            }
+            show_faces(&mut out, &faces)
+        }
+        Command::Covers(text) => {
+            let query = covers_query(text)?;
+            let matches = fonts_covering(&fonts, &query, args.min_percent);
+            print_coverage(&mut out, &fonts, &matches)
+        }
+        Command::Match(css) => {
+            let request = FontRequest::parse(css).map_err(|e| e.to_string())?;
+            let Some(found) = match_font(&fonts, &request) else {
+                return Err(format!(
+                    "no faces for any of {}",
+                    request.families.join(", ")
+                ));
+            };
+            print_match(&mut out, &fonts, &found)
+        }
+        Command::Fallback(css, text) => {
+            let request = FontRequest::parse(css).map_err(|e| e.to_string())?;
+            print_fallback(
+                &mut out,
+                &fonts,
+                text,
+                &fallback_chain(&fonts, &request, text),
+            )
+        }
+        Command::Audit(query) => {
+            let matches = apply_filter(&fonts, query.as_deref().unwrap_or(""));
+            let selected: Vec<FontInfo> = matches.iter().map(|&i| fonts[i].clone()).collect();
+            print_audit(&mut out, &selected, &embedding_audit(&selected))
+        }
+        Command::Export(query) if args.format.is_some_and(ExportFormat::is_tabular) => {
+            let matches = apply_filter(&fonts, query.as_deref().unwrap_or(""));
+            let format = args.format.unwrap_or(ExportFormat::Csv);
+            write_delimited(
+                &mut out,
+                matches.iter().map(|&i| &fonts[i]),
+                &columns,
+                format,
+            )
+        }
+        Command::Export(query) => {
+            let matches = apply_filter(&fonts, query.as_deref().unwrap_or(""));
+            let selected: Vec<FontInfo> = matches.iter().map(|&i| fonts[i].clone()).collect();
+            // I/O errors go through the broken-pipe check below
+            let format = args.format.unwrap_or(ExportFormat::Json);
+            match Inventory::new(source.mode(), selected).write(&mut out, format) {
+                Ok(()) => Ok(()),
+                Err(fontenum::Error::Io(e)) => Err(e),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Command::FontFace(family) => {
+            let rules = font_face_rules(&fonts, family, &args.font_face);
+            if rules.is_empty() {
+                return Err(format!("no faces of family '{}'", family));
+            }
+            rules
+                .iter()
+                .try_for_each(|rule| writeln!(out, "{}\n", rule))
+        }
+        Command::Substitute(css, family) => {
+            let request = FontRequest::parse(css).map_err(|e| e.to_string())?;
+            let Some(target) = match_font(&fonts, &request) else {
+                return Err(format!(
+                    "no faces for any of {}",
+                    request.families.join(", ")
+                ));
+            };
+            let target = &fonts[target.index];
+            let fallbacks = match family {
+                Some(family) => {
+                    let style = if target.italic {
+                        FontStyle::Italic
+                    } else {
+                        FontStyle::Normal
+                    };
+                    let candidate = FontRequest {
+                        families: vec![family.clone()],
+                        weight: target.weight as f32,
+                        style,
+                        ..request
+                    };
+                    let Some(found) = match_font(&fonts, &candidate) else {
+                        return Err(format!("no faces of family '{}'", family));
+                    };
+                    MetricFallback::rate(&fonts, target, found.index)
+                        .into_iter()
+                        .collect()
+                }
+                None => rank_metric_fallbacks(&fonts, target),
+            };
+            if fallbacks.is_empty() {
+                return Err(format!(
+                    "no font to compare with {} {} (no OS/2 average width)",
+                    target.family_name, target.style_name
+                ));
+            }
+            print_substitutes(&mut out, &fonts, target, &fallbacks)
+        }
+        Command::Specimen(query) => {
+            let matches = apply_filter(&fonts, query.as_deref().unwrap_or(""));
+            let selected: Vec<FontInfo> = matches.iter().map(|&i| fonts[i].clone()).collect();
+            let embed = if args.inline {
+                SpecimenFonts::Inline
+            } else {
+                SpecimenFonts::Link
+            };
+            match write_specimen(&mut out, &selected, embed) {
+                Ok(()) => Ok(()),
+                Err(fontenum::Error::Io(e)) => Err(e),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Command::Snapshot(query) => {
+            let matches = apply_filter(&fonts, query.as_deref().unwrap_or(""));
+            let selected: Vec<FontInfo> = matches.iter().map(|&i| fonts[i].clone()).collect();
+            let json = Snapshot::new(source.mode(), &selected)
+                .to_json()
+                .map_err(|e| e.to_string())?;
+            writeln!(out, "{}", json)
+        }
+        Command::Help | Command::Validate(_) | Command::Diff(..) => unreachable!(),
+    };
+
+    // A closed pipe (e.g. `| head`) is not an error worth reporting
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.to_string()),
+        _ => Ok(ExitCode::SUCCESS),
+    }
+}
+
+/// Prints the selected face, the axis values to apply and the reasoning
+fn print_match(out: &mut impl Write, fonts: &[FontInfo], found: &FontMatch) -> io::Result<()> {
+    let font = &fonts[found.index];
+    writeln!(out, "{} {}", font.family_name, font.style_name)?;
+    if !font.file_path.is_empty() {
+        writeln!(out, "  {}", font.location())?;
+    }
+    if !found.variations.is_empty() {
+        let values: Vec<String> = found
+            .variations
+            .iter()
+            .map(|(tag, v)| format!("{}={}", tag, v))
+            .collect();
+        writeln!(out, "  Variations: {}", values.join(" "))?;
+    }
+    for reason in &found.reasons {
+        writeln!(out, "  - {}", reason)?;
+    }
+    Ok(())
+}
+
+/// Prints the requested faces, the chain of each script and then the runs
+fn print_fallback(
+    out: &mut impl Write,
+    fonts: &[FontInfo],
+    text: &str,
+    fallback: &Fallback,
+) -> io::Result<()> {
+    let name = |i: usize| format!("{} {}", fonts[i].family_name, fonts[i].style_name);
+    let names = |faces: &[usize]| {
+        faces
+            .iter()
+            .map(|&i| name(i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    writeln!(out, "Requested: {}", names(&fallback.stack))?;
+    for (script, chain) in &fallback.chains {
+        let chain = if chain.is_empty() {
+            "(no font)".to_string()
+        } else {
+            names(chain)
+        };
+        writeln!(
+            out,
+            "Fallback for {}: {}",
+            script.to_string().trim_end(),
+            chain
+        )?;
+    }
+    writeln!(out)?;
+
+    let rows: Vec<Vec<String>> = fallback
+        .runs
+        .iter()
+        .map(|run| {
+            vec![
+                format!("{}-{}", run.range.start, run.range.end),
+                text[run.range.clone()].escape_debug().to_string(),
+                run.script.to_string().trim_end().to_string(),
+                run.font.map(name).unwrap_or_else(|| "(tofu)".to_string()),
+            ]
+        })
+        .collect();
+    table::print_rows(out, &["Bytes", "Text", "Script", "Font"], &rows)
+}
+
+/// Prints the best-ranked fallbacks and the @font-face rule of the first
+fn print_substitutes(
+    out: &mut impl Write,
+    fonts: &[FontInfo],
+    target: &FontInfo,
+    fallbacks: &[MetricFallback],
+) -> io::Result<()> {
+    writeln!(out, "Target: {} {}", target.family_name, target.style_name)?;
+    writeln!(out)?;
+
+    let percent = |value: f32| format!("{:.1}%", value);
+    let rows: Vec<Vec<String>> = fallbacks
+        .iter()
+        .take(SUBSTITUTES_SHOWN)
+        .map(|fallback| {
+            let font = &fonts[fallback.index];
+            vec![
+                format!("{} {}", font.family_name, font.style_name),
+                format!("{:.1}", fallback.score),
+                percent(fallback.overrides.size_adjust),
+                percent(fallback.overrides.ascent),
+                percent(fallback.overrides.descent),
+                percent(fallback.overrides.line_gap),
+                fallback.x_height.map(percent).unwrap_or_default(),
+                fallback.missing.to_string(),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Font",
+        "Score",
+        "Size Adjust",
+        "Ascent",
+        "Descent",
+        "Line Gap",
+        "x-Height",
+        "Missing",
+    ];
+    table::print_rows(out, &headers, &rows)?;
+
+    writeln!(out)?;
+    writeln!(out, "{}", fallbacks[0].font_face_rule(fonts, target))
+}
+
+/// Prints one table per permission level, least restrictive first
+fn print_audit(out: &mut impl Write, fonts: &[FontInfo], groups: &[AuditGroup]) -> io::Result<()> {
+    for (i, group) in groups.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let level = match group.permission {
+            Some(permission) => permission.to_string(),
+            None => "Unknown (no OS/2 table)".to_string(),
+        };
+        writeln!(out, "{} ({} faces)", level, group.fonts.len())?;
+
+        let rows: Vec<Vec<String>> = group
+            .fonts
+            .iter()
+            .map(|&i| {
+                let font = &fonts[i];
+                let rights = EmbeddingRights::of(font);
+                let mut restrictions = Vec::new();
+                if rights.as_ref().map(|r| r.no_subsetting).unwrap_or(false) {
+                    restrictions.push("no subsetting");
+                }
+                if rights.as_ref().map(|r| r.bitmap_only).unwrap_or(false) {
+                    restrictions.push("bitmap only");
+                }
+                vec![
+                    font.family_name.clone(),
+                    font.style_name.clone(),
+                    restrictions.join(", "),
+                    rights.and_then(|r| r.license_url).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        table::print_rows(
+            out,
+            &["Font Family", "Style", "Restrictions", "License URL"],
+            &rows,
+        )?;
+    }
+    Ok(())
+}
+
+/// Validates the given paths (else `--dir`, else the system font directories)
+///
+/// Fails with exit code 1 when any face has an error-level finding.
+fn validate(args: &Args, paths: &[PathBuf]) -> Result<ExitCode, String> {
+    let paths = match (paths.is_empty(), args.dirs.is_empty()) {
+        (false, _) => paths.to_vec(),
+        (true, false) => args.dirs.clone(),
+        (true, true) => DirectorySource::system().dirs().to_vec(),
+    };
+    let reports = validate_paths(&paths).map_err(|e| e.to_string())?;
+
+    let stdout = io::stdout();
+    match print_reports(&mut stdout.lock(), &reports) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.to_string()),
+        _ => {}
+    }
+    if reports
+        .iter()
+        .any(|r| r.severity() == Some(Severity::Error))
+    {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Compares two snapshot files, failing with exit code 1 if they differ
+fn diff(args: &Args, old: &Path, new: &Path) -> Result<ExitCode, String> {
+    let read = |path: &Path| {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Snapshot::from_json(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    let diff = diff_snapshots(&read(old)?, &read(new)?);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = match args.format {
+        None => print_diff(&mut out, &diff),
+        Some(ExportFormat::Json) => match serde_json::to_writer_pretty(&mut out, &diff) {
+            Ok(()) => writeln!(out),
+            Err(e) => Err(e.into()),
+        },
+        Some(_) => return Err("diff can only be written as text or json".to_string()),
+    };
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.to_string()),
+        _ => {}
+    }
+    Ok(if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Prints each family's differences as `+`, `-` and `~` lines, then totals
+fn print_diff(out: &mut impl Write, diff: &SnapshotDiff) -> io::Result<()> {
+    writeln!(out, "--- {} {}", diff.old_host, diff.old_timestamp)?;
+    writeln!(out, "+++ {} {}", diff.new_host, diff.new_timestamp)?;
+    for family in &diff.families {
+        writeln!(out, "{}", family.family)?;
+        for face in &family.removed {
+            writeln!(out, "  - {} ({})", face.style, face.location)?;
+        }
+        for face in &family.added {
+            writeln!(out, "  + {} ({})", face.style, face.location)?;
+        }
+        for face in &family.changed {
+            writeln!(out, "  ~ {}", face.style)?;
+            for change in &face.changes {
+                writeln!(
+                    out,
+                    "      {}: {} -> {}",
+                    change.field, change.old, change.new
+                )?;
+            }
+        }
+    }
+    let (added, removed, changed) = diff.counts();
+    writeln!(
+        out,
+        "{} added, {} removed, {} changed",
+        added, removed, changed
+    )
+}
+
+/// Prints the findings of each face that has any, then a summary line
+fn print_reports(out: &mut impl Write, reports: &[FontReport]) -> io::Result<()> {
+    for report in reports.iter().filter(|r| !r.findings.is_empty()) {
+        match &report.name {
+            Some(name) => writeln!(out, "{} ({})", report.location(), name)?,
+            None => writeln!(out, "{}", report.location())?,
+        }
+        for finding in &report.findings {
+            writeln!(out, "  {}", finding)?;
+        }
+    }
+
+    let total = |severity| reports.iter().map(|r| r.count(severity)).sum::<usize>();
+    writeln!(
+        out,
+        "{} faces checked: {} errors, {} warnings, {} info",
+        reports.len(),
+        total(Severity::Error),
+        total(Severity::Warning),
+        total(Severity::Info)
+    )
+}
+
+/// Turns the `covers` argument into query characters
+///
+/// Arguments starting with `U+` or `0x` are code point ranges; anything
+/// else is sample text.
+fn covers_query(text: &str) -> Result<Vec<(char, Option<char>)>, String> {
+    let query = if is_codepoint_list(text) {
+        let chars = parse_codepoints(text).map_err(|e| e.to_string())?;
+        chars.into_iter().map(|c| (c, None)).collect()
+    } else {
+        text_characters(text)
+    };
+    if query.is_empty() {
+        return Err("covers requires at least one character".to_string());
+    }
+    Ok(query)
+}
+
+/// Prints one row per matching font with its coverage and missing characters
+fn print_coverage(
+    out: &mut impl Write,
+    fonts: &[FontInfo],
+    matches: &[CoverageMatch],
+) -> io::Result<()> {
+    // Long lists (e.g. a whole Unicode block) are cut short after this many
+    const MAX_MISSING: usize = 12;
+
+    let rows: Vec<Vec<String>> = matches
+        .iter()
+        .map(|m| {
+            let font = &fonts[m.index];
+            let mut missing: Vec<String> = m
+                .missing
+                .iter()
+                .take(MAX_MISSING)
+                .map(|&c| format!("U+{:04X}", c as u32))
+                .collect();
+            if m.missing.len() > MAX_MISSING {
+                missing.push(format!("(+{} more)", m.missing.len() - MAX_MISSING));
+            }
+            vec![
+                font.family_name.clone(),
+                font.style_name.clone(),
+                format!("{:.1}%", m.percent()),
+                missing.join(" "),
+            ]
+        })
+        .collect();
+    table::print_rows(out, &["Font Family", "Style", "Coverage", "Missing"], &rows)
+}
+
+/// Prints the details of each face, separated by blank lines
+fn show_faces(out: &mut impl Write, faces: &[&FontInfo]) -> io::Result<()> {
+    for (i, face) in faces.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        table::print_details(out, face)?;
+        if let Some(os2) = &face.os2 {
+            writeln!(out, "OS/2:")?;
+            table::print_os2(out, os2)?;
+        }
+        if !face.variable_axes.is_empty() {
+            writeln!(out, "Axes:")?;
+            table::print_axes(out, &face.variable_axes)?;
+        }
+        if !face.named_instances.is_empty() {
+            writeln!(out, "Named instances:")?;
+            table::print_instances(out, &face.named_instances)?;
+        }
+        if !face.layout.is_empty() {
+            writeln!(out, "Layout:")?;
+            table::print_layout(out, &face.layout)?;
+        }
+        if !face.tables.is_empty() {
+            writeln!(out, "Tables:")?;
+            table::print_tables(out, face.sfnt_version, &face.tables)?;
+        }
+        if !face.names.is_empty() {
+            writeln!(out, "Names:")?;
+            table::print_names(out, &face.names)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! Plain-text output formatting
+
+use std::io::{self, Write};
+
+use fontenum::{
+    Column, FontInfo, Layout, NameTable, NamedInstance, Os2, TableRecord, Tag, VariationAxis,
+};
+
+/// Prints fonts as a table with one aligned column per `Column`
+///
+/// Column widths are the widest cell (or heading) in each column;
+/// trailing whitespace is trimmed from every line.
+pub fn print_table<'a>(
+    out: &mut impl Write,
+    columns: &[Column],
+    fonts: impl IntoIterator<Item = &'a FontInfo>,
+) -> io::Result<()> {
+    let headers: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    let rows: Vec<Vec<String>> = fonts
+        .into_iter()
+        .map(|f| columns.iter().map(|c| c.value(f)).collect())
+        .collect();
+    print_rows(out, &headers, &rows)
+}
+
+/// Prints preformatted rows under the given headings, aligned like `print_table`
+pub fn print_rows(out: &mut impl Write, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+
+    write_row(out, &headers, &widths)?;
+    write_row(out, &rule, &widths)?;
+    for row in rows {
+        write_row(out, row, &widths)?;
+    }
+    Ok(())
+}
+
+/// Columns left out of `print_details` because `show` prints their
+/// tables in full in a section of their own
+const SECTION_COLUMNS: &[Column] = &[
+    Column::Vendor,
+    Column::FsSelection,
+    Column::FsType,
+    Column::FamilyClass,
+    Column::Panose,
+    Column::UnicodeRanges,
+    Column::CodePages,
+    Column::SfntVersion,
+    Column::Tables,
+];
+
+/// Prints every column of one font as `Heading: value` lines
+pub fn print_details(out: &mut impl Write, font: &FontInfo) -> io::Result<()> {
+    let columns = Column::ALL.iter().filter(|c| !SECTION_COLUMNS.contains(c));
+    let width = columns.clone().map(|c| c.header().len()).max().unwrap_or(0);
+    for column in columns {
+        let line = format!(
+            "{:<width$}  {}",
+            format!("{}:", column.header()),
+            column.value(font),
+            width = width + 1
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Prints every name record as `ID [language]: value` lines
+///
+/// Line breaks inside values (common in license texts) are folded into
+/// spaces so each record stays on one line.
+pub fn print_names(out: &mut impl Write, names: &NameTable) -> io::Result<()> {
+    for (id, localized) in names.iter() {
+        for name in localized {
+            let value = name.value.split_whitespace().collect::<Vec<_>>().join(" ");
+            writeln!(out, "  {} [{}]: {}", id, name.language, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints each variation axis as `tag min-max (default N) Name`
+pub fn print_axes(out: &mut impl Write, axes: &[VariationAxis]) -> io::Result<()> {
+    for axis in axes {
+        let hidden = if axis.hidden { " [hidden]" } else { "" };
+        writeln!(
+            out,
+            "  {} (default {}) {}{}",
+            axis, axis.default_value, axis.name, hidden
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints each named instance as `Name: coordinates [PostScript name]`
+pub fn print_instances(out: &mut impl Write, instances: &[NamedInstance]) -> io::Result<()> {
+    for instance in instances {
+        let line = format!(
+            "  {}: {} {}",
+            instance.name,
+            instance,
+            instance.postscript_name.as_deref().unwrap_or("")
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Prints the decoded OS/2 table as indented `Field: value` lines
+pub fn print_os2(out: &mut impl Write, os2: &Os2) -> io::Result<()> {
+    let panose: Vec<String> = os2.panose.0.iter().map(|d| d.to_string()).collect();
+    writeln!(out, "  Version: {}", os2.version)?;
+    writeln!(out, "  usWeightClass: {}", os2.weight_class)?;
+    writeln!(out, "  usWidthClass: {}", os2.width_class)?;
+    writeln!(
+        out,
+        "  fsSelection: 0x{:04X} {}",
+        os2.fs_selection.0,
+        os2.fs_selection.names().join(" ")
+    )?;
+    writeln!(
+        out,
+        "  fsType: 0x{:04X} {}",
+        os2.fs_type.0,
+        os2.fs_type.permission()
+    )?;
+    writeln!(
+        out,
+        "  sFamilyClass: {}.{} ({})",
+        os2.family_class.class(),
+        os2.family_class.subclass(),
+        os2.family_class.class_name()
+    )?;
+    writeln!(
+        out,
+        "  PANOSE: {} ({})",
+        panose.join(" "),
+        os2.panose.family_name()
+    )?;
+    writeln!(out, "  achVendID: {}", os2.vendor_id)?;
+    writeln!(out, "  Unicode Ranges: {}", os2.unicode_ranges().join(", "))?;
+    writeln!(out, "  Code Pages: {}", os2.code_pages().join(", "))?;
+    Ok(())
+}
+
+/// Prints each language system as `script [language]: features`
+///
+/// The script's default language system is shown as `[default]`.
+pub fn print_layout(out: &mut impl Write, layout: &Layout) -> io::Result<()> {
+    for system in &layout.systems {
+        let language = system
+            .language
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| "default".to_string());
+        let features: Vec<String> = system.features.iter().map(|f| f.to_string()).collect();
+        let script = system.script.to_string();
+        let line = format!(
+            "  {} [{}]: {}",
+            script.trim_end(),
+            language.trim_end(),
+            features.join(" ")
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Prints the sfnt version and every table directory entry, one per line
+///
+/// Offsets and lengths are decimal; checksums are shown in hex as stored.
+pub fn print_tables(
+    out: &mut impl Write,
+    sfnt_version: Option<Tag>,
+    tables: &[TableRecord],
+) -> io::Result<()> {
+    if let Some(version) = sfnt_version {
+        // 'OTTO' and 'true' are tags; TrueType's 1.0 is a number
+        if version.0.iter().all(|b| b.is_ascii_graphic()) {
+            writeln!(out, "  sfnt version: '{}'", version)?;
+        } else {
+            writeln!(
+                out,
+                "  sfnt version: 0x{:08X}",
+                u32::from_be_bytes(version.0)
+            )?;
+        }
+    }
+    let rows: Vec<Vec<String>> = tables
+        .iter()
+        .map(|t| {
+            vec![
+                t.tag.to_string(),
+                t.offset.to_string(),
+                t.length.to_string(),
+                format!("0x{:08X}", t.checksum),
+            ]
+        })
+        .collect();
+
+    // Indent the whole table to line up with the other detail sections
+    let mut table = Vec::new();
+    print_rows(&mut table, &["Tag", "Offset", "Length", "Checksum"], &rows)?;
+    for line in String::from_utf8_lossy(&table).lines() {
+        writeln!(out, "  {}", line)?;
+    }
+    Ok(())
+}
+
+fn write_row(out: &mut impl Write, cells: &[String], widths: &[usize]) -> io::Result<()> {
+    let mut line = String::new();
+    for (i, cell) in cells.iter().enumerate() {
+        // Pad by characters rather than bytes so non-ASCII names line up
+        let pad = widths[i] - cell.chars().count();
+        line.push_str(cell);
+        line.push_str(&" ".repeat(pad + 2));
+    }
+    writeln!(out, "{}", line.trim_end())
+}
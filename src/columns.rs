@@ -0,0 +1,459 @@
+//! Tabular view of `FontInfo` - the columns shared by the list front ends
+
+use crate::{EmbeddingRights, FontInfo, NameId, Tag};
+
+/// A displayable property of a font
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Family,
+    Style,
+    Weight,
+    Width,
+    Italic,
+    Fixed,
+    FilePath,
+    VariableAxes,
+    PostScriptName,
+    Instance,
+    Container,
+    UnitsPerEm,
+    Ascender,
+    Descender,
+    LineGap,
+    TypoAscender,
+    TypoDescender,
+    TypoLineGap,
+    WinAscent,
+    WinDescent,
+    XHeight,
+    CapHeight,
+    AvgCharWidth,
+    UnderlinePosition,
+    UnderlineThickness,
+    ItalicAngle,
+    LineHeight,
+    Clipping,
+    Color,
+    Palettes,
+    Outline,
+    Glyphs,
+    Scripts,
+    Features,
+    Embedding,
+    MonospaceSignals,
+    FaceIndex,
+    Variable,
+    NamedInstances,
+    FullName,
+    Version,
+    Vendor,
+    MacStyle,
+    FsSelection,
+    FsType,
+    FamilyClass,
+    Panose,
+    UnicodeRanges,
+    CodePages,
+    Characters,
+    SfntVersion,
+    Tables,
+}
+
+impl Column {
+    /// Every column, in display order
+    pub const ALL: &'static [Column] = &[
+        Column::Family,
+        Column::Style,
+        Column::Weight,
+        Column::Width,
+        Column::Italic,
+        Column::Fixed,
+        Column::FilePath,
+        Column::VariableAxes,
+        Column::PostScriptName,
+        Column::Instance,
+        Column::Container,
+        Column::UnitsPerEm,
+        Column::Ascender,
+        Column::Descender,
+        Column::LineGap,
+        Column::TypoAscender,
+        Column::TypoDescender,
+        Column::TypoLineGap,
+        Column::WinAscent,
+        Column::WinDescent,
+        Column::XHeight,
+        Column::CapHeight,
+        Column::AvgCharWidth,
+        Column::UnderlinePosition,
+        Column::UnderlineThickness,
+        Column::ItalicAngle,
+        Column::LineHeight,
+        Column::Clipping,
+        Column::Color,
+        Column::Palettes,
+        Column::Outline,
+        Column::Glyphs,
+        Column::Scripts,
+        Column::Features,
+        Column::Embedding,
+        Column::MonospaceSignals,
+        Column::FaceIndex,
+        Column::Variable,
+        Column::NamedInstances,
+        Column::FullName,
+        Column::Version,
+        Column::Vendor,
+        Column::MacStyle,
+        Column::FsSelection,
+        Column::FsType,
+        Column::FamilyClass,
+        Column::Panose,
+        Column::UnicodeRanges,
+        Column::CodePages,
+        Column::Characters,
+        Column::SfntVersion,
+        Column::Tables,
+    ];
+
+    /// Columns shown by default in list views
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Family,
+        Column::Style,
+        Column::Weight,
+        Column::Width,
+        Column::Italic,
+        Column::Fixed,
+        Column::FilePath,
+        Column::VariableAxes,
+    ];
+
+    /// Column heading, as shown in the ListView
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Family => "Font Family",
+            Column::Style => "Style",
+            Column::Weight => "Weight",
+            Column::Width => "Width",
+            Column::Italic => "Italic",
+            Column::Fixed => "Fixed",
+            Column::FilePath => "File Path",
+            Column::VariableAxes => "Variable Axes",
+            Column::PostScriptName => "PostScript Name",
+            Column::Instance => "Instance",
+            Column::Container => "Container",
+            Column::UnitsPerEm => "Units/Em",
+            Column::Ascender => "Ascender",
+            Column::Descender => "Descender",
+            Column::LineGap => "Line Gap",
+            Column::TypoAscender => "Typo Ascender",
+            Column::TypoDescender => "Typo Descender",
+            Column::TypoLineGap => "Typo Line Gap",
+            Column::WinAscent => "Win Ascent",
+            Column::WinDescent => "Win Descent",
+            Column::XHeight => "x-Height",
+            Column::CapHeight => "Cap Height",
+            Column::AvgCharWidth => "Avg Char Width",
+            Column::UnderlinePosition => "Underline Position",
+            Column::UnderlineThickness => "Underline Thickness",
+            Column::ItalicAngle => "Italic Angle",
+            Column::LineHeight => "Line Height",
+            Column::Clipping => "Clipping",
+            Column::Color => "Color",
+            Column::Palettes => "Palettes",
+            Column::Outline => "Outline",
+            Column::Glyphs => "Glyphs",
+            Column::Scripts => "Scripts",
+            Column::Features => "Features",
+            Column::Embedding => "Embedding",
+            Column::MonospaceSignals => "Monospace Signals",
+            Column::FaceIndex => "Face Index",
+            Column::Variable => "Variable",
+            Column::NamedInstances => "Named Instances",
+            Column::FullName => "Full Name",
+            Column::Version => "Version",
+            Column::Vendor => "Vendor",
+            Column::MacStyle => "macStyle",
+            Column::FsSelection => "fsSelection",
+            Column::FsType => "fsType",
+            Column::FamilyClass => "Family Class",
+            Column::Panose => "PANOSE",
+            Column::UnicodeRanges => "Unicode Ranges",
+            Column::CodePages => "Code Pages",
+            Column::Characters => "Characters",
+            Column::SfntVersion => "sfnt Version",
+            Column::Tables => "Tables",
+        }
+    }
+
+    /// Short machine-friendly name, e.g. for command-line column lists
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Family => "family",
+            Column::Style => "style",
+            Column::Weight => "weight",
+            Column::Width => "width",
+            Column::Italic => "italic",
+            Column::Fixed => "fixed",
+            Column::FilePath => "path",
+            Column::VariableAxes => "axes",
+            Column::PostScriptName => "psname",
+            Column::Instance => "instance",
+            Column::Container => "container",
+            Column::UnitsPerEm => "upm",
+            Column::Ascender => "ascender",
+            Column::Descender => "descender",
+            Column::LineGap => "linegap",
+            Column::TypoAscender => "typoascender",
+            Column::TypoDescender => "typodescender",
+            Column::TypoLineGap => "typolinegap",
+            Column::WinAscent => "winascent",
+            Column::WinDescent => "windescent",
+            Column::XHeight => "xheight",
+            Column::CapHeight => "capheight",
+            Column::AvgCharWidth => "avgwidth",
+            Column::UnderlinePosition => "underlinepos",
+            Column::UnderlineThickness => "underlinethickness",
+            Column::ItalicAngle => "italicangle",
+            Column::LineHeight => "lineheight",
+            Column::Clipping => "clipping",
+            Column::Color => "color",
+            Column::Palettes => "palettes",
+            Column::Outline => "outline",
+            Column::Glyphs => "glyphs",
+            Column::Scripts => "scripts",
+            Column::Features => "features",
+            Column::Embedding => "embedding",
+            Column::MonospaceSignals => "monospace",
+            Column::FaceIndex => "faceindex",
+            Column::Variable => "variable",
+            Column::NamedInstances => "namedinstances",
+            Column::FullName => "fullname",
+            Column::Version => "version",
+            Column::Vendor => "vendor",
+            Column::MacStyle => "macstyle",
+            Column::FsSelection => "fsselection",
+            Column::FsType => "fstype",
+            Column::FamilyClass => "familyclass",
+            Column::Panose => "panose",
+            Column::UnicodeRanges => "unicoderanges",
+            Column::CodePages => "codepages",
+            Column::Characters => "chars",
+            Column::SfntVersion => "sfntversion",
+            Column::Tables => "tables",
+        }
+    }
+
+    /// Looks up a column by its short name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Column> {
+        Column::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Formats this column's value for a font
+    pub fn value(self, font: &FontInfo) -> String {
+        match self {
+            Column::Family => font.family_name.clone(),
+            Column::Style => font.style_name.clone(),
+            Column::Weight => font.weight.to_string(),
+            Column::Width => width_value(font.width),
+            Column::Italic => yes_no(font.italic),
+            Column::Fixed => yes_no(font.fixed_pitch),
+            Column::FilePath => font.location(),
+            Column::VariableAxes => font.variable_axes_string(),
+            Column::PostScriptName => postscript_name(font),
+            Column::Instance => font
+                .instance
+                .as_ref()
+                .map(|i| i.to_string())
+                .unwrap_or_default(),
+            Column::Container => font.container.to_string(),
+            Column::Color => color_value(font),
+            Column::Palettes => palettes_value(font),
+            Column::Outline => optional(font.outline),
+            Column::Glyphs => optional(font.glyph_count),
+            Column::Scripts => tag_list(font.layout.scripts()),
+            Column::Features => tag_list(font.layout.features()),
+            Column::Embedding => optional(EmbeddingRights::of(font)),
+            Column::MonospaceSignals => monospace_value(font),
+            Column::FaceIndex => optional(font.face_index),
+            Column::Variable => yes_no(font.is_variable),
+            Column::NamedInstances => font.named_instances.len().to_string(),
+            Column::FullName => font
+                .names
+                .best(NameId::FULL_NAME)
+                .unwrap_or_default()
+                .to_string(),
+            Column::Version => font
+                .names
+                .best(NameId::VERSION)
+                .unwrap_or_default()
+                .to_string(),
+            Column::MacStyle => font
+                .mac_style
+                .map(|m| format!("0x{:04X}", m))
+                .unwrap_or_default(),
+            Column::Characters => font.coverage.len().to_string(),
+            Column::SfntVersion => font
+                .sfnt_version
+                .map(sfnt_version_value)
+                .unwrap_or_default(),
+            Column::Tables => tag_list(font.tables.iter().map(|t| t.tag)),
+            Column::Vendor
+            | Column::FsSelection
+            | Column::FsType
+            | Column::FamilyClass
+            | Column::Panose
+            | Column::UnicodeRanges
+            | Column::CodePages => os2_value(self, font),
+            Column::UnitsPerEm
+            | Column::Ascender
+            | Column::Descender
+            | Column::LineGap
+            | Column::TypoAscender
+            | Column::TypoDescender
+            | Column::TypoLineGap
+            | Column::WinAscent
+            | Column::WinDescent
+            | Column::XHeight
+            | Column::CapHeight
+            | Column::AvgCharWidth
+            | Column::UnderlinePosition
+            | Column::UnderlineThickness
+            | Column::ItalicAngle
+            | Column::LineHeight
+            | Column::Clipping => metric_value(self, font),
+        }
+    }
+}
+
+/// Formats one of the metrics columns; empty when the source has no metrics
+fn metric_value(column: Column, font: &FontInfo) -> String {
+    let m = match &font.metrics {
+        Some(m) => m,
+        None => return String::new(),
+    };
+    match column {
+        Column::UnitsPerEm => m.units_per_em.to_string(),
+        Column::Ascender => m.ascender.to_string(),
+        Column::Descender => m.descender.to_string(),
+        Column::LineGap => m.line_gap.to_string(),
+        Column::TypoAscender => optional(m.typo_ascender),
+        Column::TypoDescender => optional(m.typo_descender),
+        Column::TypoLineGap => optional(m.typo_line_gap),
+        Column::WinAscent => optional(m.win_ascent),
+        Column::WinDescent => optional(m.win_descent),
+        Column::XHeight => optional(m.x_height),
+        Column::CapHeight => optional(m.cap_height),
+        Column::AvgCharWidth => optional(m.avg_char_width),
+        Column::UnderlinePosition => m.underline_position.to_string(),
+        Column::UnderlineThickness => m.underline_thickness.to_string(),
+        Column::ItalicAngle => m.italic_angle.to_string(),
+        Column::LineHeight => format!("{}%", m.percent_of_em(m.line_height())),
+        Column::Clipping => yes_no(m.clips()),
+        _ => String::new(),
+    }
+}
+
+/// Formats one of the OS/2 columns; empty without an OS/2 table
+fn os2_value(column: Column, font: &FontInfo) -> String {
+    let Some(os2) = &font.os2 else {
+        return String::new();
+    };
+    match column {
+        Column::Vendor => os2.vendor_id.to_string().trim_end().to_string(),
+        Column::FsSelection => format!("0x{:04X}", os2.fs_selection.0),
+        Column::FsType => format!("0x{:04X}", os2.fs_type.0),
+        Column::FamilyClass => format!(
+            "{}.{}",
+            os2.family_class.class(),
+            os2.family_class.subclass()
+        ),
+        Column::Panose => os2
+            .panose
+            .0
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        Column::UnicodeRanges => os2.unicode_ranges().join(", "),
+        Column::CodePages => os2.code_pages().join(", "),
+        _ => String::new(),
+    }
+}
+
+/// 'OTTO' and 'true' as text, TrueType's 1.0 as hex
+fn sfnt_version_value(version: Tag) -> String {
+    if version.0.iter().all(|b| b.is_ascii_graphic()) {
+        version.to_string()
+    } else {
+        format!("0x{:08X}", u32::from_be_bytes(version.0))
+    }
+}
+
+/// Color technologies, e.g. `COLRv0, COLRv1`; empty for monochrome fonts
+fn color_value(font: &FontInfo) -> String {
+    font.color
+        .formats
+        .iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// CPAL palettes as `count x entries`, e.g. `3 x 16`
+fn palettes_value(font: &FontInfo) -> String {
+    match font.color.palette_count {
+        0 => String::new(),
+        count => format!("{} x {}", count, font.color.palette_entry_count),
+    }
+}
+
+/// The monospace signals, plus those that disagree with the verdict
+///
+/// E.g. `hmtx=yes post=no panose=yes (conflict: post)`.
+fn monospace_value(font: &FontInfo) -> String {
+    let conflicts = font.monospace.conflicts();
+    if conflicts.is_empty() {
+        font.monospace.to_string()
+    } else {
+        format!("{} (conflict: {})", font.monospace, conflicts.join(", "))
+    }
+}
+
+/// Tags separated by spaces, e.g. `kern liga tnum`
+fn tag_list(tags: impl IntoIterator<Item = Tag>) -> String {
+    tags.into_iter()
+        .map(|t| t.to_string().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The instance's PostScript name on expanded rows, else name ID 6
+fn postscript_name(font: &FontInfo) -> String {
+    font.instance
+        .as_ref()
+        .and_then(|i| i.postscript_name.as_deref())
+        .or_else(|| font.names.best(NameId::POSTSCRIPT_NAME))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Width class as a number, or empty when the source doesn't report it
+fn width_value(width: i32) -> String {
+    if width == 0 {
+        String::new()
+    } else {
+        width.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes" } else { "No" }.to_string()
+}
@@ -0,0 +1,180 @@
+//! "Which fonts can render this text" queries over cmap coverage
+//!
+//! The query is a set of characters, given either as sample text
+//! (`"Привет, мир"`) or as code point ranges (`U+0400-04FF,U+0500`).
+//! Each font's `Coverage` is checked character by character, so the
+//! result says not only which fonts qualify but what each one is missing.
+//!
+//! Variation selectors (U+FE00-FE0F, U+E0100-E01EF) in sample text are
+//! checked as sequences with the preceding character, using the fonts'
+//! cmap format 14 data.
+
+use crate::{Error, FontInfo, Result};
+
+/// Prefixes of a hexadecimal code point
+const CODEPOINT_PREFIXES: &[&str] = &["U+", "u+", "0x", "0X"];
+
+/// How well one font covers a query
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageMatch {
+    pub index: usize,       // Index of the font in the list that was queried
+    pub covered: usize,     // Number of query characters the font covers
+    pub total: usize,       // Number of characters in the query
+    pub missing: Vec<char>, // Query characters the font does not cover
+}
+
+impl CoverageMatch {
+    /// Covered share of the query, 0-100
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.covered as f32 * 100.0 / self.total as f32
+        }
+    }
+}
+
+/// The distinct characters of sample text that need a glyph
+///
+/// Control characters (line breaks, tabs) are skipped; each remaining
+/// character appears once, in order of first appearance. A variation
+/// selector is kept together with its base as a `(base, selector)` pair.
+pub fn text_characters(text: &str) -> Vec<(char, Option<char>)> {
+    let mut result: Vec<(char, Option<char>)> = Vec::new();
+    let mut chars = text.chars().filter(|c| !c.is_control()).peekable();
+    while let Some(c) = chars.next() {
+        let selector = chars.next_if(|&next| is_variation_selector(next));
+        if !result.contains(&(c, selector)) {
+            result.push((c, selector));
+        }
+    }
+    result
+}
+
+/// True if `text` starts like a code point list (`U+`/`0x`) rather than
+/// sample text
+pub fn is_codepoint_list(text: &str) -> bool {
+    CODEPOINT_PREFIXES
+        .iter()
+        .any(|prefix| text.starts_with(prefix))
+}
+
+/// Parses code point ranges such as `U+0400-04FF, U+20AC, 0x1F600-1F64F`
+///
+/// Ranges are separated by commas or whitespace; the `U+`/`0x` prefix is
+/// optional and the values are hexadecimal.
+pub fn parse_codepoints(spec: &str) -> Result<Vec<char>> {
+    let hex = |s: &str| {
+        let digits = CODEPOINT_PREFIXES
+            .iter()
+            .fold(s, |digits, prefix| digits.trim_start_matches(prefix));
+        u32::from_str_radix(digits, 16)
+            .map_err(|_| Error::Parse(format!("invalid code point '{}'", s)))
+    };
+
+    let mut chars = Vec::new();
+    for part in spec
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+    {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (hex(first)?, hex(last)?),
+            None => (hex(part)?, hex(part)?),
+        };
+        if first > last || last > 0x10FFFF {
+            return Err(Error::Parse(format!("invalid code point range '{}'", part)));
+        }
+        // Surrogates are not characters and are skipped
+        chars.extend((first..=last).filter_map(char::from_u32));
+    }
+    Ok(chars)
+}
+
+/// Finds the fonts covering at least `min_percent` of the query
+///
+/// `query` is a list of characters, optionally paired with a variation
+/// selector (see `text_characters`; plain code point lists map each `c`
+/// to `(c, None)`). Fonts without coverage data (GDI, or fonts without a
+/// Unicode cmap) are never returned. Results are sorted by coverage,
+/// best first; ties keep the input order.
+pub fn fonts_covering(
+    fonts: &[FontInfo],
+    query: &[(char, Option<char>)],
+    min_percent: f32,
+) -> Vec<CoverageMatch> {
+    let mut matches: Vec<CoverageMatch> = fonts
+        .iter()
+        .enumerate()
+        .filter(|(_, font)| !font.coverage.is_empty())
+        .map(|(index, font)| {
+            let mut missing = Vec::new();
+            let mut uncovered = 0;
+            let mut check = |c: char, covered: bool| {
+                if !covered {
+                    uncovered += 1;
+                    if !missing.contains(&c) {
+                        missing.push(c);
+                    }
+                }
+            };
+            for &(c, selector) in query {
+                check(c, font.coverage.contains(c));
+                if let Some(vs) = selector {
+                    // The sequence renders if it is listed in format 14, or if
+                    // the font maps the selector itself (e.g. as a default-ignorable)
+                    check(
+                        vs,
+                        font.coverage.supports_sequence(c, vs) || font.coverage.contains(vs),
+                    );
+                }
+            }
+            let total = query.len() + query.iter().filter(|(_, vs)| vs.is_some()).count();
+            CoverageMatch {
+                index,
+                covered: total - uncovered,
+                total,
+                missing,
+            }
+        })
+        .filter(|m| m.percent() >= min_percent)
+        .collect();
+
+    matches.sort_by_key(|m| std::cmp::Reverse(m.covered));
+    matches
+}
+
+fn is_variation_selector(c: char) -> bool {
+    matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_point_lists_and_sample_text() {
+        assert!(is_codepoint_list("U+0400-04FF"));
+        assert!(is_codepoint_list("0x1F600"));
+        assert!(!is_codepoint_list("Unicode"));
+        assert!(!is_codepoint_list("0"));
+
+        let chars = parse_codepoints("U+0041-0043, 0x20AC u+D7FF-E000").unwrap();
+        assert_eq!(chars, ['A', 'B', 'C', '€', '\u{D7FF}', '\u{E000}']);
+        assert!(parse_codepoints("U+0043-0041").is_err());
+        assert!(parse_codepoints("U+110000").is_err());
+        assert!(parse_codepoints("U+XYZ").is_err());
+    }
+
+    #[test]
+    fn sample_text_keeps_variation_sequences() {
+        let chars = text_characters("a\u{2764}\u{FE0F}a\n\u{2764}");
+        assert_eq!(
+            chars,
+            [
+                ('a', None),
+                ('\u{2764}', Some('\u{FE0F}')),
+                ('\u{2764}', None)
+            ]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Embedding permission and licensing audit
+//!
+//! Whether a font may be embedded in a document (e.g. a PDF) is declared
+//! by the OS/2 fsType field: a usage permission (installable, editable,
+//! preview & print or restricted) plus the no-subsetting and bitmap-only
+//! flags. The license text and URL name records (IDs 13 and 14) are shown
+//! next to it, since fsType alone does not replace reading the license.
+//!
+//! Only sources that decode the OS/2 table (the directory source) know
+//! the permission; other fonts are audited as unknown.
+
+use std::fmt;
+
+use crate::{Embedding, FontInfo, NameId};
+
+/// What a font allows when embedded, with its license records
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbeddingRights {
+    pub permission: Embedding,
+    pub no_subsetting: bool,         // The whole font must be embedded
+    pub bitmap_only: bool,           // Only bitmaps may be embedded, not outlines
+    pub license: Option<String>,     // Name ID 13
+    pub license_url: Option<String>, // Name ID 14
+}
+
+impl EmbeddingRights {
+    /// Reads a font's fsType and license names; None without an OS/2 table
+    pub fn of(font: &FontInfo) -> Option<Self> {
+        let fs_type = font.os2.as_ref()?.fs_type;
+        Some(EmbeddingRights {
+            permission: fs_type.permission(),
+            no_subsetting: fs_type.no_subsetting(),
+            bitmap_only: fs_type.bitmap_only(),
+            license: font.names.best(NameId::LICENSE).map(str::to_string),
+            license_url: font.names.best(NameId::LICENSE_URL).map(str::to_string),
+        })
+    }
+
+    /// True if a document may carry the font's outlines
+    ///
+    /// Bitmap-only fonts can be embedded, but not as scalable outlines.
+    pub fn allows_outline_embedding(&self) -> bool {
+        self.permission.allows_embedding() && !self.bitmap_only
+    }
+}
+
+impl fmt::Display for EmbeddingRights {
+    /// Formats as the permission plus restrictions, e.g. `Preview & Print, no subsetting`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.permission)?;
+        if self.no_subsetting {
+            write!(f, ", no subsetting")?;
+        }
+        if self.bitmap_only {
+            write!(f, ", bitmap only")?;
+        }
+        Ok(())
+    }
+}
+
+/// The fonts sharing one permission level
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditGroup {
+    pub permission: Option<Embedding>, // None for fonts without an OS/2 table
+    pub fonts: Vec<usize>,             // Indices into the audited list, in list order
+}
+
+/// Groups fonts by embedding permission
+///
+/// Groups are ordered from least to most restrictive, with fonts of unknown
+/// permission last; empty groups are left out.
+pub fn embedding_audit(fonts: &[FontInfo]) -> Vec<AuditGroup> {
+    let levels = Embedding::ALL
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(std::iter::once(None));
+    levels
+        .map(|permission| AuditGroup {
+            permission,
+            fonts: fonts
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| EmbeddingRights::of(f).map(|r| r.permission) == permission)
+                .map(|(i, _)| i)
+                .collect(),
+        })
+        .filter(|group| !group.fonts.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FsType, NameTable, Os2};
+
+    fn font(fs_type: Option<u16>) -> FontInfo {
+        FontInfo {
+            os2: fs_type.map(|bits| Os2 {
+                fs_type: FsType(bits),
+                ..Default::default()
+            }),
+            names: NameTable::english(&[
+                (NameId::LICENSE, "SIL Open Font License 1.1"),
+                (NameId::LICENSE_URL, "https://openfontlicense.org"),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rights_from_fs_type_and_names() {
+        let rights = EmbeddingRights::of(&font(Some(0x0304))).unwrap();
+        assert_eq!(rights.permission, Embedding::PreviewAndPrint);
+        assert_eq!(
+            rights.to_string(),
+            "Preview & Print, no subsetting, bitmap only"
+        );
+        assert!(!rights.allows_outline_embedding());
+        assert_eq!(rights.license.as_deref(), Some("SIL Open Font License 1.1"));
+        assert_eq!(
+            rights.license_url.as_deref(),
+            Some("https://openfontlicense.org")
+        );
+
+        let rights = EmbeddingRights::of(&font(Some(0))).unwrap();
+        assert_eq!(rights.to_string(), "Installable");
+        assert!(rights.allows_outline_embedding());
+        assert_eq!(EmbeddingRights::of(&font(None)), None);
+    }
+
+    #[test]
+    fn audit_groups_least_restrictive_first() {
+        let fonts = [
+            font(Some(0x0002)),
+            font(None),
+            font(Some(0)),
+            font(Some(0x0002)),
+        ];
+        let groups: Vec<(Option<Embedding>, Vec<usize>)> = embedding_audit(&fonts)
+            .into_iter()
+            .map(|g| (g.permission, g.fonts))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (Some(Embedding::Installable), vec![2]),
+                (Some(Embedding::Restricted), vec![0, 3]),
+                (None, vec![1]),
+            ]
+        );
+    }
+}
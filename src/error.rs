@@ -0,0 +1,77 @@
+//! Error type shared by all font sources
+
+use std::fmt;
+use std::io;
+
+/// Errors produced while enumerating fonts
+#[derive(Debug)]
+pub enum Error {
+    /// A Windows API call failed (factory creation, collection lookup, ...)
+    #[cfg(windows)]
+    Windows(windows::core::Error),
+
+    /// Reading a font file or directory failed
+    Io(io::Error),
+
+    /// A font file is not a valid sfnt font or is truncated
+    Parse(String),
+
+    /// An exported inventory could not be written or read back
+    Json(serde_json::Error),
+
+    /// The requested source is not available on this system
+    /// (e.g. the FontSet API before Windows 10)
+    Unsupported(String),
+}
+
+/// Convenience alias used throughout the crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(windows)]
+            Error::Windows(e) => write!(f, "Windows API error: {}", e),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Parse(msg) => write!(f, "malformed font data: {}", msg),
+            Error::Json(e) => write!(f, "JSON error: {}", e),
+            Error::Unsupported(what) => write!(f, "not supported: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(windows)]
+            Error::Windows(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Parse(_) | Error::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        // Failed writes stay I/O errors, e.g. for broken pipe checks
+        if e.is_io() {
+            Error::Io(e.into())
+        } else {
+            Error::Json(e)
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<windows::core::Error> for Error {
+    fn from(e: windows::core::Error) -> Self {
+        Error::Windows(e)
+    }
+}
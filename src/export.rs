@@ -0,0 +1,334 @@
+//! JSON, NDJSON, CSV and TSV export of the font inventory
+//!
+//! An `Inventory` is an enumeration result (all fonts or a filtered
+//! subset) together with where and when it was taken: the source mode,
+//! a UTC timestamp and the host name. It serializes every `FontInfo`
+//! field, so other tools see the same data as the list view.
+//!
+//! - JSON: one pretty-printed object with a `fonts` array
+//! - NDJSON: a header line with the metadata and the font count, then one
+//!   compact `FontInfo` object per line, for streaming and `grep`/`jq`
+//! - CSV/TSV: a heading row and one row per font with a chosen list of
+//!   `Column`s, sorted by family, style and location so that exports of
+//!   the same fonts are identical and diff cleanly
+
+use std::io::Write;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Column, EnumMode, FontInfo, Result};
+
+/// Version of the inventory format, bumped on incompatible changes
+pub const INVENTORY_VERSION: u32 = 1;
+
+/// An exported enumeration result
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub version: u32,      // INVENTORY_VERSION of the writer
+    pub source: EnumMode,  // The source that enumerated the fonts
+    pub timestamp: String, // UTC, RFC 3339, e.g. "2024-05-01T12:00:00Z"
+    pub host: String,      // Machine name
+    pub fonts: Vec<FontInfo>,
+}
+
+/// The NDJSON header line: the inventory without its fonts
+#[derive(Serialize)]
+struct Header<'a> {
+    version: u32,
+    source: EnumMode,
+    timestamp: &'a str,
+    host: &'a str,
+    count: usize,
+}
+
+/// Export formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,   // One pretty-printed document
+    Ndjson, // Header line, then one font per line
+    Csv,    // Comma-separated columns, RFC 4180 quoting
+    Tsv,    // Tab-separated columns
+}
+
+impl ExportFormat {
+    /// Parses a format name as accepted on the command line
+    /// (`json`, `ndjson`, `csv`, `tsv`)
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+            "csv" => Some(ExportFormat::Csv),
+            "tsv" => Some(ExportFormat::Tsv),
+            _ => None,
+        }
+    }
+
+    /// True for the column-based formats, CSV and TSV
+    pub fn is_tabular(self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::Tsv)
+    }
+}
+
+impl Inventory {
+    /// Wraps fonts enumerated by `source` now, on this machine
+    pub fn new(source: EnumMode, fonts: Vec<FontInfo>) -> Self {
+        Inventory {
+            version: INVENTORY_VERSION,
+            source,
+            timestamp: utc_timestamp(SystemTime::now()),
+            host: host_name(),
+            fonts,
+        }
+    }
+
+    /// Writes the inventory in the given format
+    ///
+    /// CSV and TSV have no room for the metadata and get every column;
+    /// use `write_delimited` to choose them.
+    pub fn write(&self, out: &mut impl Write, format: ExportFormat) -> Result<()> {
+        match format {
+            ExportFormat::Csv | ExportFormat::Tsv => {
+                write_delimited(out, &self.fonts, Column::ALL, format)?
+            }
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, self)?;
+                writeln!(out)?;
+            }
+            ExportFormat::Ndjson => {
+                let header = Header {
+                    version: self.version,
+                    source: self.source,
+                    timestamp: &self.timestamp,
+                    host: &self.host,
+                    count: self.fonts.len(),
+                };
+                serde_json::to_writer(&mut *out, &header)?;
+                writeln!(out)?;
+                for font in &self.fonts {
+                    serde_json::to_writer(&mut *out, font)?;
+                    writeln!(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes fonts as CSV or TSV with the given columns
+///
+/// `fonts` can be any selection, e.g. the rows of a filtered list view.
+/// The heading row uses `Column::header`. CSV fields containing commas,
+/// quotes, line breaks or surrounding spaces are quoted; TSV cannot quote,
+/// so tabs and line breaks inside values become spaces. Lines end in
+/// `\n`. `ExportFormat::Json`/`Ndjson` are written as CSV.
+pub fn write_delimited<'a>(
+    out: &mut impl Write,
+    fonts: impl IntoIterator<Item = &'a FontInfo>,
+    columns: &[Column],
+    format: ExportFormat,
+) -> std::io::Result<()> {
+    let tsv = format == ExportFormat::Tsv;
+    let field = |value: &str| {
+        if tsv {
+            tsv_field(value)
+        } else {
+            csv_field(value)
+        }
+    };
+    let separator = if tsv { "\t" } else { "," };
+
+    let headers: Vec<String> = columns.iter().map(|c| field(c.header())).collect();
+    writeln!(out, "{}", headers.join(separator))?;
+
+    let mut rows: Vec<&FontInfo> = fonts.into_iter().collect();
+    rows.sort_by_cached_key(|f| {
+        (
+            f.family_name.to_lowercase(),
+            f.style_name.to_lowercase(),
+            f.location(),
+        )
+    });
+    for font in rows {
+        let values: Vec<String> = columns.iter().map(|c| field(&c.value(font))).collect();
+        writeln!(out, "{}", values.join(separator))?;
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field when needed, doubling embedded quotes
+fn csv_field(value: &str) -> String {
+    let needs_quotes = value.contains([',', '"', '\n', '\r']) || value.trim() != value;
+    if needs_quotes {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Replaces the characters a TSV field cannot hold with spaces
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// The machine name
+///
+/// Windows always sets COMPUTERNAME; elsewhere HOSTNAME is rarely exported,
+/// so the `hostname` command (present on Linux, macOS and Windows) is
+/// asked next, then /etc/hostname.
+pub(crate) fn host_name() -> String {
+    let command = || {
+        let output = Command::new("hostname").output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(command)
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Formats a time as RFC 3339 in UTC, e.g. `2024-05-01T12:00:00Z`
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(seconds: u64) -> String {
+        utc_timestamp(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    fn font(family: &str, style: &str) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            style_name: style.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn host_names_are_trimmed() {
+        let host = host_name();
+        assert!(!host.is_empty());
+        assert_eq!(host, host.trim());
+    }
+
+    #[test]
+    fn utc_timestamps() {
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_714_564_800 + 59), "2024-05-01T12:00:59Z");
+        assert_eq!(at(4_107_542_399), "2100-02-28T23:59:59Z");
+        assert_eq!(at(253_402_300_799), "9999-12-31T23:59:59Z");
+        // Times before the epoch are clamped to it
+        let before = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(utc_timestamp(before), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn json_and_ndjson() {
+        let inventory = Inventory {
+            version: INVENTORY_VERSION,
+            source: EnumMode::Directory,
+            timestamp: "2024-05-01T12:00:00Z".to_string(),
+            host: "build-01".to_string(),
+            fonts: vec![font("Arial", "Regular"), font("Arial", "Bold")],
+        };
+
+        let mut json = Vec::new();
+        inventory.write(&mut json, ExportFormat::Json).unwrap();
+        let read: Inventory = serde_json::from_slice(&json).unwrap();
+        assert_eq!(read, inventory);
+
+        let mut ndjson = Vec::new();
+        inventory.write(&mut ndjson, ExportFormat::Ndjson).unwrap();
+        let text = String::from_utf8(ndjson).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["count"], 2);
+        assert_eq!(header["host"], "build-01");
+        let bold: FontInfo = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(bold, inventory.fonts[1]);
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!(ExportFormat::from_name("JSONL"), Some(ExportFormat::Ndjson));
+        assert_eq!(ExportFormat::from_name("xml"), None);
+        assert!(ExportFormat::Tsv.is_tabular() && !ExportFormat::Json.is_tabular());
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("Arial"), "Arial");
+        assert_eq!(csv_field("Bold, Italic"), "\"Bold, Italic\"");
+        assert_eq!(csv_field("12\" Sans"), "\"12\"\" Sans\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(" padded"), "\" padded\"");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(tsv_field("a\tb\r\nc"), "a b  c");
+    }
+
+    #[test]
+    fn delimited_rows_are_sorted() {
+        let fonts = [
+            font("Verdana", "Regular"),
+            font("arial", "Bold, Condensed"),
+            font("Arial", "Bold"),
+        ];
+        let columns = [Column::Family, Column::Style];
+
+        let mut csv = Vec::new();
+        write_delimited(&mut csv, &fonts, &columns, ExportFormat::Csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Font Family,Style\nArial,Bold\narial,\"Bold, Condensed\"\nVerdana,Regular\n"
+        );
+
+        let mut tsv = Vec::new();
+        write_delimited(&mut tsv, &fonts[..1], &columns, ExportFormat::Tsv).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "Font Family\tStyle\nVerdana\tRegular\n"
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! Per-script font fallback for arbitrary text
+//!
+//! Splits text into clusters (a base character plus its combining marks),
+//! resolves each cluster's script (punctuation, digits and symbols take the
+//! script of the surrounding text) and picks a face for it:
+//! 1. the first face of the requested family list that covers the cluster
+//! 2. otherwise the first face of its script's fallback chain that does
+//!
+//! A script's chain is built greedily from the enumerated families: the one
+//! covering the most clusters the family list could not render comes first,
+//! then the one covering the most of the rest, and so on. Each family is
+//! represented by the face `match_font` picks for the requested weight,
+//! width and style, and families whose GSUB/GPOS support the script win
+//! ties. Clusters no font covers are reported as tofu.
+
+use std::ops::Range;
+
+use crate::{match_font, FontInfo, FontRequest, Tag};
+
+/// The script tag of text without a script of its own
+pub const DEFAULT_SCRIPT: Tag = Tag::new(b"DFLT");
+
+/// A stretch of text rendered with one face
+#[derive(Clone, Debug, PartialEq)]
+pub struct FallbackRun {
+    pub range: Range<usize>, // Byte range in the text
+    pub script: Tag,         // OpenType script tag, DEFAULT_SCRIPT if none
+    pub font: Option<usize>, // Index of the face, None if nothing covers it
+}
+
+/// How a text renders with a font request, and the fallback chains behind it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fallback {
+    pub stack: Vec<usize>,              // Faces of the requested families, in order
+    pub chains: Vec<(Tag, Vec<usize>)>, // Fallback faces per script, best first
+    pub runs: Vec<FallbackRun>,
+}
+
+impl Fallback {
+    /// The runs no font covers
+    pub fn tofu(&self) -> impl Iterator<Item = &FallbackRun> {
+        self.runs.iter().filter(|r| r.font.is_none())
+    }
+}
+
+/// Computes the runs and per-script fallback chains for `text`
+///
+/// Families of the request that are not installed (or are generic) are
+/// skipped. Fonts without coverage data (GDI) are never chosen.
+pub fn fallback_chain(fonts: &[FontInfo], request: &FontRequest, text: &str) -> Fallback {
+    let face_of = |family: &str| {
+        let request = FontRequest {
+            families: vec![family.to_string()],
+            ..request.clone()
+        };
+        match_font(fonts, &request).map(|m| m.index)
+    };
+
+    let mut stack: Vec<usize> = Vec::new();
+    for index in request.families.iter().filter_map(|family| face_of(family)) {
+        if !stack.contains(&index) {
+            stack.push(index);
+        }
+    }
+
+    let clusters = clusters(text);
+    let mut chosen: Vec<Option<usize>> = clusters
+        .iter()
+        .map(|cluster| {
+            stack
+                .iter()
+                .copied()
+                .find(|&i| covers(&fonts[i], &cluster.chars))
+        })
+        .collect();
+
+    // One representative face per family outside the requested ones
+    let mut candidates: Vec<usize> = Vec::new();
+    for font in fonts {
+        let known = candidates
+            .iter()
+            .chain(&stack)
+            .any(|&c| fonts[c].family_name == font.family_name);
+        if !known && !font.coverage.is_empty() {
+            candidates.extend(face_of(&font.family_name));
+        }
+    }
+
+    let mut chains: Vec<(Tag, Vec<usize>)> = Vec::new();
+    for (c, cluster) in clusters.iter().enumerate() {
+        if chosen[c].is_some() || chains.iter().any(|(script, _)| *script == cluster.script) {
+            continue;
+        }
+        let script = cluster.script;
+        let mut uncovered: Vec<&[char]> = clusters
+            .iter()
+            .zip(&chosen)
+            .filter(|(other, font)| other.script == script && font.is_none())
+            .map(|(other, _)| other.chars.as_slice())
+            .collect();
+        uncovered.sort_unstable();
+        uncovered.dedup();
+
+        let mut chain = Vec::new();
+        while !uncovered.is_empty() {
+            let best = candidates
+                .iter()
+                .copied()
+                .filter(|i| !chain.contains(i))
+                .map(|i| {
+                    let count = uncovered
+                        .iter()
+                        .filter(|chars| covers(&fonts[i], chars))
+                        .count();
+                    (count, shapes(&fonts[i], script), i)
+                })
+                .filter(|&(count, _, _)| count > 0)
+                .max_by_key(|&(count, shaped, i)| (count, shaped, std::cmp::Reverse(i)));
+            let Some((_, _, best)) = best else { break };
+            uncovered.retain(|chars| !covers(&fonts[best], chars));
+            chain.push(best);
+        }
+        chains.push((script, chain));
+    }
+
+    for (c, cluster) in clusters.iter().enumerate() {
+        if chosen[c].is_none() {
+            let chain = chains.iter().find(|(script, _)| *script == cluster.script);
+            chosen[c] = chain.and_then(|(_, chain)| {
+                chain
+                    .iter()
+                    .copied()
+                    .find(|&i| covers(&fonts[i], &cluster.chars))
+            });
+        }
+    }
+
+    let mut runs: Vec<FallbackRun> = Vec::new();
+    for (cluster, font) in clusters.iter().zip(chosen) {
+        match runs.last_mut() {
+            Some(run) if run.script == cluster.script && run.font == font => {
+                run.range.end = cluster.range.end
+            }
+            _ => runs.push(FallbackRun {
+                range: cluster.range.clone(),
+                script: cluster.script,
+                font,
+            }),
+        }
+    }
+
+    Fallback {
+        stack,
+        chains,
+        runs,
+    }
+}
+
+/// A base character with the marks and selectors that follow it
+struct Cluster {
+    range: Range<usize>,
+    chars: Vec<char>, // Characters that need a glyph
+    script: Tag,
+}
+
+/// Splits text into clusters and resolves their scripts
+///
+/// Common characters take the script of the preceding text, or of the
+/// following text at the start; control characters and default-ignorable
+/// format characters need no glyph.
+fn clusters(text: &str) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut common: Vec<bool> = Vec::new();
+    for (offset, c) in text.char_indices() {
+        let range = offset..offset + c.len_utf8();
+        let needs_glyph = !c.is_control() && !is_ignorable(c);
+        match script_of(c) {
+            Script::Inherited if !clusters.is_empty() => {
+                let last = clusters.last_mut().expect("not empty");
+                last.range.end = range.end;
+                if needs_glyph {
+                    last.chars.push(c);
+                }
+            }
+            Script::Tag(script) => {
+                clusters.push(Cluster {
+                    range,
+                    chars: vec![c],
+                    script,
+                });
+                common.push(false);
+            }
+            Script::Common | Script::Inherited => {
+                let chars = if needs_glyph { vec![c] } else { Vec::new() };
+                clusters.push(Cluster {
+                    range,
+                    chars,
+                    script: DEFAULT_SCRIPT,
+                });
+                common.push(true);
+            }
+        }
+    }
+
+    let mut previous = None;
+    for (cluster, &common) in clusters.iter_mut().zip(&common) {
+        if !common {
+            previous = Some(cluster.script);
+        } else if let Some(script) = previous {
+            cluster.script = script;
+        }
+    }
+    let mut next = None;
+    for (cluster, &common) in clusters.iter_mut().zip(&common).rev() {
+        if !common {
+            next = Some(cluster.script);
+        } else if cluster.script == DEFAULT_SCRIPT {
+            cluster.script = next.unwrap_or(DEFAULT_SCRIPT);
+        }
+    }
+    clusters
+}
+
+/// True if the face has a glyph for every character of a cluster
+fn covers(font: &FontInfo, chars: &[char]) -> bool {
+    !font.coverage.is_empty() && chars.iter().all(|&c| font.coverage.contains(c))
+}
+
+/// True if the face's GSUB/GPOS has rules for the script (either Indic tag version)
+fn shapes(font: &FontInfo, script: Tag) -> bool {
+    let version_2 = INDIC_V2
+        .iter()
+        .find(|(v1, _)| Tag::new(v1) == script)
+        .map(|(_, v2)| Tag::new(v2));
+    font.layout.has_script(script) || version_2.is_some_and(|tag| font.layout.has_script(tag))
+}
+
+/// Variation selectors, joiners and other characters rendered without a glyph
+fn is_ignorable(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{200B}'..='\u{200F}' | '\u{2060}'..='\u{206F}' | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}' | '\u{E0000}'..='\u{E0FFF}'
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Script {
+    Common,    // Shared by all scripts: punctuation, digits, symbols, emoji
+    Inherited, // Takes the script of its base: combining marks, selectors
+    Tag(Tag),
+}
+
+/// The script of a character from its Unicode block
+///
+/// Coarser than the Unicode Scripts property (blocks mix common characters
+/// into some scripts), but enough to pick fonts.
+fn script_of(c: char) -> Script {
+    if c.is_ascii() {
+        return if c.is_ascii_alphabetic() {
+            Script::Tag(Tag::new(b"latn"))
+        } else {
+            Script::Common
+        };
+    }
+    let c = c as u32;
+    match SCRIPT_BLOCKS.binary_search_by(|&(first, last, _)| {
+        if last < c {
+            std::cmp::Ordering::Less
+        } else if first > c {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }) {
+        Ok(i) => match SCRIPT_BLOCKS[i].2 {
+            b"Zinh" => Script::Inherited,
+            b"Zyyy" => Script::Common,
+            tag => Script::Tag(Tag::new(tag)),
+        },
+        Err(_) => Script::Common,
+    }
+}
+
+/// The version 2 shaping tags of Indic scripts
+const INDIC_V2: &[(&[u8; 4], &[u8; 4])] = &[
+    (b"deva", b"dev2"),
+    (b"beng", b"bng2"),
+    (b"guru", b"gur2"),
+    (b"gujr", b"gjr2"),
+    (b"orya", b"ory2"),
+    (b"taml", b"tml2"),
+    (b"telu", b"tel2"),
+    (b"knda", b"knd2"),
+    (b"mlym", b"mlm2"),
+    (b"mymr", b"mym2"),
+];
+
+/// Unicode blocks by OpenType script tag, sorted; `Zinh` and `Zyyy` mark
+/// inherited and common blocks
+const SCRIPT_BLOCKS: &[(u32, u32, &[u8; 4])] = &[
+    (0x0080, 0x00BF, b"Zyyy"),
+    (0x00C0, 0x024F, b"latn"),
+    (0x0250, 0x02AF, b"latn"),
+    (0x02B0, 0x02FF, b"Zyyy"),
+    (0x0300, 0x036F, b"Zinh"),
+    (0x0370, 0x03FF, b"grek"),
+    (0x0400, 0x052F, b"cyrl"),
+    (0x0530, 0x058F, b"armn"),
+    (0x0590, 0x05FF, b"hebr"),
+    (0x0600, 0x06FF, b"arab"),
+    (0x0700, 0x074F, b"syrc"),
+    (0x0750, 0x077F, b"arab"),
+    (0x0780, 0x07BF, b"thaa"),
+    (0x07C0, 0x07FF, b"nko "),
+    (0x08A0, 0x08FF, b"arab"),
+    (0x0900, 0x097F, b"deva"),
+    (0x0980, 0x09FF, b"beng"),
+    (0x0A00, 0x0A7F, b"guru"),
+    (0x0A80, 0x0AFF, b"gujr"),
+    (0x0B00, 0x0B7F, b"orya"),
+    (0x0B80, 0x0BFF, b"taml"),
+    (0x0C00, 0x0C7F, b"telu"),
+    (0x0C80, 0x0CFF, b"knda"),
+    (0x0D00, 0x0D7F, b"mlym"),
+    (0x0D80, 0x0DFF, b"sinh"),
+    (0x0E00, 0x0E7F, b"thai"),
+    (0x0E80, 0x0EFF, b"lao "),
+    (0x0F00, 0x0FFF, b"tibt"),
+    (0x1000, 0x109F, b"mymr"),
+    (0x10A0, 0x10FF, b"geor"),
+    (0x1100, 0x11FF, b"hang"),
+    (0x1200, 0x139F, b"ethi"),
+    (0x13A0, 0x13FF, b"cher"),
+    (0x1400, 0x167F, b"cans"),
+    (0x1680, 0x169F, b"ogam"),
+    (0x16A0, 0x16FF, b"runr"),
+    (0x1780, 0x17FF, b"khmr"),
+    (0x1800, 0x18AF, b"mong"),
+    (0x1AB0, 0x1AFF, b"Zinh"),
+    (0x1C80, 0x1C8F, b"cyrl"),
+    (0x1C90, 0x1CBF, b"geor"),
+    (0x1D00, 0x1DBF, b"latn"),
+    (0x1DC0, 0x1DFF, b"Zinh"),
+    (0x1E00, 0x1EFF, b"latn"),
+    (0x1F00, 0x1FFF, b"grek"),
+    (0x2000, 0x20CF, b"Zyyy"),
+    (0x20D0, 0x20FF, b"Zinh"),
+    (0x2100, 0x2BFF, b"Zyyy"),
+    (0x2C00, 0x2C5F, b"glag"),
+    (0x2C60, 0x2C7F, b"latn"),
+    (0x2C80, 0x2CFF, b"copt"),
+    (0x2D00, 0x2D2F, b"geor"),
+    (0x2D30, 0x2D7F, b"tfng"),
+    (0x2D80, 0x2DDF, b"ethi"),
+    (0x2DE0, 0x2DFF, b"cyrl"),
+    (0x2E00, 0x2E7F, b"Zyyy"),
+    (0x2E80, 0x2FDF, b"hani"),
+    (0x2FF0, 0x303F, b"Zyyy"),
+    (0x3040, 0x30FF, b"kana"),
+    (0x3100, 0x312F, b"bopo"),
+    (0x3130, 0x318F, b"hang"),
+    (0x31A0, 0x31BF, b"bopo"),
+    (0x31C0, 0x31EF, b"hani"),
+    (0x31F0, 0x31FF, b"kana"),
+    (0x3200, 0x33FF, b"Zyyy"),
+    (0x3400, 0x4DBF, b"hani"),
+    (0x4DC0, 0x4DFF, b"Zyyy"),
+    (0x4E00, 0x9FFF, b"hani"),
+    (0xA000, 0xA4CF, b"yi  "),
+    (0xA4D0, 0xA4FF, b"lisu"),
+    (0xA500, 0xA63F, b"vai "),
+    (0xA640, 0xA69F, b"cyrl"),
+    (0xA720, 0xA7FF, b"latn"),
+    (0xA960, 0xA97F, b"hang"),
+    (0xAB30, 0xAB6F, b"latn"),
+    (0xAC00, 0xD7FF, b"hang"),
+    (0xF900, 0xFAFF, b"hani"),
+    (0xFB00, 0xFB06, b"latn"),
+    (0xFB1D, 0xFB4F, b"hebr"),
+    (0xFB50, 0xFDFF, b"arab"),
+    (0xFE00, 0xFE0F, b"Zinh"),
+    (0xFE10, 0xFE1F, b"Zyyy"),
+    (0xFE20, 0xFE2F, b"Zinh"),
+    (0xFE30, 0xFE6F, b"Zyyy"),
+    (0xFE70, 0xFEFF, b"arab"),
+    (0xFF00, 0xFF20, b"Zyyy"),
+    (0xFF21, 0xFF3A, b"latn"),
+    (0xFF3B, 0xFF40, b"Zyyy"),
+    (0xFF41, 0xFF5A, b"latn"),
+    (0xFF5B, 0xFF65, b"Zyyy"),
+    (0xFF66, 0xFF9F, b"kana"),
+    (0xFFA0, 0xFFDF, b"hang"),
+    (0xFFE0, 0xFFFF, b"Zyyy"),
+    (0x1F000, 0x1FAFF, b"Zyyy"),
+    (0x20000, 0x3134F, b"hani"),
+    (0xE0100, 0xE01EF, b"Zinh"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coverage, LanguageSystem, Layout};
+
+    fn font(family: &str, ranges: &[(char, char)]) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            weight: 400,
+            width: 5,
+            coverage: Coverage::from_ranges(ranges.iter().map(|&(a, b)| (a as u32, b as u32))),
+            ..Default::default()
+        }
+    }
+
+    fn shaping(mut font: FontInfo, script: &[u8; 4]) -> FontInfo {
+        font.layout = Layout {
+            systems: vec![LanguageSystem {
+                script: Tag::new(script),
+                ..Default::default()
+            }],
+        };
+        font
+    }
+
+    fn request(families: &[&str]) -> FontRequest {
+        FontRequest {
+            families: families.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn scripts(text: &str) -> Vec<(&str, Tag)> {
+        clusters(text)
+            .into_iter()
+            .map(|c| (&text[c.range], c.script))
+            .collect()
+    }
+
+    #[test]
+    fn common_characters_take_the_surrounding_script() {
+        let latn = Tag::new(b"latn");
+        let cyrl = Tag::new(b"cyrl");
+        assert_eq!(
+            scripts("«Да», e\u{301}!"),
+            [
+                ("«", cyrl),
+                ("Д", cyrl),
+                ("а", cyrl),
+                ("»", cyrl),
+                (",", cyrl),
+                (" ", cyrl),
+                ("e\u{301}", latn),
+                ("!", latn),
+            ]
+        );
+        assert_eq!(
+            scripts("42"),
+            [("4", DEFAULT_SCRIPT), ("2", DEFAULT_SCRIPT)]
+        );
+        // Selectors and joiners need no glyph of their own
+        let chars: Vec<Vec<char>> = clusters("\u{2764}\u{FE0F}\u{200D}")
+            .into_iter()
+            .map(|c| c.chars)
+            .collect();
+        assert_eq!(chars, [vec!['\u{2764}'], vec![]]);
+    }
+
+    #[test]
+    fn script_blocks_are_sorted() {
+        assert!(SCRIPT_BLOCKS
+            .windows(2)
+            .all(|pair| pair[0].0 <= pair[0].1 && pair[0].1 < pair[1].0));
+        assert_eq!(script_of('ж'), Script::Tag(Tag::new(b"cyrl")));
+        assert_eq!(script_of('\u{301}'), Script::Inherited);
+        assert_eq!(script_of('😀'), Script::Common);
+    }
+
+    #[test]
+    fn runs_use_the_stack_then_the_script_chains() {
+        let fonts = [
+            font("Latin", &[(' ', '~')]),
+            font("Cyrillic Basic", &[('а', 'я')]),
+            font("Cyrillic Full", &[('Ѐ', 'ӿ')]),
+            font("Symbols", &[('★', '★')]),
+        ];
+        let fallback = fallback_chain(&fonts, &request(&["Missing", "Latin"]), "Hi Ёж ✓");
+        assert_eq!(fallback.stack, [0]);
+        // Full covers both Cyrillic letters, Basic only one
+        assert_eq!(fallback.chains, [(Tag::new(b"cyrl"), vec![2])]);
+        let runs: Vec<(&str, Option<usize>)> = fallback
+            .runs
+            .iter()
+            .map(|r| (&"Hi Ёж ✓"[r.range.clone()], r.font))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                ("Hi ", Some(0)),
+                ("Ёж", Some(2)),
+                (" ", Some(0)),
+                ("✓", None)
+            ]
+        );
+        assert_eq!(fallback.tofu().count(), 1);
+    }
+
+    #[test]
+    fn shaping_support_breaks_ties() {
+        let fonts = [
+            font("Plain", &[('\u{900}', '\u{97F}')]),
+            shaping(font("Shaped", &[('\u{900}', '\u{97F}')]), b"dev2"),
+        ];
+        let fallback = fallback_chain(&fonts, &request(&[]), "नमस्ते");
+        assert_eq!(fallback.chains, [(Tag::new(b"deva"), vec![1])]);
+        assert!(fallback.runs.iter().all(|r| r.font == Some(1)));
+    }
+}
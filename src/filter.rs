@@ -0,0 +1,375 @@
+//! Filtering of enumerated fonts
+//!
+//! A filter string is a list of whitespace-separated words. Words of the
+//! form `key:value` with a known key are property terms; everything else is
+//! free text matched (case-insensitively) against family and style names.
+//!
+//! | Term              | Matches                                           |
+//! |-------------------|---------------------------------------------------|
+//! | `weight:700`      | weight 700 (also `weight:bold`, `weight:300-500`) |
+//! | `width:condensed` | width class 3 (also `width:5`, `width:1-4`)       |
+//! | `container:woff2` | fonts stored as WOFF2 (also `sfnt`, `woff`)       |
+//! | `upm:1000`        | units per em (also `upm:1000-2048`)               |
+//! | `lineheight:120`  | default line height in % of the em (or a range)   |
+//! | `xheight:50-60`   | x-height in % of the em (also `capheight:`)       |
+//! | `fixed:yes`       | monospace fonts (also `fixed:no`)                 |
+//! | `fixed:conflict`  | the monospace signals disagree with each other    |
+//! | `clipping:yes`    | glyphs extend past the win (or hhea) metrics      |
+//! | `color:yes`       | color fonts (also `color:no`, `color:colrv1`,     |
+//! |                   | `colrv0`, `svg`, `sbix`, `cbdt`)                  |
+//! | `outline:cff`     | CFF outlines (also `truetype`, `cff2`, `bitmap`)  |
+//! | `script:latn`     | GSUB/GPOS has the script                          |
+//! | `embedding:print` | fsType permission (also `installable`,            |
+//! |                   | `editable`, `restricted`)                         |
+//! | `feature:tnum`    | GSUB/GPOS has the feature; `feature:tnum,onum`    |
+//! |                   | needs all of them, `feature:tnum,onum@latn` all   |
+//! |                   | of them for that script                           |
+//!
+//! All terms and the free text must match. A word whose value cannot be
+//! parsed (e.g. `weight:` while still typing) is treated as free text.
+
+use crate::{ColorFormat, Container, Embedding, FontInfo, Metrics, OutlineFormat, Tag};
+
+/// A parsed filter expression
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    text: String,     // Lowercased free text
+    terms: Vec<Term>, // Property terms, all of which must match
+}
+
+/// One `key:value` property term
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    Weight(Range),
+    Width(Range),
+    Container(Container),
+    UnitsPerEm(Range),
+    LineHeight(Range),
+    XHeight(Range),
+    CapHeight(Range),
+    Clipping(bool),
+    Fixed(bool),
+    FixedConflict,
+    Color(bool),
+    ColorFormat(ColorFormat),
+    Outline(OutlineFormat),
+    Script(Tag),
+    Features(Vec<Tag>, Option<Tag>),
+    Embedding(Embedding),
+}
+
+/// An inclusive numeric range; a single value is `min == max`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Range {
+    min: i32,
+    max: i32,
+}
+
+impl Filter {
+    /// Parses a filter string
+    pub fn parse(filter: &str) -> Self {
+        let mut words = Vec::new();
+        let mut terms = Vec::new();
+
+        for word in filter.split_whitespace() {
+            match parse_term(word) {
+                Some(term) => terms.push(term),
+                None => words.push(word),
+            }
+        }
+
+        Filter {
+            text: words.join(" ").to_lowercase(),
+            terms,
+        }
+    }
+
+    /// True if the filter has neither free text nor terms
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.terms.is_empty()
+    }
+
+    /// True if a font satisfies the free text and every term
+    pub fn matches(&self, font: &FontInfo) -> bool {
+        let text_matches = self.text.is_empty()
+            || font.family_name.to_lowercase().contains(&self.text)
+            || font.style_name.to_lowercase().contains(&self.text);
+
+        text_matches && self.terms.iter().all(|t| t.matches(font))
+    }
+}
+
+impl Term {
+    fn matches(&self, font: &FontInfo) -> bool {
+        match self {
+            Term::Weight(range) => range.contains(font.weight),
+            Term::Width(range) => range.contains(font.width),
+            Term::Container(container) => font.container == *container,
+            Term::UnitsPerEm(range) => metric(font, |m| Some(m.units_per_em as i32), *range),
+            Term::LineHeight(range) => {
+                metric(font, |m| Some(m.percent_of_em(m.line_height())), *range)
+            }
+            Term::XHeight(range) => {
+                metric(font, |m| Some(m.percent_of_em(m.x_height? as i32)), *range)
+            }
+            Term::CapHeight(range) => metric(
+                font,
+                |m| Some(m.percent_of_em(m.cap_height? as i32)),
+                *range,
+            ),
+            Term::Color(is_color) => font.color.is_color() == *is_color,
+            Term::ColorFormat(format) => font.color.contains(*format),
+            Term::Outline(outline) => font.outline == Some(*outline),
+            Term::Script(script) => font.layout.has_script(*script),
+            Term::Features(features, script) => features
+                .iter()
+                .all(|f| font.layout.has_feature(*f, *script)),
+            Term::Embedding(permission) => {
+                font.os2.as_ref().map(|o| o.fs_type.permission()) == Some(*permission)
+            }
+            Term::Fixed(fixed) => font.fixed_pitch == *fixed,
+            Term::FixedConflict => !font.monospace.conflicts().is_empty(),
+            Term::Clipping(clips) => font
+                .metrics
+                .as_ref()
+                .map(|m| m.clips() == *clips)
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl Range {
+    fn contains(self, value: i32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// True if the font has metrics and the derived value lies in `range`
+fn metric(font: &FontInfo, value: impl Fn(&Metrics) -> Option<i32>, range: Range) -> bool {
+    font.metrics
+        .as_ref()
+        .and_then(value)
+        .map(|v| range.contains(v))
+        .unwrap_or(false)
+}
+
+/// Applies a filter string to a font list
+///
+/// Returns the indices of fonts that match the filter (see the module
+/// documentation for the syntax). An empty filter matches everything.
+pub fn apply_filter(fonts: &[FontInfo], filter: &str) -> Vec<usize> {
+    let filter = Filter::parse(filter);
+
+    fonts
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| filter.matches(f))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Parses a `key:value` word into a term, if the key is known and the value valid
+fn parse_term(word: &str) -> Option<Term> {
+    let (key, value) = word.split_once(':')?;
+    match key.to_ascii_lowercase().as_str() {
+        "weight" => parse_range(value, weight_keyword).map(Term::Weight),
+        "width" | "stretch" => parse_range(value, width_keyword).map(Term::Width),
+        "container" => Container::from_name(value).map(Term::Container),
+        "upm" => parse_range(value, no_keyword).map(Term::UnitsPerEm),
+        "lineheight" => parse_range(value, no_keyword).map(Term::LineHeight),
+        "xheight" => parse_range(value, no_keyword).map(Term::XHeight),
+        "capheight" => parse_range(value, no_keyword).map(Term::CapHeight),
+        "clipping" => parse_bool(value).map(Term::Clipping),
+        "fixed" | "mono" => match value.to_ascii_lowercase().as_str() {
+            "conflict" => Some(Term::FixedConflict),
+            _ => parse_bool(value).map(Term::Fixed),
+        },
+        "color" | "colour" => parse_bool(value)
+            .map(Term::Color)
+            .or_else(|| ColorFormat::from_name(value).map(Term::ColorFormat)),
+        "outline" => OutlineFormat::from_name(value).map(Term::Outline),
+        "script" => Tag::from_name(value).map(Term::Script),
+        "feature" | "features" => parse_features(value),
+        "embedding" => Embedding::from_name(value).map(Term::Embedding),
+        _ => None,
+    }
+}
+
+/// Parses `N`, `N-M` or a keyword into a range
+fn parse_range(value: &str, keyword: fn(&str) -> Option<i32>) -> Option<Range> {
+    let single = |s: &str| {
+        s.parse::<i32>()
+            .ok()
+            .or_else(|| keyword(&s.to_ascii_lowercase()))
+    };
+
+    if let Some(v) = single(value) {
+        return Some(Range { min: v, max: v });
+    }
+    let (min, max) = value.split_once('-')?;
+    Some(Range {
+        min: single(min)?,
+        max: single(max)?,
+    })
+}
+
+/// Parses `tag,tag,...` with an optional `@script` suffix
+fn parse_features(value: &str) -> Option<Term> {
+    let (features, script) = match value.split_once('@') {
+        Some((features, script)) => (features, Some(Tag::from_name(script)?)),
+        None => (value, None),
+    };
+    let features = features
+        .split(',')
+        .map(Tag::from_name)
+        .collect::<Option<Vec<_>>>()?;
+    Some(Term::Features(features, script))
+}
+
+/// For numeric terms that have no named values
+fn no_keyword(_: &str) -> Option<i32> {
+    None
+}
+
+/// `yes`/`no` (also `true`/`false`)
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" | "true" => Some(true),
+        "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// CSS/OpenType weight names
+fn weight_keyword(name: &str) -> Option<i32> {
+    Some(match name {
+        "thin" | "hairline" => 100,
+        "extralight" | "ultralight" => 200,
+        "light" => 300,
+        "regular" | "normal" => 400,
+        "medium" => 500,
+        "semibold" | "demibold" => 600,
+        "bold" => 700,
+        "extrabold" | "ultrabold" => 800,
+        "black" | "heavy" => 900,
+        _ => return None,
+    })
+}
+
+/// usWidthClass / CSS font-stretch names
+fn width_keyword(name: &str) -> Option<i32> {
+    Some(match name {
+        "ultracondensed" | "ultra-condensed" => 1,
+        "extracondensed" | "extra-condensed" => 2,
+        "condensed" => 3,
+        "semicondensed" | "semi-condensed" => 4,
+        "normal" | "medium" => 5,
+        "semiexpanded" | "semi-expanded" => 6,
+        "expanded" => 7,
+        "extraexpanded" | "extra-expanded" => 8,
+        "ultraexpanded" | "ultra-expanded" => 9,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageSystem, Layout};
+
+    fn font(family: &str, style: &str, weight: i32, width: i32) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            style_name: style.to_string(),
+            weight,
+            width,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn terms_take_numbers_ranges_and_keywords() {
+        let range = |min, max| Range { min, max };
+        assert_eq!(
+            parse_term("weight:700"),
+            Some(Term::Weight(range(700, 700)))
+        );
+        assert_eq!(
+            parse_term("Weight:Bold"),
+            Some(Term::Weight(range(700, 700)))
+        );
+        assert_eq!(
+            parse_term("weight:light-500"),
+            Some(Term::Weight(range(300, 500)))
+        );
+        assert_eq!(
+            parse_term("stretch:semi-condensed"),
+            Some(Term::Width(range(4, 4)))
+        );
+        assert_eq!(parse_term("width:1-4"), Some(Term::Width(range(1, 4))));
+        assert_eq!(parse_term("fixed:conflict"), Some(Term::FixedConflict));
+        assert_eq!(parse_term("mono:no"), Some(Term::Fixed(false)));
+        assert_eq!(
+            parse_term("feature:tnum,onum@latn"),
+            Some(Term::Features(
+                vec![Tag::new(b"tnum"), Tag::new(b"onum")],
+                Some(Tag::new(b"latn"))
+            ))
+        );
+        for word in [
+            "weight:",
+            "weight:heavyish",
+            "width:3-",
+            "color:maybe",
+            "size:12",
+            "http://x",
+        ] {
+            assert_eq!(parse_term(word), None, "{word}");
+        }
+    }
+
+    #[test]
+    fn unparsed_words_are_free_text() {
+        let filter = Filter::parse("  Noto   weight:bold  Sans weight: ");
+        assert_eq!(filter.text, "noto sans weight:");
+        assert_eq!(filter.terms.len(), 1);
+        assert!(Filter::parse(" ").is_empty());
+    }
+
+    #[test]
+    fn text_and_every_term_must_match() {
+        let fonts = [
+            font("Noto Sans", "Regular", 400, 5),
+            font("Noto Sans", "Bold", 700, 5),
+            font("Noto Sans", "Condensed Bold", 700, 3),
+            font("Roboto", "Bold", 700, 5),
+        ];
+        assert_eq!(apply_filter(&fonts, ""), [0, 1, 2, 3]);
+        assert_eq!(apply_filter(&fonts, "bold"), [1, 2, 3]);
+        assert_eq!(apply_filter(&fonts, "noto weight:600-900"), [1, 2]);
+        assert_eq!(apply_filter(&fonts, "weight:bold width:condensed"), [2]);
+        assert_eq!(
+            apply_filter(&fonts, "NOTO SANS weight:thin"),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn fonts_without_the_data_do_not_match_property_terms() {
+        let mut font = font("Inter", "Regular", 400, 5);
+        assert!(!Filter::parse("upm:1000").matches(&font));
+        assert!(!Filter::parse("clipping:no").matches(&font));
+        assert!(!Filter::parse("embedding:installable").matches(&font));
+
+        font.layout = Layout {
+            systems: vec![LanguageSystem {
+                script: Tag::new(b"latn"),
+                language: None,
+                features: [Tag::new(b"tnum"), Tag::new(b"kern")].into(),
+            }],
+        };
+        assert!(Filter::parse("script:latn feature:tnum,kern").matches(&font));
+        assert!(!Filter::parse("feature:tnum,onum").matches(&font));
+        assert!(!Filter::parse("feature:tnum@cyrl").matches(&font));
+    }
+}
@@ -0,0 +1,136 @@
+//! The font record produced by every enumeration source
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ColorFormats, Container, Coverage, Layout, Metrics, Monospace, NameTable, NamedInstance, Os2,
+    OutlineFormat, TableRecord, Tag, VariationAxis,
+};
+
+/// Represents information about a single font face
+///
+/// Different enumeration APIs provide different levels of detail:
+/// - GDI: family_name, style_name, weight, italic, fixed_pitch
+/// - DirectWrite: Same as GDI plus width, coverage and better Unicode handling
+/// - FontSet: All above plus file_path, variable_axes, is_variable
+/// - Directory: All of the above read from the font file, plus the
+///   complete name and OS/2 tables and the fvar named instances
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FontInfo {
+    pub family_name: String,                 // e.g., "Arial", "Segoe UI"
+    pub style_name: String,                  // e.g., "Regular", "Bold Italic"
+    pub file_path: String,                   // Full path to font file (FontSet API only)
+    pub face_index: Option<u32>, // Face within a .ttc/.otc collection, None for single-face files
+    pub container: Container,    // File format: sfnt, WOFF or WOFF2 (Directory only)
+    pub variable_axes: Vec<VariationAxis>, // Variation axes, e.g., wght 100-900 (FontSet API only)
+    pub named_instances: Vec<NamedInstance>, // fvar named instances (Directory only)
+    pub instance: Option<NamedInstance>, // Set on rows made by expand_named_instances
+    pub weight: i32,             // Font weight: 400=Normal, 700=Bold, etc.
+    pub width: i32,              // Width class: 1-9, 5=Normal, 3=Condensed, 0=Unknown (GDI)
+    pub italic: bool,            // Whether this is an italic/oblique style
+    pub fixed_pitch: bool,       // True for monospace fonts (Monospace::is_monospace where known)
+    pub monospace: Monospace,    // The signals behind fixed_pitch (DirectWrite, FontSet, Directory)
+    pub mac_style: Option<u16>,  // head macStyle: bit 0 bold, bit 1 italic (Directory only)
+    pub is_variable: bool,       // True if font has variable axes
+    pub names: NameTable,        // All name records by ID and language (Directory only)
+    pub os2: Option<Os2>,        // Decoded OS/2 table (Directory only)
+    pub coverage: Coverage,      // Unicode characters with a glyph (empty for GDI)
+    pub metrics: Option<Metrics>, // Vertical metrics (DirectWrite, FontSet, Directory)
+    pub color: ColorFormats,     // Color glyph technologies (DirectWrite, FontSet, Directory)
+    pub sfnt_version: Option<Tag>, // 0x00010000, 'OTTO' or 'true' (Directory only)
+    pub outline: Option<OutlineFormat>, // glyf, CFF, CFF2 or bitmap-only (DirectWrite, FontSet, Directory)
+    pub tables: Vec<TableRecord>,       // Table directory in file order (Directory only)
+    pub glyph_count: Option<u16>,       // maxp numGlyphs (DirectWrite, FontSet, Directory)
+    pub layout: Layout, // GSUB/GPOS scripts, languages and features (DirectWrite, FontSet, Directory)
+}
+
+impl FontInfo {
+    /// The file path, with `#index` appended for faces of a collection
+    ///
+    /// E.g. `C:\Windows\Fonts\msgothic.ttc#1`; this identifies a face
+    /// unambiguously even when several faces share one file.
+    pub fn location(&self) -> String {
+        face_location(&self.file_path, self.face_index)
+    }
+
+    /// Formats the axes that actually vary, e.g. `wght 100-900, wdth 75-100`
+    pub fn variable_axes_string(&self) -> String {
+        self.variable_axes
+            .iter()
+            .filter(|a| a.is_variable())
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// One row per named instance of a variable font
+    ///
+    /// Each row is a copy of this font with the style name, weight, width
+    /// and italic flag taken from the instance's coordinates. Fonts without
+    /// named instances yield just themselves.
+    pub fn instance_rows(&self) -> Vec<FontInfo> {
+        if self.named_instances.is_empty() {
+            return vec![self.clone()];
+        }
+
+        self.named_instances
+            .iter()
+            .map(|instance| {
+                let mut row = self.clone();
+                row.named_instances = Vec::new();
+                row.style_name = instance.name.clone();
+                if let Some(wght) = instance.coordinate(Tag::new(b"wght")) {
+                    row.weight = wght.round() as i32;
+                }
+                if let Some(wdth) = instance.coordinate(Tag::new(b"wdth")) {
+                    row.width = width_class(wdth);
+                }
+                // ital is 0 or 1; any non-zero slant reads as oblique
+                let ital = instance.coordinate(Tag::new(b"ital"));
+                let slnt = instance.coordinate(Tag::new(b"slnt"));
+                if ital.is_some() || slnt.is_some() {
+                    row.italic = ital.unwrap_or(0.0) >= 1.0 || slnt.unwrap_or(0.0) != 0.0;
+                }
+                row.instance = Some(instance.clone());
+                row
+            })
+            .collect()
+    }
+}
+
+/// Replaces every variable font with one row per named instance
+///
+/// Static fonts are passed through unchanged; see `FontInfo::instance_rows`.
+pub fn expand_named_instances(fonts: &[FontInfo]) -> Vec<FontInfo> {
+    fonts.iter().flat_map(FontInfo::instance_rows).collect()
+}
+
+/// Percentages of normal width for usWidthClass 1-9 (OpenType OS/2 spec)
+const WIDTH_PERCENT: [f32; 9] = [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0];
+
+/// Maps a `wdth` axis percentage to the nearest usWidthClass (1-9)
+fn width_class(wdth: f32) -> i32 {
+    let mut best = 0;
+    for (i, &p) in WIDTH_PERCENT.iter().enumerate() {
+        if (wdth - p).abs() < (wdth - WIDTH_PERCENT[best]).abs() {
+            best = i;
+        }
+    }
+    best as i32 + 1
+}
+
+/// `path#index` for faces of a collection, the path otherwise
+pub(crate) fn face_location(file_path: &str, face_index: Option<u32>) -> String {
+    match face_index {
+        Some(index) => format!("{}#{}", file_path, index),
+        None => file_path.to_string(),
+    }
+}
+
+/// Maps a usWidthClass to its percentage of normal width; unknown (0) is 100%
+pub(crate) fn width_percent(width_class: i32) -> f32 {
+    match width_class {
+        1..=9 => WIDTH_PERCENT[width_class as usize - 1],
+        _ => 100.0,
+    }
+}
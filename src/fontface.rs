@@ -0,0 +1,471 @@
+//! CSS `@font-face` rules for self-hosting a family
+//!
+//! `font_face_rules` turns the enumerated faces of one family into the
+//! rules a stylesheet needs:
+//! - `font-weight`, `font-stretch` and `font-style` describe what each face
+//!   can render, as ranges for variable fonts (`wght`, `wdth`, `slnt`); a
+//!   face with an `ital` axis gets one rule for normal and one for italic
+//! - `src` lists every copy of the face, WOFF2 first, with `format()` hints
+//!   taken from the container and outline format
+//! - `unicode-range` is the face's cmap coverage, so browsers only download
+//!   files for characters a page uses
+//! - optionally, `ascent-override`, `descent-override` and
+//!   `line-gap-override` pin the line metrics to the font's own, so every
+//!   platform lays out lines the same way
+//!
+//! The descriptors come from the same model `match_font` uses, so a browser
+//! given these rules picks the face `match_font` reports.
+
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::matching::{has_family, Face};
+use crate::{Container, FontInfo, Metrics, NameId, OutlineFormat};
+
+/// What `font_face_rules` adds beyond the descriptors
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FontFaceOptions {
+    pub base_url: String, // Prefix of the file names in `url()`, e.g. "/fonts/"
+    pub local: bool,      // Try installed copies first with local()
+    pub metric_overrides: bool, // Ascent/descent/line gap overrides from the font's metrics
+}
+
+/// `@font-face` metric override descriptors, in percent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MetricOverrides {
+    pub size_adjust: f32, // Scale of the glyphs, 100 for none
+    pub ascent: f32,      // Of the (adjusted) em
+    pub descent: f32,     // Of the (adjusted) em, positive below the baseline
+    pub line_gap: f32,    // Of the (adjusted) em
+}
+
+impl MetricOverrides {
+    /// The overrides that reproduce a font's own line metrics (the ones
+    /// `Metrics::line_height` uses); None without unitsPerEm
+    pub fn of(metrics: &Metrics) -> Option<Self> {
+        if metrics.units_per_em == 0 {
+            return None;
+        }
+        let (ascent, descent, gap) = metrics.line_metrics();
+        let percent = |units: i32| units as f32 * 100.0 / metrics.units_per_em as f32;
+        Some(MetricOverrides {
+            size_adjust: 100.0,
+            ascent: percent(ascent),
+            descent: percent(descent),
+            line_gap: percent(gap),
+        })
+    }
+}
+
+/// One `@font-face` rule
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFaceRule {
+    pub family: String,
+    pub sources: Vec<String>, // `src` entries, e.g. `url("Inter.woff2") format("woff2")`
+    pub weight: (f32, f32),
+    pub stretch: (f32, f32),            // Percent of normal
+    pub style: String,                  // "normal", "italic" or e.g. "oblique 0deg 10deg"
+    pub unicode_range: Vec<(u32, u32)>, // Empty when the source reports no coverage
+    pub overrides: Option<MetricOverrides>,
+}
+
+/// The `@font-face` rules for every face of `family`
+///
+/// The family is matched like `match_font` does (typographic, legacy and
+/// WWS names); the rules use the enumerated family name. Copies of the same
+/// face in several files (e.g. .ttf and .woff2) share a rule. Rows of
+/// named instances are skipped, their variable font covers them. Rules are
+/// ordered by stretch, style and weight; empty if the family has no faces.
+pub fn font_face_rules(
+    fonts: &[FontInfo],
+    family: &str,
+    options: &FontFaceOptions,
+) -> Vec<FontFaceRule> {
+    let faces: Vec<&FontInfo> = fonts
+        .iter()
+        .filter(|f| f.instance.is_none() && has_family(f, family))
+        .collect();
+    let name = faces
+        .iter()
+        .map(|f| f.family_name.as_str())
+        .find(|name| name.eq_ignore_ascii_case(family))
+        .unwrap_or(family);
+
+    let mut rules: Vec<(FontFaceRule, Vec<&FontInfo>)> = Vec::new();
+    for font in faces {
+        let face = Face::new(0, font);
+        let mut styles = Vec::new();
+        if face.italic_axis {
+            styles.push("normal".to_string());
+            styles.push("italic".to_string());
+        } else if let (false, Some((lo, hi))) = (face.italic, face.oblique) {
+            styles.push(format!("oblique {}", range(lo, hi, "deg")));
+        } else if face.italic {
+            styles.push("italic".to_string());
+        } else {
+            styles.push("normal".to_string());
+        }
+
+        for style in styles {
+            let rule = FontFaceRule {
+                family: name.to_string(),
+                sources: Vec::new(),
+                weight: face.weight,
+                stretch: face.stretch,
+                style,
+                unicode_range: font.coverage.ranges().to_vec(),
+                overrides: None,
+            };
+            match rules.iter_mut().find(|(r, _)| *r == rule) {
+                Some((_, copies)) => copies.push(font),
+                None => rules.push((rule, vec![font])),
+            }
+        }
+    }
+
+    for (rule, copies) in &mut rules {
+        copies.sort_by_key(|f| match f.container {
+            Container::Woff2 => 0,
+            Container::Woff => 1,
+            Container::Sfnt => 2,
+        });
+        if options.local || copies.iter().all(|f| f.file_path.is_empty()) {
+            rule.sources.extend(local_sources(copies[0]));
+        }
+        for font in copies.iter().filter(|f| !f.file_path.is_empty()) {
+            let file = Path::new(&font.file_path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            let source = format!(
+                "url(\"{}{}\") format(\"{}\")",
+                css_string(&options.base_url),
+                css_string(&file),
+                format_hint(font)
+            );
+            if !rule.sources.contains(&source) {
+                rule.sources.push(source);
+            }
+        }
+        if options.metric_overrides {
+            rule.overrides = copies
+                .iter()
+                .find_map(|f| f.metrics.as_ref())
+                .and_then(MetricOverrides::of);
+        }
+    }
+
+    let mut rules: Vec<FontFaceRule> = rules.into_iter().map(|(rule, _)| rule).collect();
+    rules.sort_by(|a, b| {
+        a.stretch
+            .0
+            .total_cmp(&b.stretch.0)
+            .then_with(|| style_order(&a.style).cmp(&style_order(&b.style)))
+            .then_with(|| a.weight.0.total_cmp(&b.weight.0))
+    });
+    rules
+}
+
+impl fmt::Display for FontFaceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "@font-face {{")?;
+        writeln!(f, "  font-family: \"{}\";", css_string(&self.family))?;
+        if !self.sources.is_empty() {
+            writeln!(f, "  src: {};", self.sources.join(",\n       "))?;
+        }
+        writeln!(
+            f,
+            "  font-weight: {};",
+            range(self.weight.0, self.weight.1, "")
+        )?;
+        writeln!(
+            f,
+            "  font-stretch: {};",
+            range(self.stretch.0, self.stretch.1, "%")
+        )?;
+        writeln!(f, "  font-style: {};", self.style)?;
+        if !self.unicode_range.is_empty() {
+            let ranges: Vec<String> = self
+                .unicode_range
+                .iter()
+                .map(|&(first, last)| {
+                    if first == last {
+                        format!("U+{:04X}", first)
+                    } else {
+                        format!("U+{:04X}-{:04X}", first, last)
+                    }
+                })
+                .collect();
+            let lines: Vec<String> = ranges.chunks(8).map(|chunk| chunk.join(", ")).collect();
+            writeln!(f, "  unicode-range: {};", lines.join(",\n    "))?;
+        }
+        if let Some(overrides) = &self.overrides {
+            if overrides.size_adjust != 100.0 {
+                writeln!(f, "  size-adjust: {}%;", number(overrides.size_adjust))?;
+            }
+            writeln!(f, "  ascent-override: {}%;", number(overrides.ascent))?;
+            writeln!(f, "  descent-override: {}%;", number(overrides.descent))?;
+            writeln!(f, "  line-gap-override: {}%;", number(overrides.line_gap))?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// The `format()` hint of a font file
+pub(crate) fn format_hint(font: &FontInfo) -> &'static str {
+    match font.container {
+        Container::Woff => "woff",
+        Container::Woff2 => "woff2",
+        Container::Sfnt if font.face_index.is_some() => "collection",
+        Container::Sfnt => match font.outline {
+            Some(OutlineFormat::Cff | OutlineFormat::Cff2) => "opentype",
+            _ => "truetype",
+        },
+    }
+}
+
+/// Escapes text for a double-quoted CSS string
+///
+/// `<` and `>` are escaped too, so names can't close a `<style>` element,
+/// and control characters, which can't appear in a CSS string unescaped.
+pub(crate) fn css_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '"' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // A hex escape ends at the space after it
+            '<' | '>' | '\u{0}'..='\u{1F}' | '\u{7F}' => {
+                let _ = write!(escaped, "\\{:X} ", c as u32);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `local()` entries for the full and PostScript names of a face
+fn local_sources(font: &FontInfo) -> Vec<String> {
+    let full = font
+        .names
+        .best(NameId::FULL_NAME)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{} {}", font.family_name, font.style_name));
+    let mut sources = vec![format!("local(\"{}\")", css_string(&full))];
+    if let Some(postscript) = font
+        .names
+        .best(NameId::POSTSCRIPT_NAME)
+        .filter(|&p| p != full)
+    {
+        sources.push(format!("local(\"{}\")", css_string(postscript)));
+    }
+    sources
+}
+
+/// A descriptor value or range, e.g. `400` or `100 900`, `75% 100%`
+fn range(lo: f32, hi: f32, unit: &str) -> String {
+    let mut text = format!("{}{}", number(lo), unit);
+    if hi != lo {
+        let _ = write!(text, " {}{}", number(hi), unit);
+    }
+    text
+}
+
+/// Upright before italic before oblique
+fn style_order(style: &str) -> u8 {
+    match style {
+        "normal" => 0,
+        "italic" => 1,
+        _ => 2,
+    }
+}
+
+/// A number with at most two decimals, without trailing zeros
+fn number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coverage, NameTable, Tag, VariationAxis};
+
+    fn face(file: &str, container: Container, weight: i32) -> FontInfo {
+        FontInfo {
+            family_name: "Inter".to_string(),
+            style_name: "Regular".to_string(),
+            file_path: file.to_string(),
+            container,
+            outline: Some(OutlineFormat::TrueType),
+            weight,
+            width: 5,
+            coverage: Coverage::from_ranges([(0x20, 0x7E), (0xE9, 0xE9)]),
+            ..Default::default()
+        }
+    }
+
+    fn axis(tag: &[u8; 4], min_value: f32, max_value: f32) -> VariationAxis {
+        VariationAxis {
+            tag: Tag::new(tag),
+            min_value,
+            default_value: min_value.max(0.0).min(max_value),
+            max_value,
+            ..Default::default()
+        }
+    }
+
+    fn metrics() -> Metrics {
+        Metrics {
+            units_per_em: 2000,
+            ascender: 1900,
+            descender: -500,
+            line_gap: 0,
+            typo_ascender: Some(1600),
+            typo_descender: Some(-400),
+            typo_line_gap: Some(400),
+            use_typo_metrics: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn copies_share_a_rule_with_woff2_first() {
+        let fonts = [
+            face("/fonts/Inter-Bold.ttf", Container::Sfnt, 700),
+            face("/fonts/Inter-Regular.ttf", Container::Sfnt, 400),
+            face("/web/Inter-Regular.woff2", Container::Woff2, 400),
+            face("/web/Inter-Regular.woff", Container::Woff, 400),
+        ];
+        let options = FontFaceOptions {
+            base_url: "/static/".to_string(),
+            ..Default::default()
+        };
+        let rules = font_face_rules(&fonts, "inter", &options);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].family, "Inter");
+        assert_eq!(rules[0].weight, (400.0, 400.0));
+        assert_eq!(
+            rules[0].sources,
+            [
+                r#"url("/static/Inter-Regular.woff2") format("woff2")"#,
+                r#"url("/static/Inter-Regular.woff") format("woff")"#,
+                r#"url("/static/Inter-Regular.ttf") format("truetype")"#,
+            ]
+        );
+        assert_eq!(rules[1].weight, (700.0, 700.0));
+        assert!(font_face_rules(&fonts, "Roboto", &options).is_empty());
+    }
+
+    #[test]
+    fn variable_fonts_get_ranges_and_ital_two_rules() {
+        let mut font = face("Inter.ttf", Container::Sfnt, 400);
+        font.variable_axes = vec![
+            axis(b"wght", 100.0, 900.0),
+            axis(b"wdth", 75.0, 100.0),
+            axis(b"ital", 0.0, 1.0),
+        ];
+        let rules = font_face_rules(&[font.clone()], "Inter", &FontFaceOptions::default());
+        let styles: Vec<&str> = rules.iter().map(|r| r.style.as_str()).collect();
+        assert_eq!(styles, ["normal", "italic"]);
+        assert_eq!(rules[0].weight, (100.0, 900.0));
+        assert_eq!(rules[0].stretch, (75.0, 100.0));
+
+        // slnt -10..0 leans right by 0 to 10 degrees
+        font.variable_axes = vec![axis(b"slnt", -10.0, 0.0)];
+        let rules = font_face_rules(&[font], "Inter", &FontFaceOptions::default());
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].style, "oblique 0deg 10deg");
+    }
+
+    #[test]
+    fn faces_without_files_use_local_names() {
+        let mut font = face("", Container::Sfnt, 400);
+        font.names = NameTable::english(&[
+            (NameId::FULL_NAME, "Inter Regular"),
+            (NameId::POSTSCRIPT_NAME, "Inter-Regular"),
+        ]);
+        let rules = font_face_rules(&[font.clone()], "Inter", &FontFaceOptions::default());
+        assert_eq!(
+            rules[0].sources,
+            [r#"local("Inter Regular")"#, r#"local("Inter-Regular")"#]
+        );
+
+        // With a file, local() is only tried first on request
+        font.file_path = "Inter.otf".to_string();
+        font.outline = Some(OutlineFormat::Cff);
+        let options = FontFaceOptions {
+            local: true,
+            ..Default::default()
+        };
+        let rules = font_face_rules(&[font], "Inter", &options);
+        assert_eq!(rules[0].sources.len(), 3);
+        assert_eq!(
+            rules[0].sources[2],
+            r#"url("Inter.otf") format("opentype")"#
+        );
+    }
+
+    #[test]
+    fn overrides_follow_the_line_metrics() {
+        let mut metrics = metrics();
+        let overrides = MetricOverrides::of(&metrics).unwrap();
+        assert_eq!(
+            overrides,
+            MetricOverrides {
+                size_adjust: 100.0,
+                ascent: 80.0,
+                descent: 20.0,
+                line_gap: 20.0,
+            }
+        );
+        metrics.use_typo_metrics = false;
+        assert_eq!(MetricOverrides::of(&metrics).unwrap().ascent, 95.0);
+        metrics.units_per_em = 0;
+        assert_eq!(MetricOverrides::of(&metrics), None);
+    }
+
+    #[test]
+    fn rules_display_as_css() {
+        let mut font = face("Inter.woff2", Container::Woff2, 400);
+        font.metrics = Some(metrics());
+        font.variable_axes = vec![axis(b"wght", 100.0, 900.0)];
+        let options = FontFaceOptions {
+            metric_overrides: true,
+            ..Default::default()
+        };
+        let rules = font_face_rules(&[font], "Inter", &options);
+        assert_eq!(
+            rules[0].to_string(),
+            "@font-face {
+  font-family: \"Inter\";
+  src: url(\"Inter.woff2\") format(\"woff2\");
+  font-weight: 100 900;
+  font-stretch: 100%;
+  font-style: normal;
+  unicode-range: U+0020-007E, U+00E9;
+  ascent-override: 80%;
+  descent-override: 20%;
+  line-gap-override: 20%;
+}"
+        );
+        assert_eq!(number(33.333), "33.33");
+        assert_eq!(number(-0.001), "0");
+    }
+
+    #[test]
+    fn css_strings_escape_quotes_markup_and_controls() {
+        assert_eq!(css_string(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(css_string("</style>"), "\\3C /style\\3E ");
+        assert_eq!(css_string("a\nb\u{7F}"), "a\\A b\\7F ");
+        assert_eq!(css_string("Noto Sans CJK 日本"), "Noto Sans CJK 日本");
+    }
+}
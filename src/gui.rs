@@ -0,0 +1,718 @@
+//! Win32 front end - the main window and its child controls
+//!
+//! The window is one consumer of the `fontenum` library: the three toolbar
+//! buttons run the GDI, DirectWrite and FontSet sources and the results are
+//! shown in a ListView with a live filter and a preview panel.
+//!
+//! ## Architecture Overview
+//!
+//! The application follows a typical Win32 GUI structure:
+//! - Single main window with child controls (buttons, listview, preview panel)
+//! - Thread-local application state (AppState) to avoid global mutable statics
+//! - Message-driven event handling through the window procedure (wnd_proc)
+//!
+//! ## Code Organization
+//!
+//! 1. Imports & Constants
+//! 2. Data Structures - AppState
+//! 3. Entry Point - run()
+//! 4. Window Procedure - wnd_proc() handles all window messages
+//! 5. UI Creation & Layout - create_controls(), resize_controls()
+//! 6. Font Enumeration - enumerate_fonts() runs a library `FontSource`
+//! 7. Filtering & Display - apply_filter(), populate_list_view(), etc.
+
+use fontenum::{
+    Column, DirectWriteSource, EnumMode, FontInfo, FontSetSource, FontSource, GdiSource,
+};
+use std::cell::RefCell;
+use std::ffi::c_void;
+use windows::{
+    core::*,
+    Win32::{
+        Foundation::*, Graphics::Gdi::*, System::LibraryLoader::GetModuleHandleW, UI::Controls::*,
+        UI::WindowsAndMessaging::*,
+    },
+};
+
+// ============================================================================
+// CONSTANTS - Control IDs for child windows
+// ============================================================================
+// These IDs are used to identify controls in WM_COMMAND and WM_NOTIFY messages
+
+const IDC_LISTVIEW: u16 = 1001; // Main font list
+const IDC_GDI_BUTTON: u16 = 1002; // "GDI" enumeration button
+const IDC_DWRITE_BUTTON: u16 = 1003; // "DirectWrite" enumeration button
+const IDC_FONTSET_BUTTON: u16 = 1004; // "FontSet API" enumeration button
+const IDC_PREVIEW_STATIC: u16 = 1005; // Font preview panel
+const IDC_STATUS_LABEL: u16 = 1006; // Status text showing font count
+const IDC_SEARCH_EDIT: u16 = 1007; // Filter text input
+const IDC_SEARCH_LABEL: u16 = 1008; // "Filter:" label
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Application state stored in thread-local storage
+///
+/// Win32 callbacks (like wnd_proc) can't easily access Rust structs,
+/// so we use thread_local! with RefCell to provide interior mutability.
+#[derive(Default)]
+struct AppState {
+    // Window handles
+    hwnd: HWND,            // Main window
+    h_instance: HINSTANCE, // Application instance
+    list_view: HWND,       // ListView control
+    status_label: HWND,    // Status text control
+    search_edit: HWND,     // Filter input control
+    preview_static: HWND,  // Preview panel control
+
+    // Font data
+    fonts: Vec<FontInfo>,         // All enumerated fonts
+    filtered_indices: Vec<usize>, // Indices of fonts matching filter
+    filter_text: String,          // Current filter string
+    current_mode: EnumMode,       // Which API was used for enumeration
+    selected_font: String,        // Currently selected font family
+}
+
+// Thread-local storage for application state
+// This pattern avoids unsafe global mutable statics while allowing
+// the window procedure callback to access application data
+thread_local! {
+    static APP_STATE: RefCell<AppState> = RefCell::new(AppState::default());
+}
+
+// ============================================================================
+// ENTRY POINT
+// ============================================================================
+
+/// Creates the main window and runs the message loop until it is closed
+pub fn run() -> Result<()> {
+    unsafe {
+        let instance: HINSTANCE = GetModuleHandleW(None)?.into();
+
+        // Initialize common controls (required for ListView)
+        let icex = INITCOMMONCONTROLSEX {
+            dwSize: std::mem::size_of::<INITCOMMONCONTROLSEX>() as u32,
+            dwICC: ICC_LISTVIEW_CLASSES,
+        };
+        let _ = InitCommonControlsEx(&icex);
+
+        // Register the main window class
+        let class_name = w!("FontEnumRustWindowClass");
+        let wc = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            style: CS_HREDRAW | CS_VREDRAW, // Redraw on size change
+            lpfnWndProc: Some(wnd_proc),    // Message handler
+            hInstance: instance,
+            hCursor: LoadCursorW(None, IDC_ARROW)?,
+            hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as *mut c_void),
+            lpszClassName: class_name,
+            hIcon: LoadIconW(None, IDI_APPLICATION)?,
+            hIconSm: LoadIconW(None, IDI_APPLICATION)?,
+            ..Default::default()
+        };
+
+        if RegisterClassExW(&wc) == 0 {
+            return Err(Error::from_win32());
+        }
+
+        // Create the main window
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!("Font Enumerator (Rust) - GDI, DirectWrite & FontSet API"),
+            WS_OVERLAPPEDWINDOW,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT, // Default position
+            1100,
+            650, // Initial size
+            HWND::default(),
+            HMENU::default(),
+            instance,
+            None,
+        )?;
+
+        // Store handles in app state for later use
+        APP_STATE.with(|state| {
+            let mut s = state.borrow_mut();
+            s.hwnd = hwnd;
+            s.h_instance = instance;
+        });
+
+        let _ = ShowWindow(hwnd, SW_SHOW);
+        let _ = UpdateWindow(hwnd);
+
+        // Standard Win32 message loop
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).into() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// WINDOW PROCEDURE - Main message handler
+// ============================================================================
+
+/// Handles all window messages for the main window
+///
+/// Key messages handled:
+/// - WM_CREATE: Initialize child controls
+/// - WM_SIZE: Resize controls to fit window
+/// - WM_COMMAND: Button clicks and edit control changes
+/// - WM_NOTIFY: ListView selection changes
+/// - WM_DESTROY: Clean up and exit
+unsafe extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            create_controls(hwnd);
+            LRESULT(0)
+        }
+
+        WM_SIZE => {
+            resize_controls(hwnd);
+            LRESULT(0)
+        }
+
+        // Handle button clicks and edit control notifications
+        WM_COMMAND => {
+            let control_id = (wparam.0 & 0xFFFF) as u16;
+            let notification = ((wparam.0 >> 16) & 0xFFFF) as u16;
+
+            match control_id {
+                IDC_GDI_BUTTON => enumerate_fonts(&GdiSource),
+                IDC_DWRITE_BUTTON => enumerate_fonts(&DirectWriteSource),
+                IDC_FONTSET_BUTTON => enumerate_fonts(&FontSetSource),
+
+                // Filter text changed - reapply filter
+                IDC_SEARCH_EDIT if notification == EN_CHANGE as u16 => {
+                    let mut buffer = [0u16; 256];
+                    APP_STATE.with(|state| {
+                        let state = state.borrow();
+                        let _ = GetWindowTextW(state.search_edit, &mut buffer);
+                    });
+                    let filter = String::from_utf16_lossy(&buffer)
+                        .trim_end_matches('\0')
+                        .to_string();
+                    APP_STATE.with(|state| {
+                        state.borrow_mut().filter_text = filter;
+                    });
+                    apply_filter();
+                }
+                _ => {}
+            }
+            LRESULT(0)
+        }
+
+        // Handle ListView notifications (selection changes)
+        WM_NOTIFY => {
+            let nmhdr = &*(lparam.0 as *const NMHDR);
+
+            // Check if notification is from our ListView
+            if nmhdr.idFrom == IDC_LISTVIEW as usize && nmhdr.code == LVN_ITEMCHANGED {
+                let nmlv = &*(lparam.0 as *const NMLISTVIEW);
+
+                // Only respond to selection (not deselection)
+                if (nmlv.uNewState & LVIS_SELECTED.0) != 0 {
+                    // Extract font info from app state
+                    let (preview_hwnd, font_name, font_weight, font_italic, style_name) = APP_STATE
+                        .with(|state| {
+                            let mut state = state.borrow_mut();
+                            if let Some(&idx) = state.filtered_indices.get(nmlv.iItem as usize) {
+                                if idx < state.fonts.len() {
+                                    let font = &state.fonts[idx];
+                                    let family_name = font.family_name.clone();
+                                    let style_name = font.style_name.clone();
+                                    let weight = font.weight;
+                                    let italic = font.italic;
+                                    state.selected_font = family_name.clone();
+                                    return (
+                                        state.preview_static,
+                                        family_name,
+                                        weight,
+                                        italic,
+                                        style_name,
+                                    );
+                                }
+                            }
+                            (HWND::default(), String::new(), 400, false, String::new())
+                        });
+
+                    // Update the preview panel with selected font
+                    if preview_hwnd != HWND::default() && !font_name.is_empty() {
+                        // Create a font handle with the selected family, weight, and italic
+                        let font_name_wide: Vec<u16> =
+                            font_name.encode_utf16().chain(std::iter::once(0)).collect();
+                        let hfont = CreateFontW(
+                            32, // Height in logical units (pixels at 96 DPI)
+                            0,
+                            0,
+                            0,
+                            font_weight, // Use actual weight (400, 700, etc.)
+                            if font_italic { 1 } else { 0 }, // Use actual italic flag
+                            0,
+                            0, // No underline/strikeout
+                            DEFAULT_CHARSET.0 as u32,
+                            OUT_DEFAULT_PRECIS.0 as u32,
+                            CLIP_DEFAULT_PRECIS.0 as u32,
+                            CLEARTYPE_QUALITY.0 as u32,
+                            (DEFAULT_PITCH.0 | FF_DONTCARE.0) as u32,
+                            PCWSTR(font_name_wide.as_ptr()),
+                        );
+
+                        // Apply the font to the preview control
+                        let _ = SendMessageW(
+                            preview_hwnd,
+                            WM_SETFONT,
+                            WPARAM(hfont.0 as usize),
+                            LPARAM(1),
+                        );
+
+                        // Set preview text showing font name and sample characters
+                        let preview_text = format!(
+                            "{} {}\r\n\r\nAaBbCcDdEeFfGgHhIiJjKk\r\n\r\n0123456789 !@#$%",
+                            font_name, style_name
+                        );
+                        let preview_wide: Vec<u16> = preview_text
+                            .encode_utf16()
+                            .chain(std::iter::once(0))
+                            .collect();
+                        let _ = SetWindowTextW(preview_hwnd, PCWSTR(preview_wide.as_ptr()));
+                    }
+                }
+            }
+            LRESULT(0)
+        }
+
+        // Set minimum window size
+        WM_GETMINMAXINFO => {
+            let mmi = &mut *(lparam.0 as *mut MINMAXINFO);
+            mmi.ptMinTrackSize.x = 800;
+            mmi.ptMinTrackSize.y = 500;
+            LRESULT(0)
+        }
+
+        WM_DESTROY => {
+            PostQuitMessage(0);
+            LRESULT(0)
+        }
+
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+// ============================================================================
+// UI CREATION & LAYOUT
+// ============================================================================
+
+/// Creates all child controls for the main window
+///
+/// Layout:
+/// ```text
+/// +------------------------------------------------------------------+
+/// | [GDI] [DirectWrite] [FontSet API]  Filter: [____]  Status text   |
+/// +--------------------------------+--------------------------------+
+/// |                                |                                 |
+/// |         ListView               |        Preview Panel            |
+/// |     (font list table)          |    (sample text in font)        |
+/// |                                |                                 |
+/// +--------------------------------+---------------------------------+
+/// ```
+unsafe fn create_controls(hwnd: HWND) {
+    let instance = APP_STATE.with(|state| state.borrow().h_instance);
+
+    // --- Toolbar buttons ---
+    let _ = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("BUTTON"),
+        w!("GDI"),
+        WS_CHILD | WS_VISIBLE | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+        10,
+        10,
+        80,
+        30,
+        hwnd,
+        HMENU(IDC_GDI_BUTTON as *mut c_void),
+        instance,
+        None,
+    );
+
+    let _ = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("BUTTON"),
+        w!("DirectWrite"),
+        WS_CHILD | WS_VISIBLE | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+        100,
+        10,
+        100,
+        30,
+        hwnd,
+        HMENU(IDC_DWRITE_BUTTON as *mut c_void),
+        instance,
+        None,
+    );
+
+    let _ = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("BUTTON"),
+        w!("FontSet API"),
+        WS_CHILD | WS_VISIBLE | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+        210,
+        10,
+        100,
+        30,
+        hwnd,
+        HMENU(IDC_FONTSET_BUTTON as *mut c_void),
+        instance,
+        None,
+    );
+
+    // --- Filter controls ---
+    let _ = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("STATIC"),
+        w!("Filter:"),
+        WS_CHILD | WS_VISIBLE,
+        330,
+        17,
+        40,
+        20,
+        hwnd,
+        HMENU(IDC_SEARCH_LABEL as *mut c_void),
+        instance,
+        None,
+    );
+
+    let search_edit = CreateWindowExW(
+        WS_EX_CLIENTEDGE, // Sunken edge style
+        w!("EDIT"),
+        w!(""),
+        WS_CHILD | WS_VISIBLE | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+        375,
+        12,
+        180,
+        24,
+        hwnd,
+        HMENU(IDC_SEARCH_EDIT as *mut c_void),
+        instance,
+        None,
+    )
+    .unwrap_or_default();
+
+    // --- Status label ---
+    let status_label = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("STATIC"),
+        w!("Click a button to enumerate fonts"),
+        WS_CHILD | WS_VISIBLE,
+        570,
+        17,
+        350,
+        20,
+        hwnd,
+        HMENU(IDC_STATUS_LABEL as *mut c_void),
+        instance,
+        None,
+    )
+    .unwrap_or_default();
+
+    // --- ListView (font list) ---
+    let list_view = CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("SysListView32"),
+        w!(""),
+        WS_CHILD
+            | WS_VISIBLE
+            | WINDOW_STYLE((LVS_REPORT | LVS_SINGLESEL | LVS_SHOWSELALWAYS) as u32),
+        10,
+        50,
+        600,
+        500,
+        hwnd,
+        HMENU(IDC_LISTVIEW as *mut c_void),
+        instance,
+        None,
+    )
+    .unwrap_or_default();
+
+    // Enable modern ListView features
+    let _ = SendMessageW(
+        list_view,
+        LVM_SETEXTENDEDLISTVIEWSTYLE,
+        WPARAM(0),
+        LPARAM((LVS_EX_FULLROWSELECT | LVS_EX_GRIDLINES | LVS_EX_DOUBLEBUFFER) as isize),
+    );
+
+    // Add columns to ListView
+    add_column(list_view, 0, "Font Family", 180);
+    add_column(list_view, 1, "Style", 100);
+    add_column(list_view, 2, "Weight", 60);
+    add_column(list_view, 3, "Width", 50);
+    add_column(list_view, 4, "Italic", 50);
+    add_column(list_view, 5, "Fixed", 50);
+    add_column(list_view, 6, "File Path", 180);
+    add_column(list_view, 7, "Variable Axes", 180);
+
+    // --- Preview panel ---
+    // Using multiline EDIT control (read-only) for easy font display
+    // ES_MULTILINE = 0x0004, ES_READONLY = 0x0800
+    let preview_static = CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        w!("Select a font to preview"),
+        WS_CHILD | WS_VISIBLE | WINDOW_STYLE(0x0004 | 0x0800),
+        620,
+        50,
+        350,
+        400,
+        hwnd,
+        HMENU(IDC_PREVIEW_STATIC as *mut c_void),
+        instance,
+        None,
+    )
+    .unwrap_or_default();
+
+    // Store control handles in app state
+    APP_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.list_view = list_view;
+        state.status_label = status_label;
+        state.search_edit = search_edit;
+        state.preview_static = preview_static;
+    });
+}
+
+/// Helper function to add a column to the ListView
+unsafe fn add_column(list_view: HWND, index: i32, text: &str, width: i32) {
+    let text_wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let col = LVCOLUMNW {
+        mask: LVCF_TEXT | LVCF_WIDTH | LVCF_SUBITEM,
+        cx: width,
+        pszText: PWSTR(text_wide.as_ptr() as *mut u16),
+        iSubItem: index,
+        ..Default::default()
+    };
+    let _ = SendMessageW(
+        list_view,
+        LVM_INSERTCOLUMNW,
+        WPARAM(index as usize),
+        LPARAM(&col as *const _ as isize),
+    );
+}
+
+/// Resizes child controls when the window size changes
+///
+/// The layout splits the content area 60/40 between the list and preview panel.
+unsafe fn resize_controls(hwnd: HWND) {
+    let mut rect = RECT::default();
+    let _ = GetClientRect(hwnd, &mut rect);
+
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    let list_height = height - 70; // Leave space for toolbar
+
+    // Calculate widths - 60% for list, 40% for preview
+    let list_w = ((width - 40) * 60) / 100;
+    let preview_x = list_w + 20;
+    let preview_w = width - preview_x - 10;
+
+    APP_STATE.with(|state| {
+        let state = state.borrow();
+        let _ = MoveWindow(state.list_view, 10, 50, list_w, list_height, true);
+        let _ = MoveWindow(
+            state.preview_static,
+            preview_x,
+            50,
+            preview_w,
+            list_height,
+            true,
+        );
+    });
+}
+
+// ============================================================================
+// FONT ENUMERATION
+// ============================================================================
+
+/// Runs a font source and stores its results in the app state
+///
+/// The enumeration itself lives in the `fontenum` library; the window only
+/// consumes the resulting `Vec<FontInfo>`. Errors are reported in a message box.
+fn enumerate_fonts(source: &dyn FontSource) {
+    let fonts = match source.enumerate() {
+        Ok(fonts) => fonts,
+        Err(e) => {
+            show_error(&e.to_string());
+            return;
+        }
+    };
+
+    // Update app state with enumerated fonts
+    APP_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.fonts = fonts;
+        state.current_mode = source.mode();
+        state.selected_font.clear();
+    });
+
+    apply_filter();
+}
+
+/// Shows an error message box owned by the main window
+fn show_error(message: &str) {
+    let message_wide: Vec<u16> = message.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        APP_STATE.with(|state| {
+            let state = state.borrow();
+            let _ = MessageBoxW(
+                state.hwnd,
+                PCWSTR(message_wide.as_ptr()),
+                w!("Error"),
+                MB_OK | MB_ICONERROR,
+            );
+        });
+    }
+}
+
+// ============================================================================
+// FILTERING & DISPLAY
+// ============================================================================
+
+/// Applies the current filter text to the font list
+///
+/// Stores the indices of matching fonts (see `fontenum::apply_filter`)
+/// and refreshes the ListView and status text.
+fn apply_filter() {
+    APP_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let indices = fontenum::apply_filter(&state.fonts, &state.filter_text);
+        state.filtered_indices = indices;
+    });
+
+    populate_list_view();
+    update_status_text();
+
+    // Invalidate preview to clear selection
+    unsafe {
+        APP_STATE.with(|state| {
+            let state = state.borrow();
+            let _ = InvalidateRect(state.preview_static, None, true);
+        });
+    }
+}
+
+/// Populates the ListView with filtered font data
+fn populate_list_view() {
+    unsafe {
+        APP_STATE.with(|state| {
+            let state = state.borrow();
+
+            // Clear existing items
+            let _ = SendMessageW(state.list_view, LVM_DELETEALLITEMS, WPARAM(0), LPARAM(0));
+
+            // Add each filtered font to the list
+            for (i, &font_idx) in state.filtered_indices.iter().enumerate() {
+                let font = &state.fonts[font_idx];
+
+                // Insert main item (family name)
+                let family_wide: Vec<u16> = font
+                    .family_name
+                    .encode_utf16()
+                    .chain(std::iter::once(0))
+                    .collect();
+                let item = LVITEMW {
+                    mask: LVIF_TEXT | LVIF_PARAM,
+                    iItem: i as i32,
+                    iSubItem: 0,
+                    pszText: PWSTR(family_wide.as_ptr() as *mut u16),
+                    lParam: LPARAM(font_idx as isize), // Store original index for selection handling
+                    ..Default::default()
+                };
+                let _ = SendMessageW(
+                    state.list_view,
+                    LVM_INSERTITEMW,
+                    WPARAM(0),
+                    LPARAM(&item as *const _ as isize),
+                );
+
+                // Set subitem columns
+                set_list_item_text(state.list_view, i as i32, 1, &font.style_name);
+                set_list_item_text(state.list_view, i as i32, 2, &font.weight.to_string());
+                set_list_item_text(state.list_view, i as i32, 3, &Column::Width.value(font));
+                set_list_item_text(
+                    state.list_view,
+                    i as i32,
+                    4,
+                    if font.italic { "Yes" } else { "No" },
+                );
+                set_list_item_text(
+                    state.list_view,
+                    i as i32,
+                    5,
+                    if font.fixed_pitch { "Yes" } else { "No" },
+                );
+                set_list_item_text(state.list_view, i as i32, 6, &Column::FilePath.value(font));
+
+                let var_str = if font.is_variable {
+                    format!("Yes: {}", font.variable_axes_string())
+                } else {
+                    String::new()
+                };
+                set_list_item_text(state.list_view, i as i32, 7, &var_str);
+            }
+        });
+    }
+}
+
+/// Helper to set text for a ListView subitem
+unsafe fn set_list_item_text(list_view: HWND, item: i32, subitem: i32, text: &str) {
+    let text_wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let lvi = LVITEMW {
+        iSubItem: subitem,
+        pszText: PWSTR(text_wide.as_ptr() as *mut u16),
+        ..Default::default()
+    };
+    let _ = SendMessageW(
+        list_view,
+        LVM_SETITEMTEXTW,
+        WPARAM(item as usize),
+        LPARAM(&lvi as *const _ as isize),
+    );
+}
+
+/// Updates the status label with current font count
+fn update_status_text() {
+    unsafe {
+        APP_STATE.with(|state| {
+            let state = state.borrow();
+
+            let mode_str = state.current_mode.label();
+
+            let status = if state.filter_text.is_empty() {
+                format!(
+                    "{} Enumeration: Found {} fonts",
+                    mode_str,
+                    state.fonts.len()
+                )
+            } else {
+                format!(
+                    "{} Enumeration: Showing {} of {} fonts",
+                    mode_str,
+                    state.filtered_indices.len(),
+                    state.fonts.len()
+                )
+            };
+
+            let status_wide: Vec<u16> = status.encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = SetWindowTextW(state.status_label, PCWSTR(status_wide.as_ptr()));
+        });
+    }
+}
@@ -0,0 +1,96 @@
+//! fontenum - Font enumeration core shared by the Font Enumerator front ends
+//!
+//! This crate holds everything that is not tied to a window:
+//! - `FontInfo` - the per-face record every source produces
+//! - `FontSource` - the trait implemented by each enumeration back end
+//! - `apply_filter` - the filter used by the list view (family/style text
+//!   plus `key:value` terms such as `width:condensed`)
+//! - `fonts_covering` - which fonts can render a piece of text, and what
+//!   each one is missing
+//! - `expand_named_instances` - one row per named instance of variable fonts
+//! - `match_font` - the face a browser would pick for a CSS font request
+//! - `fallback_chain` - which font renders each run of a text, with the
+//!   per-script fallback chains behind the choice
+//! - `embedding_audit` - fonts grouped by fsType embedding permission
+//! - `Inventory` - JSON/NDJSON export of an enumeration result, and
+//!   `write_delimited` for CSV/TSV with chosen columns
+//! - `Snapshot`, `diff_snapshots` - saved inventories and what changed
+//!   between two of them
+//! - `write_specimen` - a self-contained HTML catalog of the fonts
+//! - `font_face_rules` - CSS `@font-face` rules for self-hosting a family
+//! - `rank_metric_fallbacks` - installed fonts that can stand in for a web
+//!   font, with the `size-adjust`/`*-override` descriptors that match them
+//! - `validate_paths` - structural and style-linking checks of font files
+//!
+//! ## Sources
+//!
+//! On Windows three system sources are available (behind `cfg(windows)`):
+//! 1. `GdiSource` - EnumFontFamiliesEx, available on all Windows versions
+//! 2. `DirectWriteSource` - IDWriteFontCollection, better Unicode support
+//! 3. `FontSetSource` - IDWriteFontSet (Windows 10+), file paths and variable axes
+//!
+//! `DirectorySource` works everywhere: it walks directories and parses
+//! .ttf/.otf/.ttc files itself, filling the same `FontInfo` fields.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use fontenum::{apply_filter, FontSource};
+//!
+//! # #[cfg(windows)]
+//! # fn run() -> fontenum::Result<()> {
+//! let fonts = fontenum::FontSetSource.enumerate()?;
+//! for &i in &apply_filter(&fonts, "segoe") {
+//!     println!("{} {}", fonts[i].family_name, fonts[i].style_name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod columns;
+mod coverage;
+mod embedding;
+mod error;
+mod export;
+mod fallback;
+mod filter;
+mod font_info;
+mod fontface;
+mod matching;
+mod metric_fallback;
+mod sfnt;
+mod snapshot;
+mod sources;
+mod specimen;
+mod style;
+mod validate;
+
+pub use columns::Column;
+pub use coverage::{
+    fonts_covering, is_codepoint_list, parse_codepoints, text_characters, CoverageMatch,
+};
+pub use embedding::{embedding_audit, AuditGroup, EmbeddingRights};
+pub use error::{Error, Result};
+pub use export::{write_delimited, ExportFormat, Inventory, INVENTORY_VERSION};
+pub use fallback::{fallback_chain, Fallback, FallbackRun, DEFAULT_SCRIPT};
+pub use filter::{apply_filter, Filter};
+pub use font_info::{expand_named_instances, FontInfo};
+pub use fontface::{font_face_rules, FontFaceOptions, FontFaceRule, MetricOverrides};
+pub use matching::{match_font, FontMatch, FontRequest, FontStyle};
+pub use metric_fallback::{metric_overrides, rank_metric_fallbacks, MetricFallback};
+pub use sfnt::{
+    read_fonts, ColorFormat, ColorFormats, Container, Coverage, Embedding, FamilyClass,
+    FsSelection, FsType, LanguageSystem, Layout, LocalizedName, Metrics, Monospace, NameId,
+    NameTable, NamedInstance, Os2, OutlineFormat, Panose, TableRecord, Tag, VariationAxis,
+};
+pub use snapshot::{
+    diff_snapshots, FaceChange, FaceRecord, FamilyDiff, FieldChange, Snapshot, SnapshotDiff,
+    SNAPSHOT_VERSION,
+};
+pub use sources::{load_font_file, open_source, DirectorySource, EnumMode, FontSource};
+pub use specimen::{write_specimen, SpecimenFonts};
+pub use style::{check_face, check_families};
+pub use validate::{validate_font_data, validate_paths, Check, Finding, FontReport, Severity};
+
+#[cfg(windows)]
+pub use sources::{DirectWriteSource, FontSetSource, GdiSource};
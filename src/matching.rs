@@ -0,0 +1,644 @@
+//! CSS Fonts Level 4 font matching over an enumerated collection
+//!
+//! Predicts which face a browser picks for a `font-family`, `font-weight`,
+//! `font-stretch` and `font-style` request (CSS Fonts 4, section 5.2):
+//! 1. the first family in the list that has any faces wins
+//! 2. font-stretch narrows the faces to the nearest width
+//! 3. font-style narrows them to italic, oblique or normal faces
+//! 4. font-weight narrows them to the nearest weight
+//!
+//! Variable fonts take part with the ranges of their `wght`, `wdth`,
+//! `ital` and `slnt` axes, and the result says where to set those axes.
+//! Each step is recorded as a line of reasoning. Generic families
+//! (`serif`, `sans-serif`, ...) are platform settings and are skipped.
+
+use std::fmt;
+
+use crate::font_info::width_percent;
+use crate::{Error, FontInfo, NameId, Result, Tag, VariationAxis};
+
+const WGHT: Tag = Tag::new(b"wght");
+const WDTH: Tag = Tag::new(b"wdth");
+const ITAL: Tag = Tag::new(b"ital");
+const SLNT: Tag = Tag::new(b"slnt");
+
+/// The angle of `font-style: oblique` without an explicit angle
+const DEFAULT_OBLIQUE: f32 = 14.0;
+
+/// CSS generic family names, which depend on user settings
+const GENERIC_FAMILIES: &[&str] = &[
+    "serif",
+    "sans-serif",
+    "monospace",
+    "cursive",
+    "fantasy",
+    "system-ui",
+    "ui-serif",
+    "ui-sans-serif",
+    "ui-monospace",
+    "ui-rounded",
+    "math",
+    "emoji",
+    "fangsong",
+];
+
+/// A CSS `font-style` value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique(f32), // Angle in degrees, positive leans right
+}
+
+impl fmt::Display for FontStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontStyle::Normal => write!(f, "normal"),
+            FontStyle::Italic => write!(f, "italic"),
+            FontStyle::Oblique(angle) => write!(f, "oblique {}deg", angle),
+        }
+    }
+}
+
+/// The font properties a CSS rule asks for
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontRequest {
+    pub families: Vec<String>, // font-family list, in order of preference
+    pub weight: f32,           // font-weight, 1-1000
+    pub stretch: f32,          // font-stretch, % of normal width
+    pub style: FontStyle,
+}
+
+impl Default for FontRequest {
+    fn default() -> Self {
+        FontRequest {
+            families: Vec::new(),
+            weight: 400.0,
+            stretch: 100.0,
+            style: FontStyle::Normal,
+        }
+    }
+}
+
+impl FontRequest {
+    /// Parses a request written like the CSS `font` shorthand, minus the size
+    ///
+    /// Optional style, weight and stretch values come first, followed by the
+    /// comma-separated family list, e.g. `italic bold condensed "Segoe UI", Arial`.
+    /// Accepts `normal`, `italic`, `oblique [N]deg`, weights 1-1000,
+    /// `bold`, stretch keywords and percentages.
+    pub fn parse(css: &str) -> Result<Self> {
+        let mut request = FontRequest::default();
+        let mut rest = css.trim();
+
+        while let Some((word, after)) = split_word(rest) {
+            let lower = word.to_ascii_lowercase();
+            if lower == "normal" {
+                // Resets nothing; valid for each of the three properties
+            } else if lower == "italic" {
+                request.style = FontStyle::Italic;
+            } else if lower == "oblique" {
+                request.style = FontStyle::Oblique(DEFAULT_OBLIQUE);
+                if let Some((angle, after_angle)) = split_word(after) {
+                    if let Some(angle) = angle.strip_suffix("deg").and_then(|a| a.parse().ok()) {
+                        request.style = FontStyle::Oblique(angle);
+                        rest = after_angle;
+                        continue;
+                    }
+                }
+            } else if lower == "bold" {
+                request.weight = 700.0;
+            } else if let Some(weight) = word
+                .parse::<f32>()
+                .ok()
+                .filter(|w| (1.0..=1000.0).contains(w))
+            {
+                request.weight = weight;
+            } else if let Some(stretch) = stretch_keyword(&lower).or_else(|| parse_percent(word)) {
+                request.stretch = stretch;
+            } else {
+                break;
+            }
+            rest = after;
+        }
+
+        request.families = rest
+            .split(',')
+            .map(|f| f.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+        if request.families.is_empty() {
+            return Err(Error::Parse(format!("no font family in '{}'", css)));
+        }
+        Ok(request)
+    }
+}
+
+impl fmt::Display for FontRequest {
+    /// Formats as a `font` shorthand without the size, e.g. `italic 700 75% Arial, Verdana`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}% {}",
+            self.style,
+            self.weight,
+            self.stretch,
+            self.families.join(", ")
+        )
+    }
+}
+
+/// The face chosen for a request, with the reasoning behind it
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontMatch {
+    pub index: usize,                // Index of the face in the collection
+    pub family: String,              // The requested family that matched
+    pub variations: Vec<(Tag, f32)>, // Axis values to apply to a variable face
+    pub synthetic_bold: bool,        // A browser would embolden the face
+    pub synthetic_oblique: bool,     // A browser would slant the face
+    pub reasons: Vec<String>,        // One line per matching step
+}
+
+/// Selects the face a browser would use for a request
+///
+/// Returns None (with no reasoning) only when no requested family has
+/// faces in the collection.
+pub fn match_font(fonts: &[FontInfo], request: &FontRequest) -> Option<FontMatch> {
+    let mut reasons = Vec::new();
+
+    for family in &request.families {
+        if GENERIC_FAMILIES
+            .iter()
+            .any(|g| g.eq_ignore_ascii_case(family))
+        {
+            reasons.push(format!("family '{}': generic family, skipped", family));
+            continue;
+        }
+        let faces: Vec<Face> = fonts
+            .iter()
+            .enumerate()
+            .filter(|(_, font)| has_family(font, family))
+            .map(|(index, font)| Face::new(index, font))
+            .collect();
+        if faces.is_empty() {
+            reasons.push(format!("family '{}': no faces", family));
+            continue;
+        }
+        reasons.push(format!("family '{}': {} faces", family, faces.len()));
+
+        let faces = narrow(faces, |f| stretch_key(f.stretch, request.stretch));
+        reasons.push(format!(
+            "font-stretch {}%: {} at {}",
+            request.stretch,
+            count(faces.len()),
+            describe_range(faces[0].stretch, "%")
+        ));
+
+        let faces = narrow(faces, |f| style_key(f, request.style));
+        reasons.push(format!(
+            "font-style {}: {} {}",
+            request.style,
+            count(faces.len()),
+            faces[0].style_name()
+        ));
+
+        let faces = narrow(faces, |f| weight_key(f.weight, request.weight));
+        reasons.push(format!(
+            "font-weight {}: {} at {}",
+            request.weight,
+            count(faces.len()),
+            describe_range(faces[0].weight, "")
+        ));
+
+        if faces.len() > 1 {
+            reasons.push(format!(
+                "{} faces are equally good; the first is used",
+                faces.len()
+            ));
+        }
+        let face = &faces[0];
+        let font = &fonts[face.index];
+
+        let mut variations = Vec::new();
+        let weight = clamp(request.weight, face.weight);
+        let stretch = clamp(request.stretch, face.stretch);
+        if is_variable(font, WGHT) {
+            variations.push((WGHT, weight));
+        }
+        if is_variable(font, WDTH) {
+            variations.push((WDTH, stretch));
+        }
+        match (request.style, face.italic_axis, face.oblique) {
+            (FontStyle::Italic, true, _) => variations.push((ITAL, 1.0)),
+            (FontStyle::Oblique(angle), _, Some(range)) if is_variable(font, SLNT) => {
+                variations.push((SLNT, -clamp(angle, range)));
+            }
+            _ => {}
+        }
+
+        let synthetic_bold = request.weight >= 600.0 && weight <= 500.0;
+        let synthetic_oblique =
+            request.style != FontStyle::Normal && !face.italic && face.oblique.is_none();
+        if synthetic_bold {
+            reasons.push("no bold face: a browser would synthesize bold".to_string());
+        }
+        if synthetic_oblique {
+            reasons.push("no slanted face: a browser would synthesize an oblique".to_string());
+        }
+
+        return Some(FontMatch {
+            index: face.index,
+            family: family.clone(),
+            variations,
+            synthetic_bold,
+            synthetic_oblique,
+            reasons,
+        });
+    }
+    None
+}
+
+/// What a face (or the design space of a variable face) can render
+pub(crate) struct Face {
+    pub(crate) index: usize,
+    pub(crate) weight: (f32, f32),          // Weight range
+    pub(crate) stretch: (f32, f32),         // Width range in % of normal
+    pub(crate) normal: bool,                // Can render upright
+    pub(crate) italic: bool,                // Can render italic
+    pub(crate) italic_axis: bool,           // Italic through an ital axis
+    pub(crate) oblique: Option<(f32, f32)>, // Oblique angles (positive leans right)
+}
+
+impl Face {
+    pub(crate) fn new(index: usize, font: &FontInfo) -> Self {
+        let axis = |tag| variable_axis(font, tag);
+        let weight = axis(WGHT)
+            .map(|a| (a.min_value, a.max_value))
+            .unwrap_or((font.weight as f32, font.weight as f32));
+        let stretch = axis(WDTH)
+            .map(|a| (a.min_value, a.max_value))
+            .unwrap_or_else(|| {
+                let percent = width_percent(font.width);
+                (percent, percent)
+            });
+
+        // Static faces: the OS/2 OBLIQUE bit tells oblique from italic
+        let fs_oblique = font
+            .os2
+            .as_ref()
+            .map(|o| o.fs_selection.is_oblique())
+            .unwrap_or(false);
+        let static_oblique = font.italic && fs_oblique;
+        let angle = font
+            .metrics
+            .as_ref()
+            .map(|m| -m.italic_angle)
+            .filter(|&a| a != 0.0)
+            .unwrap_or(DEFAULT_OBLIQUE);
+
+        let ital = axis(ITAL);
+        let slnt = axis(SLNT);
+        // slnt is counter-clockwise, CSS oblique angles lean right
+        let oblique = match slnt {
+            Some(slnt) => Some((-slnt.max_value, -slnt.min_value)),
+            None => static_oblique.then_some((angle, angle)),
+        };
+        let upright_slnt = slnt.map(|a| a.min_value <= 0.0 && 0.0 <= a.max_value);
+
+        Face {
+            index,
+            weight,
+            stretch,
+            normal: ital.is_some() || upright_slnt.unwrap_or(!font.italic),
+            italic: ital.is_some() || (font.italic && !static_oblique),
+            italic_axis: ital.is_some(),
+            oblique,
+        }
+    }
+
+    /// What the narrowed faces offer, for the reasoning
+    fn style_name(&self) -> String {
+        match (self.italic, self.oblique) {
+            (true, _) => "italic".to_string(),
+            (false, Some(range)) => format!("oblique {}deg", describe_range(range, "")),
+            (false, None) => "normal".to_string(),
+        }
+    }
+}
+
+/// Keeps the faces with the best (lowest) key
+fn narrow(faces: Vec<Face>, key: impl Fn(&Face) -> (u8, f32)) -> Vec<Face> {
+    let best = faces
+        .iter()
+        .map(&key)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .expect("at least one face");
+    faces.into_iter().filter(|f| key(f) == best).collect()
+}
+
+/// font-stretch order: at or below normal, narrower widths first; above, wider first
+fn stretch_key(range: (f32, f32), desired: f32) -> (u8, f32) {
+    let (lo, hi) = range;
+    match () {
+        _ if lo <= desired && desired <= hi => (0, 0.0),
+        _ if desired <= 100.0 && hi < desired => (1, desired - hi),
+        _ if desired <= 100.0 => (2, lo - desired),
+        _ if lo > desired => (1, lo - desired),
+        _ => (2, desired - hi),
+    }
+}
+
+/// font-weight order: 400-500 look up to 500 first, lighter requests
+/// look lighter first, bolder requests look bolder first
+fn weight_key(range: (f32, f32), desired: f32) -> (u8, f32) {
+    let (lo, hi) = range;
+    if lo <= desired && desired <= hi {
+        return (0, 0.0);
+    }
+    if (400.0..=500.0).contains(&desired) {
+        match () {
+            _ if lo > desired && lo <= 500.0 => (1, lo - desired),
+            _ if hi < desired => (2, desired - hi),
+            _ => (3, lo - desired),
+        }
+    } else if desired < 400.0 {
+        if hi < desired {
+            (1, desired - hi)
+        } else {
+            (2, lo - desired)
+        }
+    } else if lo > desired {
+        (1, lo - desired)
+    } else {
+        (2, desired - hi)
+    }
+}
+
+/// font-style order: the requested style, then the closest slanted
+/// alternative, then the rest
+fn style_key(face: &Face, desired: FontStyle) -> (u8, f32) {
+    let oblique_distance = |angle: f32| {
+        face.oblique
+            .map(|range| (angle - clamp(angle, range)).abs())
+    };
+    match desired {
+        FontStyle::Normal if face.normal => (0, 0.0),
+        FontStyle::Normal => match oblique_distance(0.0) {
+            Some(distance) => (1, distance),
+            None => (2, 0.0),
+        },
+        FontStyle::Italic if face.italic => (0, 0.0),
+        FontStyle::Italic => match oblique_distance(DEFAULT_OBLIQUE) {
+            Some(distance) => (1, distance),
+            None => (2, 0.0),
+        },
+        FontStyle::Oblique(angle) => match oblique_distance(angle) {
+            Some(distance) => (if distance == 0.0 { 0 } else { 1 }, distance),
+            None if face.italic => (2, 0.0),
+            None => (3, 0.0),
+        },
+    }
+}
+
+/// True if any family name of the face (typographic, legacy or WWS) is `family`
+pub(crate) fn has_family(font: &FontInfo, family: &str) -> bool {
+    font.family_name.eq_ignore_ascii_case(family)
+        || [
+            NameId::TYPOGRAPHIC_FAMILY,
+            NameId::FAMILY,
+            NameId::WWS_FAMILY,
+        ]
+        .into_iter()
+        .flat_map(|id| font.names.get(id))
+        .any(|n| n.value.eq_ignore_ascii_case(family))
+}
+
+/// The variable axis `tag`; rows of a named instance are treated as static
+fn variable_axis(font: &FontInfo, tag: Tag) -> Option<&VariationAxis> {
+    if font.instance.is_some() {
+        return None;
+    }
+    font.variable_axes
+        .iter()
+        .find(|a| a.tag == tag && a.is_variable())
+}
+
+fn is_variable(font: &FontInfo, tag: Tag) -> bool {
+    variable_axis(font, tag).is_some()
+}
+
+fn clamp(value: f32, (lo, hi): (f32, f32)) -> f32 {
+    value.max(lo).min(hi)
+}
+
+fn describe_range((lo, hi): (f32, f32), unit: &str) -> String {
+    if lo == hi {
+        format!("{}{}", lo, unit)
+    } else {
+        format!("{}-{}{}", lo, hi, unit)
+    }
+}
+
+fn count(n: usize) -> String {
+    if n == 1 {
+        "1 face".to_string()
+    } else {
+        format!("{} faces", n)
+    }
+}
+
+/// Splits off the first whitespace-separated word
+fn split_word(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some((&text[..end], &text[end..]))
+}
+
+/// CSS font-stretch keywords
+fn stretch_keyword(name: &str) -> Option<f32> {
+    Some(match name {
+        "ultra-condensed" => 50.0,
+        "extra-condensed" => 62.5,
+        "condensed" => 75.0,
+        "semi-condensed" => 87.5,
+        "semi-expanded" => 112.5,
+        "expanded" => 125.0,
+        "extra-expanded" => 150.0,
+        "ultra-expanded" => 200.0,
+        _ => return None,
+    })
+}
+
+fn parse_percent(word: &str) -> Option<f32> {
+    word.strip_suffix('%')?
+        .parse()
+        .ok()
+        .filter(|p: &f32| *p > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FsSelection, Os2};
+
+    fn face(family: &str, weight: i32, width: i32, italic: bool) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            weight,
+            width,
+            italic,
+            ..Default::default()
+        }
+    }
+
+    fn oblique(family: &str) -> FontInfo {
+        FontInfo {
+            os2: Some(Os2 {
+                fs_selection: FsSelection(FsSelection::OBLIQUE),
+                ..Default::default()
+            }),
+            ..face(family, 400, 5, true)
+        }
+    }
+
+    fn variable(family: &str, axes: &[(&[u8; 4], f32, f32)]) -> FontInfo {
+        FontInfo {
+            variable_axes: axes
+                .iter()
+                .map(|&(tag, min_value, max_value)| VariationAxis {
+                    tag: Tag::new(tag),
+                    min_value,
+                    default_value: min_value,
+                    max_value,
+                    ..Default::default()
+                })
+                .collect(),
+            is_variable: true,
+            ..face(family, 400, 5, false)
+        }
+    }
+
+    fn pick(fonts: &[FontInfo], css: &str) -> Option<usize> {
+        match_font(fonts, &FontRequest::parse(css).unwrap()).map(|m| m.index)
+    }
+
+    #[test]
+    fn first_family_with_faces_wins() {
+        let fonts = [face("Arial", 400, 5, false), face("Verdana", 400, 5, false)];
+        assert_eq!(pick(&fonts, "sans-serif, Missing, verdana, Arial"), Some(1));
+        assert_eq!(pick(&fonts, "Missing"), None);
+        let found = match_font(&fonts, &FontRequest::parse("serif, Arial").unwrap()).unwrap();
+        assert_eq!(found.family, "Arial");
+        assert_eq!(found.reasons[0], "family 'serif': generic family, skipped");
+    }
+
+    #[test]
+    fn stretch_narrows_before_style_and_weight() {
+        let fonts = [
+            face("F", 400, 3, false), // Condensed regular
+            face("F", 700, 5, true),  // Normal-width bold italic
+        ];
+        assert_eq!(pick(&fonts, "normal 400 F"), Some(1));
+        assert_eq!(pick(&fonts, "condensed bold italic F"), Some(0));
+
+        // At or below normal, narrower widths are tried first
+        let fonts = [face("F", 400, 4, false), face("F", 400, 6, false)];
+        assert_eq!(pick(&fonts, "F"), Some(0));
+        // Above normal, wider widths are tried first
+        let fonts = [face("F", 400, 6, false), face("F", 400, 8, false)];
+        assert_eq!(pick(&fonts, "expanded F"), Some(1));
+    }
+
+    #[test]
+    fn style_falls_back_through_slanted_faces() {
+        let fonts = [face("F", 400, 5, false), oblique("F")];
+        assert_eq!(pick(&fonts, "italic F"), Some(1));
+        assert_eq!(pick(&fonts, "oblique F"), Some(1));
+        assert_eq!(pick(&fonts, "F"), Some(0));
+
+        let fonts = [face("F", 400, 5, false), face("F", 400, 5, true)];
+        assert_eq!(pick(&fonts, "oblique 10deg F"), Some(1));
+        assert_eq!(pick(&fonts, "italic F"), Some(1));
+    }
+
+    #[test]
+    fn weight_search_direction() {
+        let fonts = [
+            face("F", 300, 5, false),
+            face("F", 600, 5, false),
+            face("F", 500, 5, false),
+        ];
+        // 400-500 look up to 500 first, then lighter
+        assert_eq!(pick(&fonts, "400 F"), Some(2));
+        assert_eq!(pick(&fonts, "450 F"), Some(2));
+        let fonts = [face("F", 300, 5, false), face("F", 600, 5, false)];
+        assert_eq!(pick(&fonts, "450 F"), Some(0));
+        // Lighter requests look lighter first, bolder requests bolder first
+        let fonts = [
+            face("F", 200, 5, false),
+            face("F", 400, 5, false),
+            face("F", 600, 5, false),
+            face("F", 800, 5, false),
+        ];
+        assert_eq!(pick(&fonts, "300 F"), Some(0));
+        assert_eq!(pick(&fonts, "700 F"), Some(3));
+        assert_eq!(pick(&fonts, "900 F"), Some(3));
+        assert_eq!(pick(&fonts, "100 F"), Some(0));
+    }
+
+    #[test]
+    fn variable_faces_get_axis_values() {
+        let fonts = [variable(
+            "V",
+            &[
+                (b"wght", 100.0, 900.0),
+                (b"wdth", 75.0, 100.0),
+                (b"slnt", -10.0, 0.0),
+            ],
+        )];
+        let found = match_font(
+            &fonts,
+            &FontRequest::parse("oblique 8deg 650 125% V").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            found.variations,
+            [(WGHT, 650.0), (WDTH, 100.0), (SLNT, -8.0)]
+        );
+        assert!(!found.synthetic_bold && !found.synthetic_oblique);
+
+        let fonts = [variable("V", &[(b"ital", 0.0, 1.0)])];
+        let found = match_font(&fonts, &FontRequest::parse("italic V").unwrap()).unwrap();
+        assert_eq!(found.variations, [(ITAL, 1.0)]);
+    }
+
+    #[test]
+    fn synthesis_is_reported() {
+        let fonts = [face("F", 400, 5, false)];
+        let found = match_font(&fonts, &FontRequest::parse("italic bold F").unwrap()).unwrap();
+        assert!(found.synthetic_bold);
+        assert!(found.synthetic_oblique);
+    }
+
+    #[test]
+    fn requests_parse_like_the_font_shorthand() {
+        let request =
+            FontRequest::parse("italic 300 semi-condensed \"Segoe UI\", 'Arial'").unwrap();
+        assert_eq!(request.style, FontStyle::Italic);
+        assert_eq!(request.weight, 300.0);
+        assert_eq!(request.stretch, 87.5);
+        assert_eq!(request.families, ["Segoe UI", "Arial"]);
+
+        let request = FontRequest::parse("oblique -5deg 80% Inter").unwrap();
+        assert_eq!(request.style, FontStyle::Oblique(-5.0));
+        assert_eq!(request.stretch, 80.0);
+        assert_eq!(
+            FontRequest::parse("oblique Inter").unwrap().style,
+            FontStyle::Oblique(14.0)
+        );
+        assert!(FontRequest::parse("bold italic").is_err());
+    }
+}
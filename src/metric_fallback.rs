@@ -0,0 +1,288 @@
+//! Metric-compatible local fallbacks for a web font
+//!
+//! While a web font loads (or if it fails), the browser renders with a
+//! local fallback; when the web font arrives, every line whose width or
+//! height differs reflows. A fallback `@font-face` rule with `size-adjust`
+//! and the `*-override` descriptors scales a local font so its average
+//! character width matches the web font's, then pins its ascent, descent
+//! and line gap to the web font's, so the swap moves nothing.
+//!
+//! `metric_overrides` computes those descriptors for one pair of fonts.
+//! `rank_metric_fallbacks` does it for one face of every installed family
+//! and orders them by how much the substitute still differs once adjusted:
+//! - how far the glyphs have to be scaled (`size-adjust` away from 100%)
+//! - the x-height left after scaling, which decides how similar the text
+//!   looks
+//! - pitch, italic and weight mismatches, and basic Latin characters the
+//!   substitute lacks
+//!
+//! Average widths come from OS/2 xAvgCharWidth, so fonts without an OS/2
+//! table (and sources that don't read it) can't be compared.
+
+use std::cmp::Ordering;
+
+use crate::font_info::width_percent;
+use crate::fontface::{css_string, FontFaceRule, MetricOverrides};
+use crate::matching::{has_family, match_font, Face, FontRequest, FontStyle};
+use crate::{FontInfo, NameId};
+
+/// Score added for a monospaced substitute of a proportional font, or
+/// the reverse
+const PITCH_PENALTY: f32 = 25.0;
+
+/// Score added for an upright substitute of an italic font, or the reverse
+const ITALIC_PENALTY: f32 = 10.0;
+
+/// Score added per 100 units of weight difference
+const WEIGHT_PENALTY: f32 = 5.0;
+
+/// A local font that can stand in for the target
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricFallback {
+    pub index: usize, // Into the fonts passed to `rank_metric_fallbacks`
+    pub overrides: MetricOverrides,
+    pub x_height: Option<f32>, // Adjusted x-height, % of the target's
+    pub missing: usize,        // Basic Latin characters of the target it lacks
+    pub score: f32,            // 0 for a perfect substitute, lower is better
+}
+
+impl MetricFallback {
+    /// Compares `fonts[index]` with the target; None if either lacks
+    /// metrics or an average width
+    pub fn rate(fonts: &[FontInfo], target: &FontInfo, index: usize) -> Option<Self> {
+        let candidate = &fonts[index];
+        let overrides = metric_overrides(target, candidate)?;
+        let scale = overrides.size_adjust / 100.0;
+
+        let x_height = match (x_height(target), x_height(candidate)) {
+            (Some(t), Some(c)) if t > 0.0 => Some(c * scale / t * 100.0),
+            _ => None,
+        };
+
+        let basic_latin = ('!'..='~').filter(|&c| target.coverage.contains(c));
+        let missing = basic_latin
+            .clone()
+            .filter(|&c| !candidate.coverage.contains(c))
+            .count();
+        let covered_share = match basic_latin.count() {
+            0 => 1.0,
+            total => 1.0 - missing as f32 / total as f32,
+        };
+
+        let mut score = (scale.ln() * 100.0).abs();
+        score += x_height.map(|x| (x - 100.0).abs()).unwrap_or(0.0);
+        score += (1.0 - covered_share) * 100.0;
+        if target.fixed_pitch != candidate.fixed_pitch {
+            score += PITCH_PENALTY;
+        }
+        if target.italic != candidate.italic {
+            score += ITALIC_PENALTY;
+        }
+        score += (target.weight - candidate.weight).abs() as f32 / 100.0 * WEIGHT_PENALTY;
+
+        Some(MetricFallback {
+            index,
+            overrides,
+            x_height,
+            missing,
+            score,
+        })
+    }
+
+    /// The fallback `@font-face` rule, e.g. family `"Inter Fallback"` with
+    /// `src: local("Arial")` and the overrides, for the target's style
+    pub fn font_face_rule(&self, fonts: &[FontInfo], target: &FontInfo) -> FontFaceRule {
+        let candidate = &fonts[self.index];
+        let full = candidate
+            .names
+            .best(NameId::FULL_NAME)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} {}", candidate.family_name, candidate.style_name));
+        let face = Face::new(0, target);
+        FontFaceRule {
+            family: format!("{} Fallback", target.family_name),
+            sources: vec![format!("local(\"{}\")", css_string(&full))],
+            weight: face.weight,
+            stretch: face.stretch,
+            style: if target.italic { "italic" } else { "normal" }.to_string(),
+            unicode_range: Vec::new(),
+            overrides: Some(self.overrides),
+        }
+    }
+}
+
+/// The `@font-face` descriptors that make `candidate` take up the same
+/// space as `target`
+///
+/// `size-adjust` scales the candidate so its average character width
+/// matches the target's; the ascent, descent and line gap are the
+/// target's (from the same metrics as `Metrics::line_height`) divided by
+/// that scale, since browsers apply the overrides to the adjusted em.
+/// None if either font lacks metrics or an average width.
+pub fn metric_overrides(target: &FontInfo, candidate: &FontInfo) -> Option<MetricOverrides> {
+    let own = MetricOverrides::of(target.metrics.as_ref()?)?;
+    let target_width = average_width(target)?;
+    let candidate_width = average_width(candidate)?;
+
+    let scale = target_width / candidate_width;
+    Some(MetricOverrides {
+        size_adjust: scale * 100.0,
+        ascent: own.ascent / scale,
+        descent: own.descent / scale,
+        line_gap: own.line_gap / scale,
+    })
+}
+
+/// One face of every other family as a fallback for `target`, best first
+///
+/// From each family the face a browser would pick for the target's
+/// weight, stretch and style is compared. Families of the target itself
+/// and faces without an average width are left out.
+pub fn rank_metric_fallbacks(fonts: &[FontInfo], target: &FontInfo) -> Vec<MetricFallback> {
+    let mut families: Vec<&str> = fonts
+        .iter()
+        .filter(|f| f.instance.is_none() && !has_family(f, &target.family_name))
+        .map(|f| f.family_name.as_str())
+        .collect();
+    families.sort_unstable();
+    families.dedup();
+
+    let style = if target.italic {
+        FontStyle::Italic
+    } else {
+        FontStyle::Normal
+    };
+    let mut ranked: Vec<MetricFallback> = families
+        .into_iter()
+        .filter_map(|family| {
+            let request = FontRequest {
+                families: vec![family.to_string()],
+                weight: target.weight as f32,
+                stretch: width_percent(target.width),
+                style,
+            };
+            let found = match_font(fonts, &request)?;
+            MetricFallback::rate(fonts, target, found.index)
+        })
+        .collect();
+    // Requests for e.g. "Noto Sans" and "Noto Sans Display" can pick the same face
+    ranked.sort_by_key(|fallback| fallback.index);
+    ranked.dedup_by_key(|fallback| fallback.index);
+    ranked.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal));
+    ranked
+}
+
+/// xAvgCharWidth as a fraction of the em
+///
+/// None unless it is positive: broken and legacy fonts store 0 or less,
+/// which would make the scale infinite or negative.
+fn average_width(font: &FontInfo) -> Option<f32> {
+    let metrics = font.metrics.as_ref()?;
+    let width = metrics.avg_char_width.filter(|&w| w > 0)?;
+    (metrics.units_per_em > 0).then(|| width as f32 / metrics.units_per_em as f32)
+}
+
+/// sxHeight as a fraction of the em
+fn x_height(font: &FontInfo) -> Option<f32> {
+    let metrics = font.metrics.as_ref()?;
+    let height = metrics.x_height.filter(|&h| h > 0)?;
+    (metrics.units_per_em > 0).then(|| height as f32 / metrics.units_per_em as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coverage, Metrics};
+
+    fn font(family: &str, weight: i32, avg_char_width: i16, x_height: i16) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            style_name: "Regular".to_string(),
+            weight,
+            width: 5,
+            coverage: Coverage::from_ranges([(0x20, 0x7E)]),
+            metrics: Some(Metrics {
+                units_per_em: 1000,
+                ascender: 900,
+                descender: -250,
+                avg_char_width: Some(avg_char_width),
+                x_height: Some(x_height),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn overrides_scale_the_targets_metrics() {
+        let target = font("Inter", 400, 500, 500);
+        let overrides = metric_overrides(&target, &font("Narrow", 400, 400, 400)).unwrap();
+        assert_eq!(
+            overrides,
+            MetricOverrides {
+                size_adjust: 125.0,
+                ascent: 72.0,
+                descent: 20.0,
+                line_gap: 0.0,
+            }
+        );
+        let mut unknown = font("Unknown", 400, 400, 400);
+        unknown.metrics.as_mut().unwrap().avg_char_width = None;
+        assert_eq!(metric_overrides(&target, &unknown), None);
+        assert_eq!(metric_overrides(&unknown, &target), None);
+        for width in [0, -100] {
+            let broken = font("Broken", 400, width, 400);
+            assert_eq!(metric_overrides(&target, &broken), None);
+            assert_eq!(metric_overrides(&broken, &target), None);
+        }
+        let fonts = [target.clone(), font("Broken", 400, 0, 400)];
+        assert!(rank_metric_fallbacks(&fonts, &target).is_empty());
+    }
+
+    #[test]
+    fn fallbacks_are_ranked_by_what_differs_after_adjusting() {
+        let target = font("Inter", 400, 500, 500);
+        let mut partial = font("Partial", 400, 500, 500);
+        partial.coverage = Coverage::from_ranges([(u32::from('A'), u32::from('Z'))]);
+        let mut no_metrics = font("Bare", 400, 500, 500);
+        no_metrics.metrics = None;
+        let fonts = [
+            target.clone(),
+            font("Inter", 700, 520, 500),
+            font("Narrow", 400, 400, 400),
+            font("Twin", 700, 500, 500),
+            font("Twin", 400, 500, 500),
+            partial,
+            no_metrics,
+        ];
+        let ranked = rank_metric_fallbacks(&fonts, &target);
+        let order: Vec<usize> = ranked.iter().map(|f| f.index).collect();
+        assert_eq!(order, [4, 2, 5]);
+        assert_eq!(ranked[0].score, 0.0);
+        // Scaled by 125%, the x-height matches
+        assert!((ranked[1].x_height.unwrap() - 100.0).abs() < 0.01);
+        assert!((ranked[1].score - 1.25f32.ln() * 100.0).abs() < 0.01);
+        assert_eq!(ranked[2].missing, 94 - 26);
+    }
+
+    #[test]
+    fn the_fallback_rule_uses_the_local_font() {
+        let target = font("Inter", 400, 500, 500);
+        let fonts = [target.clone(), font("Narrow", 400, 400, 400)];
+        let fallback = MetricFallback::rate(&fonts, &target, 1).unwrap();
+        assert_eq!(
+            fallback.font_face_rule(&fonts, &target).to_string(),
+            "@font-face {
+  font-family: \"Inter Fallback\";
+  src: local(\"Narrow Regular\");
+  font-weight: 400;
+  font-stretch: 100%;
+  font-style: normal;
+  size-adjust: 125%;
+  ascent-override: 72%;
+  descent-override: 20%;
+  line-gap-override: 0%;
+}"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! `cmap` table - character coverage
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"cmap");
+
+/// The last Unicode code point
+const MAX_CODE_POINT: u32 = 0x10FFFF;
+
+/// Variation selectors, each with the base code point ranges it applies to
+type Sequences = Vec<(u32, Vec<(u32, u32)>)>;
+
+/// The set of Unicode code points a font maps to a glyph
+///
+/// Stored as sorted, non-overlapping inclusive ranges, which keeps even
+/// CJK fonts with tens of thousands of characters down to a few hundred
+/// entries. Variation sequences from a format 14 subtable are kept per
+/// variation selector.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    ranges: Vec<(u32, u32)>, // Inclusive code point ranges
+    sequences: Sequences,    // Variation selector -> base code point ranges
+}
+
+impl Coverage {
+    /// Builds a coverage set from arbitrary (possibly overlapping) ranges
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        Coverage {
+            ranges: normalize(ranges.into_iter().collect()),
+            sequences: Vec::new(),
+        }
+    }
+
+    /// True if nothing is known to be covered (e.g. the source has no cmap data)
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of covered code points
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|&(first, last)| (last - first) as usize + 1)
+            .sum()
+    }
+
+    /// The covered code points as inclusive ranges, in ascending order
+    pub fn ranges(&self) -> &[(u32, u32)] {
+        &self.ranges
+    }
+
+    /// True if the font maps `c` to a glyph
+    pub fn contains(&self, c: char) -> bool {
+        contains(&self.ranges, c as u32)
+    }
+
+    /// True if the font lists any variation sequence with this selector
+    pub fn has_variation_selector(&self, selector: char) -> bool {
+        self.sequences.iter().any(|(vs, _)| *vs == selector as u32)
+    }
+
+    /// True if the font supports the variation sequence `base` + `selector`
+    pub fn supports_sequence(&self, base: char, selector: char) -> bool {
+        self.sequences
+            .iter()
+            .any(|(vs, bases)| *vs == selector as u32 && contains(bases, base as u32))
+    }
+}
+
+fn contains(ranges: &[(u32, u32)], cp: u32) -> bool {
+    // Binary search for the last range starting at or before cp
+    match ranges.partition_point(|&(first, _)| first <= cp) {
+        0 => false,
+        i => cp <= ranges[i - 1].1,
+    }
+}
+
+/// Sorts ranges and merges overlapping or adjacent ones
+fn normalize(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.retain(|&(first, last)| first <= last);
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (first, last) in ranges {
+        match merged.last_mut() {
+            Some(prev) if first <= prev.1.saturating_add(1) => prev.1 = prev.1.max(last),
+            _ => merged.push((first, last)),
+        }
+    }
+    merged
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Parses the Unicode subtables of a cmap into a coverage set
+///
+/// Every Unicode subtable (platform 0, or Windows BMP/full repertoire) in
+/// formats 4 and 12 contributes; format 14 supplies variation sequences.
+/// Format 13 is left out: last resort fonts use it to map every character
+/// to a fallback glyph, which covers nothing. Code points mapped to glyph 0
+/// (.notdef) are not counted as covered, and a subtable that can't be read
+/// is skipped.
+pub(crate) fn parse(data: &[u8]) -> Result<Coverage> {
+    let mut r = Reader::new(data);
+    let _version = r.u16()?;
+    let num_tables = r.u16()?;
+
+    let mut ranges = Vec::new();
+    let mut sequences = Vec::new();
+    for _ in 0..num_tables {
+        let platform_id = r.u16()?;
+        let encoding_id = r.u16()?;
+        let offset = r.u32()? as usize;
+
+        let is_unicode = platform_id == 0 || (platform_id == 3 && matches!(encoding_id, 1 | 10));
+        if !is_unicode {
+            continue;
+        }
+
+        match Reader::at(data, offset).and_then(|mut r| r.u16()) {
+            Ok(4) => ranges.extend(parse_format4(data, offset).unwrap_or_default()),
+            Ok(12) => ranges.extend(parse_format12(data, offset).unwrap_or_default()),
+            Ok(14) => sequences.extend(parse_format14(data, offset).unwrap_or_default()),
+            _ => {} // Formats 0, 2, 6, 8, 10: legacy or not used for Unicode
+        }
+    }
+
+    Ok(Coverage {
+        ranges: normalize(ranges),
+        sequences,
+    })
+}
+
+/// Format 4: segment mapping to delta values (BMP only)
+fn parse_format4(data: &[u8], offset: usize) -> Result<Vec<(u32, u32)>> {
+    let mut ranges = Vec::new();
+    let mut r = Reader::at(data, offset + 6)?; // format, length, language
+    let seg_count = r.u16()? as usize / 2;
+    r.skip(6)?; // searchRange, entrySelector, rangeShift
+
+    let end_codes = offset + 14;
+    let start_codes = end_codes + seg_count * 2 + 2; // + reservedPad
+    let id_deltas = start_codes + seg_count * 2;
+    let id_range_offsets = id_deltas + seg_count * 2;
+
+    for seg in 0..seg_count {
+        let end = Reader::at(data, end_codes + seg * 2)?.u16()?;
+        let start = Reader::at(data, start_codes + seg * 2)?.u16()?;
+        let delta = Reader::at(data, id_deltas + seg * 2)?.u16()?;
+        let range_offset_pos = id_range_offsets + seg * 2;
+        let range_offset = Reader::at(data, range_offset_pos)?.u16()? as usize;
+        if start > end || start == 0xFFFF {
+            continue;
+        }
+
+        if range_offset == 0 {
+            // glyph = (c + delta) mod 65536; only one code point can hit glyph 0
+            let notdef = 0u16.wrapping_sub(delta);
+            push_excluding(&mut ranges, start as u32, end as u32, notdef as u32);
+            continue;
+        }
+
+        // Glyph IDs come from glyphIdArray; check each code point
+        let mut run: Option<(u32, u32)> = None;
+        for c in start..=end {
+            let pos = range_offset_pos + range_offset + (c - start) as usize * 2;
+            let glyph = match Reader::at(data, pos).and_then(|mut r| r.u16()) {
+                Ok(0) | Err(_) => 0,
+                Ok(g) => g.wrapping_add(delta),
+            };
+            if glyph == 0 {
+                ranges.extend(run.take());
+            } else {
+                run = Some(match run {
+                    Some((first, _)) => (first, c as u32),
+                    None => (c as u32, c as u32),
+                });
+            }
+        }
+        ranges.extend(run);
+    }
+    Ok(ranges)
+}
+
+/// Pushes `first..=last` minus a single excluded code point
+fn push_excluding(ranges: &mut Vec<(u32, u32)>, first: u32, last: u32, excluded: u32) {
+    if excluded < first || excluded > last {
+        ranges.push((first, last));
+        return;
+    }
+    if excluded > first {
+        ranges.push((first, excluded - 1));
+    }
+    if excluded < last {
+        ranges.push((excluded + 1, last));
+    }
+}
+
+/// Format 12: segmented coverage
+fn parse_format12(data: &[u8], offset: usize) -> Result<Vec<(u32, u32)>> {
+    let mut ranges = Vec::new();
+    let mut r = Reader::at(data, offset + 12)?; // format, reserved, length, language
+    let num_groups = r.u32()?;
+    for _ in 0..num_groups {
+        let first = r.u32()?;
+        let last = r.u32()?.min(MAX_CODE_POINT);
+        let glyph = r.u32()?;
+        // Groups beyond Unicode (or reversed) cover nothing; this also keeps
+        // `first + 1` below from overflowing
+        if first > last {
+            continue;
+        }
+        // Glyphs increase from `glyph`, so only `first` can be .notdef
+        match glyph {
+            0 => ranges.push((first + 1, last)),
+            _ => ranges.push((first, last)),
+        }
+    }
+    Ok(ranges)
+}
+
+/// Format 14: Unicode variation sequences
+fn parse_format14(data: &[u8], offset: usize) -> Result<Sequences> {
+    let mut sequences = Vec::new();
+    let mut r = Reader::at(data, offset + 6)?; // format, length
+    let num_records = r.u32()?;
+    for _ in 0..num_records {
+        let selector = r.u24()?;
+        let default_offset = r.u32()? as usize;
+        let non_default_offset = r.u32()? as usize;
+
+        let mut bases = Vec::new();
+        if default_offset != 0 {
+            // Default UVS: ranges of bases that use their default glyph
+            let mut r = Reader::at(data, offset + default_offset)?;
+            for _ in 0..r.u32()? {
+                let start = r.u24()?;
+                let additional = r.u8()? as u32;
+                bases.push((start, start + additional));
+            }
+        }
+        if non_default_offset != 0 {
+            // Non-default UVS: individual bases with their own glyph
+            let mut r = Reader::at(data, offset + non_default_offset)?;
+            for _ in 0..r.u32()? {
+                let base = r.u24()?;
+                let _glyph = r.u16()?;
+                bases.push((base, base));
+            }
+        }
+        sequences.push((selector, normalize(bases)));
+    }
+    Ok(sequences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(out: &mut Vec<u8>, values: &[u16]) {
+        for v in values {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+
+    /// A cmap with `(platform, encoding, subtable)` records
+    fn cmap(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        push(&mut out, &[0, subtables.len() as u16]);
+        let mut offset = 4 + subtables.len() * 8;
+        for (platform, encoding, subtable) in subtables {
+            push(&mut out, &[*platform, *encoding]);
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            offset += subtable.len();
+        }
+        for (_, _, subtable) in subtables {
+            out.extend_from_slice(subtable);
+        }
+        out
+    }
+
+    /// Format 4 with `(start, end, delta, glyphIdArray)` segments
+    fn format4(segments: &[(u16, u16, u16, &[u16])]) -> Vec<u8> {
+        let n = segments.len() as u16;
+        let mut out = Vec::new();
+        push(&mut out, &[4, 0, 0, n * 2, 0, 0, 0]);
+        push(&mut out, &segments.iter().map(|s| s.1).collect::<Vec<_>>());
+        push(&mut out, &[0]);
+        push(&mut out, &segments.iter().map(|s| s.0).collect::<Vec<_>>());
+        push(&mut out, &segments.iter().map(|s| s.2).collect::<Vec<_>>());
+        let mut glyphs = 0;
+        for (i, (_, _, _, ids)) in segments.iter().enumerate() {
+            let offset = if ids.is_empty() {
+                0
+            } else {
+                (n - i as u16 + glyphs) * 2
+            };
+            push(&mut out, &[offset]);
+            glyphs += ids.len() as u16;
+        }
+        for (_, _, _, ids) in segments {
+            push(&mut out, ids);
+        }
+        out
+    }
+
+    /// Format 12 or 13 with `(first, last, glyph)` groups
+    fn format12(format: u16, groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        push(&mut out, &[format, 0, 0, 0, 0, 0]);
+        out.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+        for group in groups {
+            for v in [group.0, group.1, group.2] {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn format4_skips_notdef_mappings() {
+        let subtable = format4(&[
+            (0x41, 0x43, 0u16.wrapping_sub(0x42), &[]), // 'B' maps to glyph 0
+            (0x100, 0x103, 0, &[5, 0, 7, 8]),
+            (0xFFFF, 0xFFFF, 1, &[]),
+        ]);
+        let coverage = parse(&cmap(&[(3, 1, subtable)])).unwrap();
+        assert_eq!(
+            coverage.ranges(),
+            [(0x41, 0x41), (0x43, 0x43), (0x100, 0x100), (0x102, 0x103)]
+        );
+        assert!(coverage.contains('A'));
+        assert!(!coverage.contains('B'));
+        assert_eq!(coverage.len(), 5);
+    }
+
+    #[test]
+    fn format12_groups_and_last_resort_subtables() {
+        let f12 = format12(
+            12,
+            &[
+                (0x20, 0x22, 0), // Starts at .notdef
+                (0x1F600, 0x1F601, 10),
+                (0x10FFFF, 0xFFFF_FFFF, 0),
+                (0xFFFF_FFFF, 0xFFFF_FFFF, 0),
+            ],
+        );
+        // Format 13 maps everything to a last resort glyph: no coverage
+        let f13 = format12(13, &[(0, 0x10FFFF, 3)]);
+        let coverage = parse(&cmap(&[(3, 10, f12), (0, 6, f13)])).unwrap();
+        assert_eq!(coverage.ranges(), [(0x21, 0x22), (0x1F600, 0x1F601)]);
+    }
+
+    #[test]
+    fn unreadable_subtables_are_skipped() {
+        let f12 = format12(12, &[(0x41, 0x5A, 1)]);
+        let mut truncated = format12(12, &[(0x61, 0x7A, 1)]);
+        truncated.truncate(20);
+        let mut data = cmap(&[(3, 10, f12), (0, 4, truncated), (0, 3, Vec::new())]);
+        // Point the last record past the end of the table
+        let record = 4 + 2 * 8;
+        data[record + 4..record + 8].copy_from_slice(&u32::MAX.to_be_bytes());
+        let coverage = parse(&data).unwrap();
+        assert_eq!(coverage.ranges(), [(0x41, 0x5A)]);
+    }
+
+    #[test]
+    fn non_unicode_subtables_are_ignored() {
+        let symbol = format12(12, &[(0xF020, 0xF0FF, 1)]);
+        let coverage = parse(&cmap(&[(3, 0, symbol)])).unwrap();
+        assert!(coverage.is_empty());
+    }
+
+    #[test]
+    fn format14_variation_sequences() {
+        let mut f14 = Vec::new();
+        push(&mut f14, &[14, 0, 0]);
+        f14.extend_from_slice(&1u32.to_be_bytes());
+        f14.extend_from_slice(&[0x00, 0xFE, 0x0F]); // VS16
+        f14.extend_from_slice(&21u32.to_be_bytes()); // Default UVS
+        f14.extend_from_slice(&29u32.to_be_bytes()); // Non-default UVS
+        f14.extend_from_slice(&1u32.to_be_bytes());
+        f14.extend_from_slice(&[0x00, 0x27, 0x64, 1]); // U+2764-2765
+        f14.extend_from_slice(&1u32.to_be_bytes());
+        f14.extend_from_slice(&[0x00, 0x26, 0x3A, 0, 9]); // U+263A, glyph 9
+
+        let coverage = parse(&cmap(&[(0, 5, f14)])).unwrap();
+        assert!(coverage.has_variation_selector('\u{FE0F}'));
+        assert!(!coverage.has_variation_selector('\u{FE0E}'));
+        assert!(coverage.supports_sequence('\u{2764}', '\u{FE0F}'));
+        assert!(coverage.supports_sequence('\u{2765}', '\u{FE0F}'));
+        assert!(coverage.supports_sequence('\u{263A}', '\u{FE0F}'));
+        assert!(!coverage.supports_sequence('\u{2766}', '\u{FE0F}'));
+    }
+
+    #[test]
+    fn ranges_are_merged() {
+        let coverage = Coverage::from_ranges([(10, 20), (5, 9), (15, 30), (40, 35), (50, 50)]);
+        assert_eq!(coverage.ranges(), [(5, 30), (50, 50)]);
+    }
+}
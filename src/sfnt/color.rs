@@ -0,0 +1,219 @@
+//! Color font detection - COLR/CPAL, SVG, sbix and CBDT/CBLC
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const COLR: Tag = Tag::new(b"COLR");
+pub(crate) const CPAL: Tag = Tag::new(b"CPAL");
+pub(crate) const SVG: Tag = Tag::new(b"SVG ");
+pub(crate) const SBIX: Tag = Tag::new(b"sbix");
+pub(crate) const CBDT: Tag = Tag::new(b"CBDT");
+pub(crate) const CBLC: Tag = Tag::new(b"CBLC");
+
+/// Bytes of the COLR and CPAL headers needed by `ColorFormats::from_tables`
+#[cfg(windows)]
+pub(crate) const COLR_HEADER_LEN: usize = 18;
+#[cfg(windows)]
+pub(crate) const CPAL_HEADER_LEN: usize = 6;
+
+/// A color glyph technology
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorFormat {
+    ColrV0, // COLR version 0 - layered solid-color glyphs
+    ColrV1, // COLR version 1 - paint graphs with gradients, transforms, compositing
+    Svg,    // SVG documents per glyph
+    Sbix,   // Apple bitmap glyphs (PNG/JPEG/TIFF)
+    Cbdt,   // Google color bitmaps (CBDT/CBLC)
+}
+
+impl ColorFormat {
+    /// Every color technology
+    pub const ALL: &'static [ColorFormat] = &[
+        ColorFormat::ColrV0,
+        ColorFormat::ColrV1,
+        ColorFormat::Svg,
+        ColorFormat::Sbix,
+        ColorFormat::Cbdt,
+    ];
+
+    /// Short lowercase name, as used in filters
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorFormat::ColrV0 => "colrv0",
+            ColorFormat::ColrV1 => "colrv1",
+            ColorFormat::Svg => "svg",
+            ColorFormat::Sbix => "sbix",
+            ColorFormat::Cbdt => "cbdt",
+        }
+    }
+
+    /// Looks up a technology by its short name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<ColorFormat> {
+        ColorFormat::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for ColorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorFormat::ColrV0 => "COLRv0",
+            ColorFormat::ColrV1 => "COLRv1",
+            ColorFormat::Svg => "SVG",
+            ColorFormat::Sbix => "sbix",
+            ColorFormat::Cbdt => "CBDT",
+        })
+    }
+}
+
+/// The color technologies a font carries, with its COLR/CPAL details
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColorFormats {
+    pub formats: Vec<ColorFormat>, // In ColorFormat::ALL order; empty for monochrome fonts
+    pub colr_version: Option<u16>, // COLR table version, if present
+    pub palette_count: u16,        // CPAL numPalettes (0 without CPAL)
+    pub palette_entry_count: u16,  // CPAL numPaletteEntries - colors per palette
+}
+
+impl ColorFormats {
+    /// True if the font has any color glyphs
+    pub fn is_color(&self) -> bool {
+        !self.formats.is_empty()
+    }
+
+    /// True if the font carries the given technology
+    pub fn contains(&self, format: ColorFormat) -> bool {
+        self.formats.contains(&format)
+    }
+
+    /// Detects the color technologies from the relevant tables
+    ///
+    /// Only the headers of COLR and CPAL are read (`COLR_HEADER_LEN` and
+    /// `CPAL_HEADER_LEN` bytes); the other tables just need to exist.
+    /// CBDT counts only together with its CBLC index.
+    pub(crate) fn from_tables(
+        colr: Option<&[u8]>,
+        cpal: Option<&[u8]>,
+        has: impl Fn(Tag) -> bool,
+    ) -> Result<Self> {
+        let mut color = ColorFormats::default();
+
+        if let Some(colr) = colr {
+            let mut r = Reader::new(colr);
+            let version = r.u16()?;
+            let num_base_glyph_records = r.u16()?;
+            color.colr_version = Some(version);
+            if version == 0 || num_base_glyph_records > 0 {
+                color.formats.push(ColorFormat::ColrV0);
+            }
+            if version >= 1 {
+                r.skip(10)?; // baseGlyphRecordsOffset, layerRecordsOffset, numLayerRecords
+                if r.u32()? != 0 {
+                    color.formats.push(ColorFormat::ColrV1); // baseGlyphListOffset
+                }
+            }
+        }
+        if has(SVG) {
+            color.formats.push(ColorFormat::Svg);
+        }
+        if has(SBIX) {
+            color.formats.push(ColorFormat::Sbix);
+        }
+        if has(CBDT) && has(CBLC) {
+            color.formats.push(ColorFormat::Cbdt);
+        }
+
+        if let Some(cpal) = cpal {
+            let mut r = Reader::new(cpal);
+            let _version = r.u16()?;
+            color.palette_entry_count = r.u16()?;
+            color.palette_count = r.u16()?;
+        }
+        Ok(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A COLR header with `num_base_glyph_records` and, for version 1,
+    /// baseGlyphListOffset
+    fn colr(version: u16, num_base_glyph_records: u16, base_glyph_list: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        for v in [version, num_base_glyph_records, 0, 14, 0, 0, 0] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        if version >= 1 {
+            out.extend_from_slice(&base_glyph_list.to_be_bytes());
+        }
+        out
+    }
+
+    fn cpal(entries: u16, palettes: u16) -> Vec<u8> {
+        [0, entries, palettes, entries * palettes]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn colr_versions() {
+        let none = |_| false;
+        let v0 = ColorFormats::from_tables(Some(&colr(0, 3, 0)), None, none).unwrap();
+        assert_eq!(v0.formats, [ColorFormat::ColrV0]);
+        assert_eq!(v0.colr_version, Some(0));
+
+        // Version 1 fonts often keep v0 records as a fallback
+        let both = ColorFormats::from_tables(Some(&colr(1, 3, 34)), None, none).unwrap();
+        assert_eq!(both.formats, [ColorFormat::ColrV0, ColorFormat::ColrV1]);
+        let v1 = ColorFormats::from_tables(Some(&colr(1, 0, 34)), None, none).unwrap();
+        assert_eq!(v1.formats, [ColorFormat::ColrV1]);
+        assert_eq!(v1.colr_version, Some(1));
+        // A version 1 header without either kind of glyph
+        let empty = ColorFormats::from_tables(Some(&colr(1, 0, 0)), None, none).unwrap();
+        assert!(!empty.is_color());
+
+        let truncated = colr(1, 0, 34);
+        assert!(ColorFormats::from_tables(Some(&truncated[..16]), None, none).is_err());
+    }
+
+    #[test]
+    fn palettes() {
+        let color =
+            ColorFormats::from_tables(Some(&colr(0, 1, 0)), Some(&cpal(12, 2)), |_| false).unwrap();
+        assert_eq!(color.palette_count, 2);
+        assert_eq!(color.palette_entry_count, 12);
+        assert!(ColorFormats::from_tables(None, Some(&[0, 0]), |_| false).is_err());
+    }
+
+    #[test]
+    fn other_tables_only_need_to_exist() {
+        let tables = [SVG, SBIX, CBDT];
+        let color = ColorFormats::from_tables(None, None, |tag| tables.contains(&tag)).unwrap();
+        // CBDT without its CBLC index doesn't count
+        assert_eq!(color.formats, [ColorFormat::Svg, ColorFormat::Sbix]);
+        assert!(color.contains(ColorFormat::Sbix));
+
+        let color =
+            ColorFormats::from_tables(None, None, |tag| tag == CBDT || tag == CBLC).unwrap();
+        assert_eq!(color.formats, [ColorFormat::Cbdt]);
+        assert_eq!(color.colr_version, None);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for &format in ColorFormat::ALL {
+            assert_eq!(ColorFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(ColorFormat::from_name("COLRv1"), Some(ColorFormat::ColrV1));
+        assert_eq!(ColorFormat::from_name("png"), None);
+    }
+}
@@ -0,0 +1,261 @@
+//! `fvar` table - variation axes and named instances
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::name::{NameId, NameTable};
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"fvar");
+
+/// axis flags: the axis should not be exposed in user interfaces
+const HIDDEN_AXIS: u16 = 0x0001;
+
+/// A variation axis, e.g. `wght 100-900 (default 400)`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariationAxis {
+    pub tag: Tag,     // e.g. "wght", "wdth", "opsz"
+    pub name: String, // Display name from the name table, e.g. "Weight"
+    pub min_value: f32,
+    pub default_value: f32,
+    pub max_value: f32,
+    pub hidden: bool, // HIDDEN_AXIS flag - not meant for end users
+}
+
+impl VariationAxis {
+    /// True if the axis actually varies (min and max differ)
+    pub fn is_variable(&self) -> bool {
+        self.min_value != self.max_value
+    }
+}
+
+impl fmt::Display for VariationAxis {
+    /// Formats as `tag min-max`, e.g. `wght 100-900`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}-{}",
+            self.tag,
+            format_value(self.min_value),
+            format_value(self.max_value)
+        )
+    }
+}
+
+/// A named instance - a predefined position in the design space
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamedInstance {
+    pub name: String,                    // Subfamily name, e.g. "SemiBold Condensed"
+    pub postscript_name: Option<String>, // e.g. "RobotoFlex-SemiBoldCondensed"
+    pub coordinates: Vec<(Tag, f32)>,    // One value per axis, in fvar axis order
+}
+
+impl NamedInstance {
+    /// The coordinate on a given axis, if the font has that axis
+    pub fn coordinate(&self, tag: Tag) -> Option<f32> {
+        self.coordinates
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|&(_, v)| v)
+    }
+}
+
+impl fmt::Display for NamedInstance {
+    /// Formats the coordinates as `wght=700 wdth=75`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (tag, value)) in self.coordinates.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", tag, format_value(*value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats an axis value without a trailing `.0` (e.g. `100`, `12.5`)
+pub(crate) fn format_value(value: f32) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        // Fixed 16.16 values have ~5 significant decimals; trim the noise
+        let s = format!("{:.3}", value);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Parses the axis and instance records of an fvar table
+///
+/// Axis and instance names are resolved through the face's name table.
+pub(crate) fn parse(
+    data: &[u8],
+    names: &NameTable,
+) -> Result<(Vec<VariationAxis>, Vec<NamedInstance>)> {
+    let mut r = Reader::new(data);
+    let _major_version = r.u16()?;
+    let _minor_version = r.u16()?;
+    let axes_array_offset = r.u16()? as usize;
+    r.skip(2)?; // reserved
+    let axis_count = r.u16()? as usize;
+    let axis_size = r.u16()? as usize;
+    let instance_count = r.u16()? as usize;
+    let instance_size = r.u16()? as usize;
+
+    let name = |id: u16| names.best(NameId(id)).map(str::to_string);
+
+    let mut axes = Vec::with_capacity(axis_count);
+    for i in 0..axis_count {
+        let mut r = Reader::at(data, axes_array_offset + i * axis_size)?;
+        let tag = r.tag()?;
+        let min_value = r.fixed()?;
+        let default_value = r.fixed()?;
+        let max_value = r.fixed()?;
+        let flags = r.u16()?;
+        let axis_name_id = r.u16()?;
+        axes.push(VariationAxis {
+            tag,
+            name: name(axis_name_id).unwrap_or_else(|| tag.to_string()),
+            min_value,
+            default_value,
+            max_value,
+            hidden: flags & HIDDEN_AXIS != 0,
+        });
+    }
+
+    // Instances follow the axes; the optional postScriptNameID is present
+    // only when the record is large enough to hold it
+    let instances_offset = axes_array_offset + axis_count * axis_size;
+    let has_postscript_name = instance_size >= axis_count * 4 + 6;
+
+    let mut instances = Vec::with_capacity(instance_count);
+    for i in 0..instance_count {
+        let mut r = Reader::at(data, instances_offset + i * instance_size)?;
+        let subfamily_name_id = r.u16()?;
+        let _flags = r.u16()?;
+        let mut coordinates = Vec::with_capacity(axis_count);
+        for axis in &axes {
+            coordinates.push((axis.tag, r.fixed()?));
+        }
+        let postscript_name = match has_postscript_name {
+            true => Some(r.u16()?).filter(|&id| id != 0xFFFF).and_then(name),
+            false => None,
+        };
+        instances.push(NamedInstance {
+            name: name(subfamily_name_id).unwrap_or_default(),
+            postscript_name,
+            coordinates,
+        });
+    }
+
+    Ok((axes, instances))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sfnt::name;
+
+    fn fixed(value: f32) -> [u8; 4] {
+        ((value * 65536.0) as i32).to_be_bytes()
+    }
+
+    /// A name table with Windows English records for `(name ID, text)`
+    fn names(records: &[(u16, &str)]) -> NameTable {
+        let mut out = Vec::new();
+        let mut storage = Vec::new();
+        for v in [0, records.len() as u16, 6 + records.len() as u16 * 12] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        for (id, text) in records {
+            let text: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            for v in [3, 1, 0x409, *id, text.len() as u16, storage.len() as u16] {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            storage.extend_from_slice(&text);
+        }
+        out.extend_from_slice(&storage);
+        name::parse(&out).unwrap()
+    }
+
+    /// An fvar with wght 100-900 and a hidden opsz 8-8, and two instances;
+    /// `instance_size` decides whether postScriptNameID is present
+    fn fvar(instance_size: u16) -> Vec<u8> {
+        let mut out = Vec::new();
+        for v in [1, 0, 16, 2, 2, 20, 2, instance_size] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        for (tag, min, default, max, flags, name_id) in [
+            (b"wght", 100.0, 400.0, 900.0, 0u16, 256u16),
+            (b"opsz", 8.0, 8.0, 8.0, HIDDEN_AXIS, 300),
+        ] {
+            out.extend_from_slice(tag);
+            for v in [min, default, max] {
+                out.extend_from_slice(&fixed(v));
+            }
+            out.extend_from_slice(&flags.to_be_bytes());
+            out.extend_from_slice(&name_id.to_be_bytes());
+        }
+        for (name_id, weight, postscript_id) in [(257u16, 700.0, 258u16), (259, 350.5, 0xFFFF)] {
+            out.extend_from_slice(&name_id.to_be_bytes());
+            out.extend_from_slice(&[0, 0]);
+            out.extend_from_slice(&fixed(weight));
+            out.extend_from_slice(&fixed(8.0));
+            if instance_size == 14 {
+                out.extend_from_slice(&postscript_id.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn axes_and_instances_resolve_their_names() {
+        let names = names(&[
+            (256, "Weight"),
+            (257, "Bold"),
+            (258, "Test-Bold"),
+            (259, "Book"),
+        ]);
+        let (axes, instances) = parse(&fvar(14), &names).unwrap();
+
+        assert_eq!(axes.len(), 2);
+        assert_eq!(axes[0].name, "Weight");
+        assert_eq!(axes[0].to_string(), "wght 100-900");
+        assert!(axes[0].is_variable() && !axes[0].hidden);
+        // Unnamed axes fall back to the tag
+        assert_eq!(axes[1].name, "opsz");
+        assert!(!axes[1].is_variable() && axes[1].hidden);
+
+        assert_eq!(instances[0].name, "Bold");
+        assert_eq!(instances[0].postscript_name.as_deref(), Some("Test-Bold"));
+        assert_eq!(instances[0].coordinate(Tag::new(b"wght")), Some(700.0));
+        assert_eq!(instances[0].coordinate(Tag::new(b"wdth")), None);
+        assert_eq!(instances[1].postscript_name, None);
+        assert_eq!(instances[1].to_string(), "wght=350.5 opsz=8");
+    }
+
+    #[test]
+    fn instances_without_postscript_names() {
+        let (_, instances) = parse(&fvar(12), &names(&[(259, "Book")])).unwrap();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0].name, "");
+        assert_eq!(instances[1].name, "Book");
+        assert!(instances.iter().all(|i| i.postscript_name.is_none()));
+    }
+
+    #[test]
+    fn truncated_records_are_errors() {
+        let data = fvar(14);
+        assert!(parse(&data[..data.len() - 4], &NameTable::default()).is_err());
+    }
+
+    #[test]
+    fn values_are_formatted_without_noise() {
+        assert_eq!(format_value(400.0), "400");
+        assert_eq!(format_value(-12.0), "-12");
+        assert_eq!(format_value(12.5), "12.5");
+        assert_eq!(format_value(0.333_33), "0.333");
+    }
+}
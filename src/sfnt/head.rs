@@ -0,0 +1,44 @@
+//! `head` table - font header
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"head");
+
+// macStyle bits
+pub(crate) const MAC_STYLE_BOLD: u16 = 1 << 0;
+pub(crate) const MAC_STYLE_ITALIC: u16 = 1 << 1;
+
+/// The subset of the head table used by `FontInfo`
+pub(crate) struct Head {
+    pub units_per_em: u16,
+    pub bbox: [i16; 4], // xMin, yMin, xMax, yMax over all glyphs
+    pub mac_style: u16,
+}
+
+impl Head {
+    pub fn is_bold(&self) -> bool {
+        self.mac_style & MAC_STYLE_BOLD != 0
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.mac_style & MAC_STYLE_ITALIC != 0
+    }
+}
+
+/// Parses the head table
+pub(crate) fn parse(data: &[u8]) -> Result<Head> {
+    // version, fontRevision, checksumAdjustment, magicNumber, flags
+    let mut r = Reader::at(data, 18)?;
+    let units_per_em = r.u16()?;
+    r.skip(16)?; // created, modified
+    let bbox = [r.i16()?, r.i16()?, r.i16()?, r.i16()?];
+    let mac_style = r.u16()?;
+
+    Ok(Head {
+        units_per_em,
+        bbox,
+        mac_style,
+    })
+}
@@ -0,0 +1,32 @@
+//! `hhea` table - horizontal header
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"hhea");
+
+/// The subset of the hhea table used by `FontInfo`
+pub(crate) struct Hhea {
+    pub ascender: i16,
+    pub descender: i16, // Negative below the baseline
+    pub line_gap: i16,
+    pub num_h_metrics: u16, // numberOfHMetrics - advance widths stored in hmtx
+}
+
+/// Parses the hhea table header
+pub(crate) fn parse(data: &[u8]) -> Result<Hhea> {
+    let mut r = Reader::at(data, 4)?; // version
+    let ascender = r.i16()?;
+    let descender = r.i16()?;
+    let line_gap = r.i16()?;
+    r.skip(24)?; // advanceWidthMax ... metricDataFormat
+    let num_h_metrics = r.u16()?;
+
+    Ok(Hhea {
+        ascender,
+        descender,
+        line_gap,
+        num_h_metrics,
+    })
+}
@@ -0,0 +1,341 @@
+//! `GSUB` and `GPOS` tables - OpenType layout scripts, languages and features
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const GSUB: Tag = Tag::new(b"GSUB");
+pub(crate) const GPOS: Tag = Tag::new(b"GPOS");
+
+/// The script used for text whose script the font doesn't list
+const DFLT: Tag = Tag::new(b"DFLT");
+
+/// LangSys requiredFeatureIndex when no feature is required
+const NO_REQUIRED_FEATURE: u16 = 0xFFFF;
+
+/// The features a font enables for one script and language
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LanguageSystem {
+    pub script: Tag,             // e.g. "latn", "cyrl", "DFLT"
+    pub language: Option<Tag>,   // e.g. "TRK ", None for the script's default
+    pub features: BTreeSet<Tag>, // GSUB and GPOS features combined, e.g. "kern", "liga"
+}
+
+/// The script/language systems of a font's GSUB and GPOS tables
+///
+/// A language system that appears in both tables is listed once with the
+/// features of both, sorted by script and then language (default first).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub systems: Vec<LanguageSystem>,
+}
+
+impl Layout {
+    /// True if the font has no GSUB or GPOS language systems
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// The script tags, e.g. `DFLT`, `cyrl`, `latn`
+    pub fn scripts(&self) -> BTreeSet<Tag> {
+        self.systems.iter().map(|s| s.script).collect()
+    }
+
+    /// Every feature tag, under any script or language
+    pub fn features(&self) -> BTreeSet<Tag> {
+        self.systems
+            .iter()
+            .flat_map(|s| s.features.iter().copied())
+            .collect()
+    }
+
+    /// True if the font has the script
+    pub fn has_script(&self, script: Tag) -> bool {
+        self.systems.iter().any(|s| s.script == script)
+    }
+
+    /// True if any language system (of `script`, if given) enables the feature
+    ///
+    /// Text of a script the font doesn't list is shaped with the `DFLT`
+    /// script, so its features count then.
+    pub fn has_feature(&self, feature: Tag, script: Option<Tag>) -> bool {
+        let script = script.map(|s| if self.has_script(s) { s } else { DFLT });
+        self.systems
+            .iter()
+            .filter(|s| script.map(|script| s.script == script).unwrap_or(true))
+            .any(|s| s.features.contains(&feature))
+    }
+
+    /// Adds one table's language systems, merging those already present
+    fn merge(&mut self, systems: Vec<LanguageSystem>) {
+        for system in systems {
+            match self
+                .systems
+                .iter_mut()
+                .find(|s| s.script == system.script && s.language == system.language)
+            {
+                Some(existing) => existing.features.extend(system.features),
+                None => self.systems.push(system),
+            }
+        }
+        self.systems.sort_by_key(|s| (s.script, s.language));
+    }
+
+    /// Builds the layout inventory from the GSUB and GPOS tables, if present
+    ///
+    /// Each table is read on its own: a malformed one adds nothing, and the
+    /// other one still counts.
+    pub(crate) fn from_tables(gsub: Option<&[u8]>, gpos: Option<&[u8]>) -> Self {
+        let mut layout = Layout::default();
+        for data in [gsub, gpos].into_iter().flatten() {
+            if let Ok(systems) = parse(data) {
+                layout.merge(systems);
+            }
+        }
+        layout
+    }
+}
+
+/// Parses the ScriptList and FeatureList of a GSUB or GPOS table
+///
+/// Both tables share the same header layout up to the lookup list, which
+/// is not needed: only which features each language system refers to.
+fn parse(data: &[u8]) -> Result<Vec<LanguageSystem>> {
+    let mut r = Reader::at(data, 4)?; // majorVersion, minorVersion
+    let script_list = r.u16()? as usize;
+    let feature_list = r.u16()? as usize;
+
+    // FeatureList: the tag of each feature index
+    let mut r = Reader::at(data, feature_list)?;
+    let feature_count = r.u16()?;
+    let mut feature_tags = Vec::with_capacity(feature_count as usize);
+    for _ in 0..feature_count {
+        feature_tags.push(r.tag()?);
+        r.skip(2)?; // featureOffset
+    }
+
+    let mut systems = Vec::new();
+    let mut r = Reader::at(data, script_list)?;
+    let script_count = r.u16()?;
+    for _ in 0..script_count {
+        let script = r.tag()?;
+        let script_offset = script_list + r.u16()? as usize;
+
+        let mut s = Reader::at(data, script_offset)?;
+        let default_lang_sys = s.u16()?;
+        if default_lang_sys != 0 {
+            let features = parse_lang_sys(
+                data,
+                script_offset + default_lang_sys as usize,
+                &feature_tags,
+            )?;
+            systems.push(LanguageSystem {
+                script,
+                language: None,
+                features,
+            });
+        }
+        let lang_sys_count = s.u16()?;
+        for _ in 0..lang_sys_count {
+            let language = s.tag()?;
+            let offset = script_offset + s.u16()? as usize;
+            let features = parse_lang_sys(data, offset, &feature_tags)?;
+            systems.push(LanguageSystem {
+                script,
+                language: Some(language),
+                features,
+            });
+        }
+    }
+    Ok(systems)
+}
+
+/// Reads a LangSys table's feature indices and resolves them to tags
+///
+/// Indices outside the FeatureList are ignored rather than failing the font.
+fn parse_lang_sys(data: &[u8], offset: usize, feature_tags: &[Tag]) -> Result<BTreeSet<Tag>> {
+    let mut r = Reader::at(data, offset)?;
+    r.skip(2)?; // lookupOrderOffset (reserved)
+    let required = r.u16()?;
+    let count = r.u16()?;
+
+    let mut indices = Vec::with_capacity(count as usize + 1);
+    if required != NO_REQUIRED_FEATURE {
+        indices.push(required);
+    }
+    for _ in 0..count {
+        indices.push(r.u16()?);
+    }
+    Ok(indices
+        .into_iter()
+        .filter_map(|i| feature_tags.get(i as usize).copied())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A LangSys: required feature index and feature indices
+    type LangSys<'a> = (u16, &'a [u16]);
+
+    /// A script: tag, default LangSys and `(language, LangSys)` records
+    type Script<'a> = (
+        &'a [u8; 4],
+        Option<LangSys<'a>>,
+        &'a [(&'a [u8; 4], LangSys<'a>)],
+    );
+
+    fn push(out: &mut Vec<u8>, values: &[u16]) {
+        for v in values {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+
+    fn lang_sys(out: &mut Vec<u8>, (required, indices): LangSys) {
+        push(out, &[0, required, indices.len() as u16]);
+        push(out, indices);
+    }
+
+    /// A GSUB/GPOS header, ScriptList and FeatureList (without lookups)
+    fn layout_table(scripts: &[Script], features: &[&[u8; 4]]) -> Vec<u8> {
+        let mut script_list = Vec::new();
+        push(&mut script_list, &[scripts.len() as u16]);
+        let mut tables = Vec::new();
+        for (tag, default, languages) in scripts {
+            let offset = 2 + scripts.len() * 6 + tables.len();
+            script_list.extend_from_slice(*tag);
+            push(&mut script_list, &[offset as u16]);
+
+            let mut script = Vec::new();
+            let mut lang_systems = Vec::new();
+            let records = 4 + languages.len() * 6;
+            match default {
+                Some(default) => {
+                    push(&mut script, &[records as u16]);
+                    lang_sys(&mut lang_systems, *default);
+                }
+                None => push(&mut script, &[0]),
+            }
+            push(&mut script, &[languages.len() as u16]);
+            for (language, system) in *languages {
+                script.extend_from_slice(*language);
+                push(&mut script, &[(records + lang_systems.len()) as u16]);
+                lang_sys(&mut lang_systems, *system);
+            }
+            tables.extend_from_slice(&script);
+            tables.extend_from_slice(&lang_systems);
+        }
+        script_list.extend_from_slice(&tables);
+
+        let mut out = Vec::new();
+        push(&mut out, &[1, 0, 10, 10 + script_list.len() as u16, 0]);
+        out.extend_from_slice(&script_list);
+        push(&mut out, &[features.len() as u16]);
+        for tag in features {
+            out.extend_from_slice(*tag);
+            push(&mut out, &[0]);
+        }
+        out
+    }
+
+    fn tags(tags: &[&[u8; 4]]) -> BTreeSet<Tag> {
+        tags.iter().map(|t| Tag::new(t)).collect()
+    }
+
+    #[test]
+    fn language_systems_resolve_feature_tags() {
+        let gsub = layout_table(
+            &[
+                (
+                    b"latn",
+                    Some((NO_REQUIRED_FEATURE, &[0, 1])),
+                    &[(b"TRK ", (2, &[0, 9]))],
+                ),
+                (b"DFLT", Some((NO_REQUIRED_FEATURE, &[0])), &[]),
+                (b"cyrl", None, &[(b"SRB ", (NO_REQUIRED_FEATURE, &[1]))]),
+            ],
+            &[b"liga", b"smcp", b"locl"],
+        );
+        let layout = Layout::from_tables(Some(&gsub), None);
+        let systems: Vec<(Tag, Option<Tag>, BTreeSet<Tag>)> = layout
+            .systems
+            .iter()
+            .map(|s| (s.script, s.language, s.features.clone()))
+            .collect();
+        assert_eq!(
+            systems,
+            [
+                (Tag::new(b"DFLT"), None, tags(&[b"liga"])),
+                (Tag::new(b"cyrl"), Some(Tag::new(b"SRB ")), tags(&[b"smcp"])),
+                (Tag::new(b"latn"), None, tags(&[b"liga", b"smcp"])),
+                // The required feature counts; index 9 is out of range
+                (
+                    Tag::new(b"latn"),
+                    Some(Tag::new(b"TRK ")),
+                    tags(&[b"liga", b"locl"])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn gsub_and_gpos_systems_are_merged() {
+        let gsub = layout_table(
+            &[(b"latn", Some((NO_REQUIRED_FEATURE, &[0])), &[])],
+            &[b"liga"],
+        );
+        let gpos = layout_table(
+            &[
+                (b"latn", Some((NO_REQUIRED_FEATURE, &[0, 1])), &[]),
+                (b"grek", Some((NO_REQUIRED_FEATURE, &[0])), &[]),
+            ],
+            &[b"kern", b"mark"],
+        );
+        let layout = Layout::from_tables(Some(&gsub), Some(&gpos));
+        assert_eq!(layout.systems.len(), 2);
+        assert_eq!(layout.scripts(), tags(&[b"grek", b"latn"]));
+        assert_eq!(layout.features(), tags(&[b"kern", b"liga", b"mark"]));
+        assert!(layout.has_feature(Tag::new(b"liga"), Some(Tag::new(b"latn"))));
+        assert!(!layout.has_feature(Tag::new(b"liga"), Some(Tag::new(b"grek"))));
+        assert!(layout.has_feature(Tag::new(b"kern"), None));
+        assert!(layout.has_script(Tag::new(b"grek")));
+    }
+
+    #[test]
+    fn unlisted_scripts_use_dflt() {
+        let gsub = layout_table(
+            &[
+                (b"DFLT", Some((NO_REQUIRED_FEATURE, &[0])), &[]),
+                (b"latn", Some((NO_REQUIRED_FEATURE, &[1])), &[]),
+            ],
+            &[b"ccmp", b"liga"],
+        );
+        let layout = Layout::from_tables(Some(&gsub), None);
+        let ccmp = Tag::new(b"ccmp");
+        assert!(layout.has_feature(ccmp, Some(Tag::new(b"cyrl"))));
+        // A listed script doesn't fall back
+        assert!(!layout.has_feature(ccmp, Some(Tag::new(b"latn"))));
+    }
+
+    #[test]
+    fn missing_and_truncated_tables() {
+        assert!(Layout::from_tables(None, None).is_empty());
+        let gsub = layout_table(&[(b"latn", Some((0, &[])), &[])], &[b"ccmp"]);
+        let gpos = layout_table(
+            &[(b"grek", Some((NO_REQUIRED_FEATURE, &[0])), &[])],
+            &[b"kern"],
+        );
+        let truncated = &gsub[..gsub.len() - 4];
+        assert!(parse(truncated).is_err());
+        // A broken GSUB leaves GPOS, and the other way round
+        let layout = Layout::from_tables(Some(truncated), Some(&gpos));
+        assert_eq!(layout.scripts(), tags(&[b"grek"]));
+        let layout = Layout::from_tables(Some(&gpos), Some(&gpos[..6]));
+        assert_eq!(layout.features(), tags(&[b"kern"]));
+    }
+}
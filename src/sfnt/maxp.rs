@@ -0,0 +1,13 @@
+//! `maxp` table - maximum profile
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"maxp");
+
+/// Reads numGlyphs, the only field shared by version 0.5 (CFF) and 1.0 (TrueType)
+pub(crate) fn parse_num_glyphs(data: &[u8]) -> Result<u16> {
+    let mut r = Reader::at(data, 4)?; // version
+    r.u16()
+}
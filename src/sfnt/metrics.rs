@@ -0,0 +1,202 @@
+//! Vertical and typographic metrics gathered from head, hhea, OS/2 and post
+
+use serde::{Deserialize, Serialize};
+
+use super::head::Head;
+use super::hhea::Hhea;
+use super::os2::Os2;
+use super::post::Post;
+
+/// A font's vertical metrics, in font design units
+///
+/// There are three competing sets of ascender/descender values: hhea (used
+/// by macOS and most browsers), OS/2 typo (used when USE_TYPO_METRICS is
+/// set) and OS/2 win (Windows GDI, which clips glyphs outside them).
+/// Fields a source or table version doesn't provide are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub units_per_em: u16,           // head unitsPerEm, 16-16384
+    pub ascender: i16,               // hhea ascender
+    pub descender: i16,              // hhea descender, negative below the baseline
+    pub line_gap: i16,               // hhea lineGap
+    pub typo_ascender: Option<i16>,  // OS/2 sTypoAscender
+    pub typo_descender: Option<i16>, // OS/2 sTypoDescender, negative below the baseline
+    pub typo_line_gap: Option<i16>,  // OS/2 sTypoLineGap
+    pub win_ascent: Option<u16>,     // OS/2 usWinAscent
+    pub win_descent: Option<u16>,    // OS/2 usWinDescent, positive below the baseline
+    pub use_typo_metrics: bool,      // OS/2 fsSelection USE_TYPO_METRICS
+    pub x_height: Option<i16>,       // OS/2 sxHeight (version 2+)
+    pub cap_height: Option<i16>,     // OS/2 sCapHeight (version 2+)
+    pub avg_char_width: Option<i16>, // OS/2 xAvgCharWidth
+    pub underline_position: i16,     // post underlinePosition
+    pub underline_thickness: i16,    // post underlineThickness
+    pub italic_angle: f32,           // post italicAngle, degrees (negative leans right)
+    pub y_max: Option<i16>,          // head yMax - top of the tallest glyph
+    pub y_min: Option<i16>,          // head yMin - bottom of the deepest glyph
+}
+
+impl Metrics {
+    /// Builds the metrics from a face's tables; head and hhea are required
+    pub(crate) fn from_tables(
+        head: &Head,
+        hhea: &Hhea,
+        os2: Option<&Os2>,
+        post: Option<&Post>,
+    ) -> Self {
+        Metrics {
+            units_per_em: head.units_per_em,
+            ascender: hhea.ascender,
+            descender: hhea.descender,
+            line_gap: hhea.line_gap,
+            typo_ascender: os2.and_then(|o| o.typo_ascender),
+            typo_descender: os2.and_then(|o| o.typo_descender),
+            typo_line_gap: os2.and_then(|o| o.typo_line_gap),
+            win_ascent: os2.and_then(|o| o.win_ascent),
+            win_descent: os2.and_then(|o| o.win_descent),
+            use_typo_metrics: os2
+                .map(|o| o.fs_selection.use_typo_metrics())
+                .unwrap_or(false),
+            x_height: os2.and_then(|o| o.x_height),
+            cap_height: os2.and_then(|o| o.cap_height),
+            avg_char_width: os2.map(|o| o.avg_char_width).filter(|&w| w > 0),
+            underline_position: post.map(|p| p.underline_position).unwrap_or(0),
+            underline_thickness: post.map(|p| p.underline_thickness).unwrap_or(0),
+            italic_angle: post.map(|p| p.italic_angle).unwrap_or(0.0),
+            y_max: Some(head.bbox[3]),
+            y_min: Some(head.bbox[1]),
+        }
+    }
+
+    /// The default line height in design units
+    ///
+    /// Follows what browsers use for `line-height: normal`: the typo
+    /// metrics when USE_TYPO_METRICS is set, the hhea metrics otherwise.
+    pub fn line_height(&self) -> i32 {
+        let (ascent, descent, gap) = self.line_metrics();
+        ascent + descent + gap
+    }
+
+    /// Ascent, descent (positive below the baseline) and line gap in design
+    /// units, from the same metrics as `line_height`
+    pub fn line_metrics(&self) -> (i32, i32, i32) {
+        match (
+            self.use_typo_metrics,
+            self.typo_ascender,
+            self.typo_descender,
+            self.typo_line_gap,
+        ) {
+            (true, Some(ascender), Some(descender), Some(gap)) => {
+                (ascender as i32, -(descender as i32), gap as i32)
+            }
+            _ => (
+                self.ascender as i32,
+                -(self.descender as i32),
+                self.line_gap as i32,
+            ),
+        }
+    }
+
+    /// Converts design units to a percentage of the em, rounded
+    pub fn percent_of_em(&self, units: i32) -> i32 {
+        if self.units_per_em == 0 {
+            return 0;
+        }
+        (units as f32 * 100.0 / self.units_per_em as f32).round() as i32
+    }
+
+    /// True if the tallest or deepest glyphs extend past the clipping box
+    ///
+    /// Windows clips glyphs to usWinAscent/usWinDescent; without them the
+    /// hhea ascender/descender are used. Fonts without a glyph bounding box
+    /// (system API sources) are never reported as clipping.
+    pub fn clips(&self) -> bool {
+        let (top, bottom) = match (self.win_ascent, self.win_descent) {
+            (Some(ascent), Some(descent)) => (ascent as i32, -(descent as i32)),
+            _ => (self.ascender as i32, self.descender as i32),
+        };
+        let above = self.y_max.map(|y| y as i32 > top).unwrap_or(false);
+        let below = self.y_min.map(|y| (y as i32) < bottom).unwrap_or(false);
+        above || below
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FsSelection;
+
+    fn metrics(os2: Option<&Os2>) -> Metrics {
+        let head = Head {
+            units_per_em: 2048,
+            bbox: [-100, -500, 2000, 2100],
+            mac_style: 0,
+        };
+        let hhea = Hhea {
+            ascender: 1900,
+            descender: -500,
+            line_gap: 0,
+            num_h_metrics: 1,
+        };
+        let post = Post {
+            italic_angle: -12.5,
+            underline_position: -150,
+            underline_thickness: 100,
+            is_fixed_pitch: false,
+        };
+        Metrics::from_tables(&head, &hhea, os2, Some(&post))
+    }
+
+    fn os2(fs_selection: u16) -> Os2 {
+        Os2 {
+            avg_char_width: 1000,
+            fs_selection: FsSelection(fs_selection),
+            typo_ascender: Some(1500),
+            typo_descender: Some(-500),
+            typo_line_gap: Some(400),
+            win_ascent: Some(2200),
+            win_descent: Some(600),
+            x_height: Some(1100),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tables_are_combined() {
+        let m = metrics(Some(&os2(0)));
+        assert_eq!(m.units_per_em, 2048);
+        assert_eq!((m.ascender, m.descender), (1900, -500));
+        assert_eq!(m.typo_line_gap, Some(400));
+        assert_eq!(m.win_descent, Some(600));
+        assert_eq!(m.x_height, Some(1100));
+        assert_eq!(m.avg_char_width, Some(1000));
+        assert_eq!(m.italic_angle, -12.5);
+        assert_eq!((m.y_min, m.y_max), (Some(-500), Some(2100)));
+
+        let m = metrics(None);
+        assert_eq!(m.typo_ascender, None);
+        assert_eq!(m.avg_char_width, None);
+        assert!(!m.use_typo_metrics);
+    }
+
+    #[test]
+    fn line_height_follows_use_typo_metrics() {
+        assert_eq!(metrics(Some(&os2(0))).line_height(), 2400);
+        let typo = metrics(Some(&os2(FsSelection::USE_TYPO_METRICS)));
+        assert!(typo.use_typo_metrics);
+        assert_eq!(typo.line_metrics(), (1500, 500, 400));
+        assert_eq!(typo.line_height(), 2400);
+        assert_eq!(typo.percent_of_em(1024), 50);
+        assert_eq!(Metrics::default().percent_of_em(1024), 0);
+    }
+
+    #[test]
+    fn clipping_uses_win_metrics_when_present() {
+        // yMax 2100 is inside usWinAscent 2200 but above the hhea ascender
+        assert!(!metrics(Some(&os2(0))).clips());
+        assert!(metrics(None).clips());
+        let mut short = os2(0);
+        short.win_descent = Some(400);
+        assert!(metrics(Some(&short)).clips());
+        assert!(!Metrics::default().clips());
+    }
+}
@@ -0,0 +1,538 @@
+//! Pure-Rust sfnt (TrueType/OpenType) parsing
+//!
+//! Reads font files directly instead of going through a system API, so
+//! `FontInfo` can be produced on any platform:
+//! - `Face` - the table directory of one face in a file
+//! - `name`, `os2`, `head`, `hhea`, `post`, `fvar` - the tables `FontInfo` is built from
+//! - `NameTable` - every naming record, in every platform and language
+//! - `Os2` - weight/width classes, fsSelection, fsType, PANOSE, Unicode and code page ranges
+//! - `VariationAxis`, `NamedInstance` - the design space of a variable font
+//! - `Coverage` - the Unicode characters mapped by `cmap`
+//! - `Metrics` - vertical metrics from head, hhea, OS/2 and post
+//! - `Monospace` - fixed pitch from hmtx advance widths, post and PANOSE
+//! - `ColorFormats` - COLR/CPAL, SVG, sbix and CBDT color glyph support
+//! - `OutlineFormat`, `TableRecord` - glyph technology and the table directory
+//! - `Layout` - GSUB/GPOS scripts, language systems and feature tags
+//!
+//! `read_fonts` ties these together and turns a file's bytes into one
+//! `FontInfo` per face. WOFF and WOFF2 files are first decoded back into
+//! sfnt data (`woff`, `woff2`), so web fonts are read the same way.
+
+mod cmap;
+pub(crate) mod color;
+mod fvar;
+pub(crate) mod head;
+mod hhea;
+pub(crate) mod layout;
+mod maxp;
+mod metrics;
+pub(crate) mod monospace;
+mod name;
+mod os2;
+pub(crate) mod outline;
+mod post;
+mod reader;
+mod structure;
+mod woff;
+mod woff2;
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, FontInfo, Result};
+use reader::Reader;
+
+pub use cmap::Coverage;
+pub use color::{ColorFormat, ColorFormats};
+pub use fvar::{NamedInstance, VariationAxis};
+pub use layout::{LanguageSystem, Layout};
+pub use metrics::Metrics;
+pub use monospace::Monospace;
+pub use name::{LocalizedName, NameId, NameTable};
+pub use os2::{Embedding, FamilyClass, FsSelection, FsType, Os2, Panose};
+pub use outline::OutlineFormat;
+pub(crate) use structure::validate_faces;
+pub use woff::Container;
+
+// ============================================================================
+// TAGS
+// ============================================================================
+
+/// A 4-byte OpenType tag, e.g. `glyf`, `wght`, `latn`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag(pub [u8; 4]);
+
+impl Tag {
+    /// Creates a tag from its 4 ASCII bytes
+    pub const fn new(bytes: &[u8; 4]) -> Self {
+        Tag(*bytes)
+    }
+
+    /// Parses a tag written as text, e.g. `liga` or `TRK`
+    ///
+    /// Tags shorter than 4 characters are padded with spaces, as in the
+    /// font; None if the text is empty, too long or not printable ASCII.
+    pub fn from_name(name: &str) -> Option<Tag> {
+        let bytes = name.as_bytes();
+        if bytes.is_empty() || bytes.len() > 4 || !bytes.iter().all(|b| b.is_ascii_graphic()) {
+            return None;
+        }
+        let mut tag = [b' '; 4];
+        tag[..bytes.len()].copy_from_slice(bytes);
+        Some(Tag(tag))
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &b in &self.0 {
+            // Tags are printable ASCII; anything else is shown as '?'
+            let c = if (0x20..0x7F).contains(&b) {
+                b as char
+            } else {
+                '?'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as its text (`"wght"`), or as `"0x00010000"` when not printable
+impl Serialize for Tag {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if self.0.iter().all(|b| (0x20..0x7F).contains(b)) {
+            serializer.collect_str(self)
+        } else {
+            serializer.collect_str(&format_args!("0x{:08X}", u32::from_be_bytes(self.0)))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let bytes = text.as_bytes();
+        if let Ok(bytes) = <[u8; 4]>::try_from(bytes) {
+            if bytes.iter().all(|b| (0x20..0x7F).contains(b)) {
+                return Ok(Tag(bytes));
+            }
+        }
+        text.strip_prefix("0x")
+            .filter(|hex| hex.len() == 8)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .map(|value| Tag(value.to_be_bytes()))
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid tag '{}'", text)))
+    }
+}
+
+// ============================================================================
+// TABLE DIRECTORY
+// ============================================================================
+
+/// Signature of a TrueType/OpenType collection file
+const TTC_TAG: Tag = Tag::new(b"ttcf");
+
+/// One entry of a face's table directory
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableRecord {
+    pub tag: Tag,
+    pub checksum: u32, // As stored in the directory, not recomputed
+    pub offset: u32,   // From the start of the (decoded) file
+    pub length: u32,   // Unpadded length in bytes
+}
+
+/// A single face inside a font file, identified by its table directory
+pub(crate) struct Face<'a> {
+    data: &'a [u8],
+    sfnt_version: Tag,
+    tables: Vec<TableRecord>,
+}
+
+impl<'a> Face<'a> {
+    /// Parses the table directory starting at `offset` in `data`
+    pub fn parse(data: &'a [u8], offset: usize) -> Result<Self> {
+        let mut r = Reader::at(data, offset)?;
+        let sfnt_version = r.tag()?;
+        match &sfnt_version.0 {
+            b"\0\x01\0\0" | b"OTTO" | b"true" => {} // 1.0, 'OTTO', 'true'
+            _ => {
+                return Err(Error::Parse(format!(
+                    "unknown sfnt version 0x{:08X}",
+                    u32::from_be_bytes(sfnt_version.0)
+                )))
+            }
+        }
+
+        let num_tables = r.u16()?;
+        r.skip(6)?; // searchRange, entrySelector, rangeShift
+
+        let mut tables = Vec::with_capacity(num_tables as usize);
+        for _ in 0..num_tables {
+            let tag = r.tag()?;
+            let checksum = r.u32()?;
+            let offset = r.u32()?;
+            let length = r.u32()?;
+            tables.push(TableRecord {
+                tag,
+                checksum,
+                offset,
+                length,
+            });
+        }
+
+        Ok(Face {
+            data,
+            sfnt_version,
+            tables,
+        })
+    }
+
+    /// True if the table directory lists `tag`
+    pub fn has_table(&self, tag: Tag) -> bool {
+        self.tables.iter().any(|t| t.tag == tag)
+    }
+
+    /// Returns the bytes of a table, or None if it is missing or out of bounds
+    pub fn table(&self, tag: Tag) -> Option<&'a [u8]> {
+        let record = self.tables.iter().find(|t| t.tag == tag)?;
+        let start = record.offset as usize;
+        let end = start.checked_add(record.length as usize)?;
+        self.data.get(start..end)
+    }
+
+    /// The outline format, from the table directory and loca
+    pub fn outline_format(&self) -> Option<OutlineFormat> {
+        OutlineFormat::detect(
+            |tag| self.has_table(tag),
+            || outline::has_glyf_outlines(self.table(head::TAG), self.table(outline::LOCA)),
+        )
+    }
+}
+
+/// True if the file is a TrueType/OpenType collection (.ttc/.otc)
+pub(crate) fn is_collection(data: &[u8]) -> bool {
+    data.starts_with(&TTC_TAG.0)
+}
+
+/// Returns the offsets of every face's table directory in a file
+///
+/// A plain sfnt file has a single face at offset 0; a collection (.ttc/.otc)
+/// lists one offset per member face in its header. Version 2.0 headers add
+/// a DSIG reference after the offsets, which is not needed here.
+pub(crate) fn face_offsets(data: &[u8]) -> Result<Vec<usize>> {
+    if !is_collection(data) {
+        return Ok(vec![0]);
+    }
+
+    let mut r = Reader::at(data, 4)?;
+    let major_version = r.u16()?;
+    let _minor_version = r.u16()?;
+    if !matches!(major_version, 1 | 2) {
+        return Err(Error::Parse(format!(
+            "unknown collection version {}",
+            major_version
+        )));
+    }
+    let num_fonts = r.u32()?;
+    let mut offsets = Vec::new();
+    for _ in 0..num_fonts {
+        offsets.push(r.u32()? as usize);
+    }
+    Ok(offsets)
+}
+
+// ============================================================================
+// FONT INFO
+// ============================================================================
+
+/// Parses every face in a font file into a `FontInfo`
+///
+/// `file_path` is recorded as-is on each face; faces of a collection also
+/// get their `face_index`. WOFF/WOFF2 files are decoded first and their
+/// faces get the matching `container`. Fails if the file is not a font, or
+/// a face lacks a name table or has a malformed name or head table; other
+/// tables that can't be parsed are treated as absent.
+pub fn read_fonts(data: &[u8], file_path: &str) -> Result<Vec<FontInfo>> {
+    let (data, container) = decode_container(data)?;
+    let data: &[u8] = &data;
+
+    let collection = is_collection(data);
+    face_offsets(data)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, offset)| {
+            let mut font = read_face(&Face::parse(data, offset)?, file_path)?;
+            font.face_index = collection.then_some(index as u32);
+            font.container = container;
+            Ok(font)
+        })
+        .collect()
+}
+
+/// Unwraps WOFF/WOFF2 files into sfnt data; other data is passed through
+fn decode_container(data: &[u8]) -> Result<(Cow<'_, [u8]>, Container)> {
+    if data.starts_with(&woff::WOFF_SIGNATURE.0) {
+        Ok((Cow::Owned(woff::decode_woff(data)?), Container::Woff))
+    } else if data.starts_with(&woff2::WOFF2_SIGNATURE.0) {
+        Ok((Cow::Owned(woff2::decode_woff2(data)?), Container::Woff2))
+    } else {
+        Ok((Cow::Borrowed(data), Container::Sfnt))
+    }
+}
+
+/// Builds a `FontInfo` from one face's tables
+fn read_face(face: &Face, file_path: &str) -> Result<FontInfo> {
+    let names = face
+        .table(name::TAG)
+        .ok_or_else(|| Error::Parse("missing 'name' table".to_string()))?;
+    let names = name::parse(names)?;
+    let (family_name, style_name) = names.family_and_style()?;
+
+    let head = face.table(head::TAG).map(head::parse).transpose()?;
+
+    // The other tables are optional: a malformed one reads as missing
+    let os2 = face.table(os2::TAG).and_then(|d| os2::parse(d).ok());
+    let post = face.table(post::TAG).and_then(|d| post::parse(d).ok());
+    let hhea = face.table(hhea::TAG).and_then(|d| hhea::parse(d).ok());
+    let color =
+        ColorFormats::from_tables(face.table(color::COLR), face.table(color::CPAL), |tag| {
+            face.table(tag).is_some()
+        })
+        .unwrap_or_default();
+    let layout = Layout::from_tables(face.table(layout::GSUB), face.table(layout::GPOS));
+    let glyph_count = face
+        .table(maxp::TAG)
+        .and_then(|d| maxp::parse_num_glyphs(d).ok());
+    let monospace = Monospace::from_tables(
+        face.table(monospace::HMTX),
+        hhea.as_ref().map(|h| h.num_h_metrics),
+        glyph_count,
+        post.as_ref().map(|p| p.is_fixed_pitch),
+        os2.as_ref().map(|o| o.panose.0),
+    );
+    let coverage = face
+        .table(cmap::TAG)
+        .and_then(|d| cmap::parse(d).ok())
+        .unwrap_or_default();
+    let (axes, instances) = face
+        .table(fvar::TAG)
+        .and_then(|d| fvar::parse(d, &names).ok())
+        .unwrap_or_default();
+
+    // Weight: OS/2 usWeightClass, falling back to the macStyle bold bit
+    let weight = match (&os2, &head) {
+        (Some(os2), _) => os2.weight_class as i32,
+        (None, Some(head)) if head.is_bold() => 700,
+        _ => 400,
+    };
+
+    // Width: OS/2 usWidthClass, normal (5) without an OS/2 table
+    let width = os2.as_ref().map(|os2| os2.width_class as i32).unwrap_or(5);
+
+    // Italic: OS/2 fsSelection ITALIC/OBLIQUE, falling back to macStyle
+    let italic = match (&os2, &head) {
+        (Some(os2), _) => os2.fs_selection.is_italic() || os2.fs_selection.is_oblique(),
+        (None, Some(head)) => head.is_italic(),
+        _ => false,
+    };
+
+    let metrics = match (&head, &hhea) {
+        (Some(head), Some(hhea)) => Some(Metrics::from_tables(
+            head,
+            hhea,
+            os2.as_ref(),
+            post.as_ref(),
+        )),
+        _ => None,
+    };
+
+    Ok(FontInfo {
+        family_name,
+        style_name,
+        file_path: file_path.to_string(),
+        // Only axes with a real range make a font variable (same rule as FontSet)
+        is_variable: axes.iter().any(|a| a.is_variable()),
+        variable_axes: axes,
+        named_instances: instances,
+        instance: None,
+        weight,
+        width,
+        italic,
+        fixed_pitch: monospace.is_monospace(),
+        monospace,
+        mac_style: head.map(|h| h.mac_style),
+        metrics,
+        color,
+        sfnt_version: Some(face.sfnt_version),
+        outline: face.outline_format(),
+        tables: face.tables.clone(),
+        glyph_count,
+        layout,
+        names,
+        os2,
+        coverage,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A name table with a Windows English family name
+    pub(super) fn name_table(family: &str) -> Vec<u8> {
+        let text: Vec<u8> = family.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let mut out = Vec::new();
+        for v in [0, 1, 18, 3, 1, 0x409, 1, text.len() as u16, 0] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        out.extend_from_slice(&text);
+        out
+    }
+
+    /// An sfnt with `tables`, which must be sorted by tag
+    pub(super) fn sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff::push_search_fields(&mut out, tables.len());
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, data) in tables {
+            out.extend_from_slice(*tag);
+            out.extend_from_slice(&woff::table_checksum(Tag(**tag), data).to_be_bytes());
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += data.len().next_multiple_of(4);
+        }
+        for (_, data) in tables {
+            out.extend_from_slice(data);
+            out.resize(out.len().next_multiple_of(4), 0);
+        }
+        out
+    }
+
+    #[test]
+    fn malformed_optional_tables_read_as_missing() {
+        let data = sfnt(&[
+            (b"OS/2", vec![0, 1]),
+            (b"cmap", vec![0]),
+            (b"fvar", vec![0, 1, 0]),
+            (b"name", name_table("Test Sans")),
+        ]);
+        let fonts = read_fonts(&data, "test.ttf").unwrap();
+        assert_eq!(fonts.len(), 1);
+        assert_eq!(fonts[0].family_name, "Test Sans");
+        assert_eq!(fonts[0].style_name, "Regular");
+        assert_eq!(fonts[0].os2, None);
+        assert_eq!(fonts[0].weight, 400);
+        assert!(fonts[0].coverage.ranges().is_empty());
+        assert!(fonts[0].variable_axes.is_empty());
+    }
+
+    #[test]
+    fn name_and_head_are_required() {
+        assert!(read_fonts(&sfnt(&[(b"OS/2", vec![0; 100])]), "").is_err());
+        assert!(read_fonts(&sfnt(&[(b"name", vec![0, 0, 0])]), "").is_err());
+        let data = sfnt(&[(b"head", vec![0; 10]), (b"name", name_table("Test Sans"))]);
+        assert!(read_fonts(&data, "").is_err());
+    }
+
+    /// A collection whose faces each have a name table with one family
+    fn collection(version: u16, families: &[&str]) -> Vec<u8> {
+        let n = families.len();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"ttcf");
+        for v in [version, 0] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        out.extend_from_slice(&(n as u32).to_be_bytes());
+        let directories = 12 + n * 4;
+        for i in 0..n {
+            out.extend_from_slice(&((directories + i * 28) as u32).to_be_bytes());
+        }
+        let mut offset = directories + n * 28;
+        let tables: Vec<Vec<u8>> = families.iter().map(|f| name_table(f)).collect();
+        for table in &tables {
+            out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            woff::push_search_fields(&mut out, 1);
+            out.extend_from_slice(b"name");
+            out.extend_from_slice(&0u32.to_be_bytes());
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += table.len();
+        }
+        for table in &tables {
+            out.extend_from_slice(table);
+        }
+        out
+    }
+
+    #[test]
+    fn collection_faces_get_their_index() {
+        for version in [1, 2] {
+            let data = collection(version, &["MS Gothic", "MS PGothic", "MS UI Gothic"]);
+            assert!(is_collection(&data));
+            let fonts = read_fonts(&data, "msgothic.ttc").unwrap();
+            let faces: Vec<(&str, Option<u32>)> = fonts
+                .iter()
+                .map(|f| (f.family_name.as_str(), f.face_index))
+                .collect();
+            assert_eq!(
+                faces,
+                [
+                    ("MS Gothic", Some(0)),
+                    ("MS PGothic", Some(1)),
+                    ("MS UI Gothic", Some(2))
+                ]
+            );
+            assert_eq!(fonts[1].location(), "msgothic.ttc#1");
+        }
+    }
+
+    #[test]
+    fn single_fonts_have_no_index() {
+        let data = sfnt(&[(b"name", name_table("Test Sans"))]);
+        assert!(!is_collection(&data));
+        let fonts = read_fonts(&data, "test.ttf").unwrap();
+        assert_eq!(fonts[0].face_index, None);
+        assert_eq!(fonts[0].location(), "test.ttf");
+    }
+
+    #[test]
+    fn unknown_or_truncated_collections_are_errors() {
+        assert!(face_offsets(&collection(3, &["A"])).is_err());
+        let data = collection(1, &["A", "B"]);
+        assert!(face_offsets(&data[..14]).is_err());
+        // A face offset past the end of the file
+        let mut data = collection(1, &["A"]);
+        data[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(read_fonts(&data, "").is_err());
+    }
+
+    #[test]
+    fn table_directory_and_outline_format() {
+        let data = sfnt(&[
+            (b"CFF ", vec![1, 0, 4, 1]),
+            (b"name", name_table("Test Sans")),
+        ]);
+        let font = &read_fonts(&data, "").unwrap()[0];
+        let tables: Vec<(Tag, u32, u32)> = font
+            .tables
+            .iter()
+            .map(|t| (t.tag, t.offset, t.length))
+            .collect();
+        assert_eq!(
+            tables,
+            [(Tag::new(b"CFF "), 44, 4), (Tag::new(b"name"), 48, 36)]
+        );
+        assert_eq!(font.outline, Some(OutlineFormat::Cff));
+        assert_eq!(font.sfnt_version, Some(Tag(0x0001_0000u32.to_be_bytes())));
+    }
+}
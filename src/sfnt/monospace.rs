@@ -0,0 +1,234 @@
+//! Monospace detection from hmtx advance widths, post and PANOSE
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const HMTX: Tag = Tag::new(b"hmtx");
+
+/// PANOSE family type "Latin Text", whose proportion digit 9 means monospaced
+const PANOSE_LATIN_TEXT: u8 = 2;
+const PANOSE_MONOSPACED: u8 = 9;
+
+/// The signals that say whether a font is monospaced
+///
+/// Each is `None` when the source or font doesn't provide it. The advance
+/// widths are the ground truth; the flags are what applications read, so
+/// a flag that disagrees with the widths is worth knowing about.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Monospace {
+    pub advance: Option<u16>, // The single (half-width) advance, if the widths are monospaced
+    pub hmtx: Option<bool>,   // Every non-zero advance is one width or double that width
+    pub post: Option<bool>,   // post.isFixedPitch
+    pub panose: Option<bool>, // PANOSE proportion (Latin Text fonts only)
+    pub api: Option<bool>,    // The system API's answer (GDI pitch bits, IsMonospacedFont)
+}
+
+impl Monospace {
+    /// The verdict: the advance widths if known, else the flags' majority
+    ///
+    /// Without widths a tie (or no signal at all) counts as proportional.
+    pub fn is_monospace(&self) -> bool {
+        if let Some(hmtx) = self.hmtx {
+            return hmtx;
+        }
+        let flags = [self.post, self.panose, self.api];
+        let yes = flags.iter().filter(|&&f| f == Some(true)).count();
+        let no = flags.iter().filter(|&&f| f == Some(false)).count();
+        yes > no
+    }
+
+    /// The signals that are known, by name, e.g. `[("hmtx", true), ("post", false)]`
+    pub fn signals(&self) -> Vec<(&'static str, bool)> {
+        [
+            ("hmtx", self.hmtx),
+            ("post", self.post),
+            ("panose", self.panose),
+            ("api", self.api),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect()
+    }
+
+    /// Names of the signals that disagree with `is_monospace`
+    pub fn conflicts(&self) -> Vec<&'static str> {
+        let verdict = self.is_monospace();
+        self.signals()
+            .into_iter()
+            .filter(|&(_, v)| v != verdict)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Builds the signals from already parsed table values
+    ///
+    /// `num_glyphs` and `num_h_metrics` come from maxp and hhea; hmtx is
+    /// only examined when both are known, and a truncated hmtx leaves the
+    /// widths unknown rather than failing the font.
+    pub(crate) fn from_tables(
+        hmtx: Option<&[u8]>,
+        num_h_metrics: Option<u16>,
+        num_glyphs: Option<u16>,
+        is_fixed_pitch: Option<bool>,
+        panose: Option<[u8; 10]>,
+    ) -> Self {
+        let advance = match (hmtx, num_h_metrics, num_glyphs) {
+            (Some(hmtx), Some(metrics), Some(glyphs)) => {
+                monospace_advance(hmtx, metrics, glyphs).ok().flatten()
+            }
+            _ => None,
+        };
+        Monospace {
+            advance: advance.flatten(),
+            hmtx: advance.map(|a| a.is_some()),
+            post: is_fixed_pitch,
+            panose: panose
+                .filter(|p| p[0] == PANOSE_LATIN_TEXT)
+                .map(|p| p[3] == PANOSE_MONOSPACED),
+            api: None,
+        }
+    }
+
+    /// Builds the signals from the raw hhea, maxp, hmtx, post and OS/2 tables
+    ///
+    /// For sources that hand out table data rather than a parsed face;
+    /// tables that fail to parse count as missing.
+    #[cfg(windows)]
+    pub(crate) fn from_raw_tables(
+        hhea: Option<&[u8]>,
+        maxp: Option<&[u8]>,
+        hmtx: Option<&[u8]>,
+        post: Option<&[u8]>,
+        os2: Option<&[u8]>,
+    ) -> Self {
+        Monospace::from_tables(
+            hmtx,
+            hhea.and_then(|t| super::hhea::parse(t).ok())
+                .map(|h| h.num_h_metrics),
+            maxp.and_then(|t| super::maxp::parse_num_glyphs(t).ok()),
+            post.and_then(|t| super::post::parse(t).ok())
+                .map(|p| p.is_fixed_pitch),
+            os2.and_then(|t| super::os2::parse(t).ok())
+                .map(|o| o.panose.0),
+        )
+    }
+}
+
+impl fmt::Display for Monospace {
+    /// Formats the known signals, e.g. `hmtx=yes post=no panose=yes`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.signals().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", name, if value { "yes" } else { "no" })?;
+        }
+        Ok(())
+    }
+}
+
+/// The font's advance width if all glyphs share it
+///
+/// `Some(Some(width))` for monospaced widths, `Some(None)` for proportional
+/// ones and `None` when there are no non-zero widths to judge by.
+/// Zero-width glyphs (combining marks) are ignored, and so is .notdef
+/// unless it holds the only stored advance; glyphs exactly twice the
+/// narrowest width (full-width CJK) are allowed. Glyphs past
+/// numberOfHMetrics repeat the last advance, so only the stored advances
+/// need to be read.
+fn monospace_advance(
+    hmtx: &[u8],
+    num_h_metrics: u16,
+    num_glyphs: u16,
+) -> Result<Option<Option<u16>>> {
+    let count = num_h_metrics.min(num_glyphs) as usize;
+    let mut r = Reader::new(hmtx);
+    let mut advances = Vec::with_capacity(count);
+    for _ in 0..count {
+        advances.push(r.u16()?);
+        r.skip(2)?; // lsb
+    }
+
+    // With a single stored metric, that advance applies to every glyph
+    let skip = if advances.len() > 1 { 1 } else { 0 };
+    let widths = || advances.iter().skip(skip).copied().filter(|&a| a != 0);
+
+    // The narrowest width is the half width; anything else must be double it
+    let Some(width) = widths().min() else {
+        return Ok(None);
+    };
+    let mono = widths().all(|a| a == width || a as u32 == width as u32 * 2);
+    Ok(Some(mono.then_some(width)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// hmtx data with the given advances and zero side bearings
+    fn hmtx(advances: &[u16]) -> Vec<u8> {
+        advances
+            .iter()
+            .flat_map(|a| [a.to_be_bytes(), [0, 0]].concat())
+            .collect()
+    }
+
+    fn signals(advances: &[u16], num_glyphs: u16) -> Monospace {
+        let data = hmtx(advances);
+        Monospace::from_tables(
+            Some(&data),
+            Some(advances.len() as u16),
+            Some(num_glyphs),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn single_stored_metric_applies_to_every_glyph() {
+        let mono = signals(&[600], 500);
+        assert_eq!(mono.advance, Some(600));
+        assert_eq!(mono.hmtx, Some(true));
+        assert!(mono.is_monospace());
+    }
+
+    #[test]
+    fn full_width_glyphs_are_double_the_half_width() {
+        let mono = signals(&[0, 500, 1000, 500, 0, 1000], 6);
+        assert_eq!(mono.advance, Some(500));
+        assert_eq!(mono.hmtx, Some(true));
+    }
+
+    #[test]
+    fn notdef_width_is_ignored_when_more_are_stored() {
+        assert_eq!(signals(&[1000, 600, 600], 3).hmtx, Some(true));
+    }
+
+    #[test]
+    fn proportional_widths() {
+        let mono = signals(&[500, 250, 600, 720], 4);
+        assert_eq!(mono.advance, None);
+        assert_eq!(mono.hmtx, Some(false));
+        assert!(!mono.is_monospace());
+    }
+
+    #[test]
+    fn no_widths_leave_the_flags_to_decide() {
+        let data = hmtx(&[0, 0]);
+        let mono = Monospace::from_tables(Some(&data), Some(2), Some(2), Some(true), None);
+        assert_eq!(mono.hmtx, None);
+        assert!(mono.is_monospace());
+    }
+
+    #[test]
+    fn truncated_hmtx_leaves_the_widths_unknown() {
+        let data = hmtx(&[600]);
+        let mono = Monospace::from_tables(Some(&data), Some(3), Some(3), None, None);
+        assert_eq!(mono.hmtx, None);
+    }
+}
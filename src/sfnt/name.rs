@@ -0,0 +1,662 @@
+//! `name` table - naming records (family, style, copyright, ...)
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::{Error, Result};
+
+pub(crate) const TAG: Tag = Tag::new(b"name");
+
+// Platform IDs
+const PLATFORM_UNICODE: u16 = 0;
+const PLATFORM_MACINTOSH: u16 = 1;
+const PLATFORM_WINDOWS: u16 = 3;
+
+/// Windows language ID for English (United States)
+const LANGUAGE_EN_US: u16 = 0x0409;
+
+// ============================================================================
+// NAME IDS
+// ============================================================================
+
+/// An OpenType name ID, e.g. `NameId::FULL_NAME`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NameId(pub u16);
+
+impl NameId {
+    pub const COPYRIGHT: NameId = NameId(0);
+    pub const FAMILY: NameId = NameId(1);
+    pub const SUBFAMILY: NameId = NameId(2);
+    pub const UNIQUE_ID: NameId = NameId(3);
+    pub const FULL_NAME: NameId = NameId(4);
+    pub const VERSION: NameId = NameId(5);
+    pub const POSTSCRIPT_NAME: NameId = NameId(6);
+    pub const TRADEMARK: NameId = NameId(7);
+    pub const MANUFACTURER: NameId = NameId(8);
+    pub const DESIGNER: NameId = NameId(9);
+    pub const DESCRIPTION: NameId = NameId(10);
+    pub const VENDOR_URL: NameId = NameId(11);
+    pub const DESIGNER_URL: NameId = NameId(12);
+    pub const LICENSE: NameId = NameId(13);
+    pub const LICENSE_URL: NameId = NameId(14);
+    pub const TYPOGRAPHIC_FAMILY: NameId = NameId(16);
+    pub const TYPOGRAPHIC_SUBFAMILY: NameId = NameId(17);
+    pub const COMPATIBLE_FULL: NameId = NameId(18);
+    pub const SAMPLE_TEXT: NameId = NameId(19);
+    pub const POSTSCRIPT_CID_NAME: NameId = NameId(20);
+    pub const WWS_FAMILY: NameId = NameId(21);
+    pub const WWS_SUBFAMILY: NameId = NameId(22);
+    pub const LIGHT_BACKGROUND_PALETTE: NameId = NameId(23);
+    pub const DARK_BACKGROUND_PALETTE: NameId = NameId(24);
+    pub const VARIATIONS_POSTSCRIPT_PREFIX: NameId = NameId(25);
+
+    /// Human-readable description of the predefined name IDs
+    ///
+    /// IDs 26-255 are reserved and 256+ are font-specific (referenced from
+    /// fvar, STAT, CPAL, ...); those return None.
+    pub fn label(self) -> Option<&'static str> {
+        Some(match self.0 {
+            0 => "Copyright",
+            1 => "Family",
+            2 => "Subfamily",
+            3 => "Unique ID",
+            4 => "Full Name",
+            5 => "Version",
+            6 => "PostScript Name",
+            7 => "Trademark",
+            8 => "Manufacturer",
+            9 => "Designer",
+            10 => "Description",
+            11 => "Vendor URL",
+            12 => "Designer URL",
+            13 => "License",
+            14 => "License URL",
+            16 => "Typographic Family",
+            17 => "Typographic Subfamily",
+            18 => "Compatible Full Name",
+            19 => "Sample Text",
+            20 => "PostScript CID Name",
+            21 => "WWS Family",
+            22 => "WWS Subfamily",
+            23 => "Light Background Palette",
+            24 => "Dark Background Palette",
+            25 => "Variations PostScript Prefix",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for NameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label() {
+            Some(label) => write!(f, "{}", label),
+            None => write!(f, "Name {}", self.0),
+        }
+    }
+}
+
+// ============================================================================
+// NAME TABLE
+// ============================================================================
+
+/// One decoded naming record
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LocalizedName {
+    pub platform_id: u16,
+    pub encoding_id: u16,
+    pub language_id: u16,
+    pub language: String, // BCP 47 tag, e.g. "en-US", "ja-JP"
+    pub value: String,
+}
+
+/// Every decodable record of a face's `name` table, keyed by name ID
+///
+/// Records for all platforms and languages are kept in table order.
+/// Records in legacy multi-byte encodings (Shift-JIS, Big5, Mac Japanese, ...)
+/// are not decoded and are left out.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NameTable {
+    entries: BTreeMap<NameId, Vec<LocalizedName>>,
+}
+
+impl NameTable {
+    /// True if no records were decoded (e.g. for API-based sources)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All localized strings for a name ID
+    pub fn get(&self, id: NameId) -> &[LocalizedName] {
+        self.entries.get(&id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Iterates over name IDs in ascending order with their localized strings
+    pub fn iter(&self) -> impl Iterator<Item = (NameId, &[LocalizedName])> {
+        self.entries
+            .iter()
+            .map(|(&id, names)| (id, names.as_slice()))
+    }
+
+    /// The string for a name ID in a given language (BCP 47, case-insensitive)
+    pub fn localized(&self, id: NameId, language: &str) -> Option<&str> {
+        self.get(id)
+            .iter()
+            .find(|n| n.language.eq_ignore_ascii_case(language))
+            .map(|n| n.value.as_str())
+    }
+
+    /// Picks the most suitable string for a name ID
+    ///
+    /// Preference: Windows English (US), then any Windows or Unicode record,
+    /// then Macintosh English, then whatever is left.
+    pub fn best(&self, id: NameId) -> Option<&str> {
+        let candidates: Vec<&LocalizedName> = self
+            .get(id)
+            .iter()
+            .filter(|n| !n.value.is_empty())
+            .collect();
+
+        candidates
+            .iter()
+            .find(|n| n.platform_id == PLATFORM_WINDOWS && n.language_id == LANGUAGE_EN_US)
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|n| n.platform_id == PLATFORM_WINDOWS)
+            })
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|n| n.platform_id == PLATFORM_UNICODE)
+            })
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|n| n.platform_id == PLATFORM_MACINTOSH && n.language_id == 0)
+            })
+            .or_else(|| candidates.first())
+            .map(|n| n.value.as_str())
+    }
+
+    /// The family and style names of a face
+    ///
+    /// These are the legacy RIBBI names (IDs 1/2), which GDI and the
+    /// platform font sets report too, so every source lists a file under
+    /// the same family. The typographic names (IDs 16/17) stay in the table.
+    pub fn family_and_style(&self) -> Result<(String, String)> {
+        let family = self
+            .best(NameId::FAMILY)
+            .ok_or_else(|| Error::Parse("no family name record".to_string()))?;
+        let style = self.best(NameId::SUBFAMILY).unwrap_or("Regular");
+
+        Ok((family.to_string(), style.to_string()))
+    }
+}
+
+#[cfg(test)]
+impl NameTable {
+    /// A table of Windows English (US) records, for tests outside this module
+    pub(crate) fn english(records: &[(NameId, &str)]) -> Self {
+        let mut table = NameTable::default();
+        for &(id, value) in records {
+            table.entries.entry(id).or_default().push(LocalizedName {
+                platform_id: PLATFORM_WINDOWS,
+                encoding_id: 1,
+                language_id: LANGUAGE_EN_US,
+                language: "en-US".to_string(),
+                value: value.to_string(),
+            });
+        }
+        table
+    }
+}
+
+/// Parses all naming records that can be decoded
+pub(crate) fn parse(data: &[u8]) -> Result<NameTable> {
+    let mut r = Reader::new(data);
+    let version = r.u16()?;
+    let count = r.u16()?;
+    let storage_offset = r.u16()? as usize;
+
+    let mut raw = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let platform_id = r.u16()?;
+        let encoding_id = r.u16()?;
+        let language_id = r.u16()?;
+        let name_id = r.u16()?;
+        let length = r.u16()? as usize;
+        let offset = r.u16()? as usize;
+        raw.push((
+            platform_id,
+            encoding_id,
+            language_id,
+            name_id,
+            length,
+            offset,
+        ));
+    }
+
+    // Version 1 adds language-tag records, referenced by language IDs >= 0x8000
+    let mut lang_tags = Vec::new();
+    if version >= 1 {
+        let lang_tag_count = r.u16()?;
+        for _ in 0..lang_tag_count {
+            let length = r.u16()? as usize;
+            let offset = r.u16()? as usize;
+            let tag = storage_bytes(data, storage_offset + offset, length)
+                .and_then(|b| decode(PLATFORM_UNICODE, 0, b))
+                .unwrap_or_default();
+            lang_tags.push(tag);
+        }
+    }
+
+    let mut table = NameTable::default();
+    for (platform_id, encoding_id, language_id, name_id, length, offset) in raw {
+        // Skip records pointing outside the table
+        let bytes = match storage_bytes(data, storage_offset + offset, length) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+        if let Some(value) = decode(platform_id, encoding_id, bytes) {
+            let language = language_tag(platform_id, language_id, &lang_tags);
+            table
+                .entries
+                .entry(NameId(name_id))
+                .or_default()
+                .push(LocalizedName {
+                    platform_id,
+                    encoding_id,
+                    language_id,
+                    language,
+                    value,
+                });
+        }
+    }
+    Ok(table)
+}
+
+/// Returns `length` bytes of string storage at `offset`, if in bounds
+fn storage_bytes(data: &[u8], offset: usize, length: usize) -> Option<&[u8]> {
+    Reader::at(data, offset)
+        .and_then(|mut s| s.bytes(length))
+        .ok()
+}
+
+/// Decodes a name string according to its platform and encoding
+///
+/// Unicode and Windows records are UTF-16BE; Macintosh Roman records use
+/// the Mac OS Roman character set. Other encodings are not decoded.
+fn decode(platform_id: u16, encoding_id: u16, bytes: &[u8]) -> Option<String> {
+    match (platform_id, encoding_id) {
+        (PLATFORM_UNICODE, _) | (PLATFORM_WINDOWS, 0 | 1 | 10) => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        (PLATFORM_MACINTOSH, 0) => Some(bytes.iter().map(|&b| mac_roman(b)).collect()),
+        _ => None,
+    }
+}
+
+/// Maps a Mac OS Roman byte to its Unicode character
+fn mac_roman(b: u8) -> char {
+    const HIGH: [char; 128] = [
+        'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è', 'ê', 'ë',
+        'í', 'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü', '†', '°', '¢', '£',
+        '§', '•', '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø', '∞', '±', '≤', '≥', '¥', 'µ',
+        '∂', '∑', '∏', 'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø', '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«',
+        '»', '…', '\u{A0}', 'À', 'Ã', 'Õ', 'Œ', 'œ', '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ',
+        'Ÿ', '⁄', '€', '‹', '›', 'ﬁ', 'ﬂ', '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í',
+        'Î', 'Ï', 'Ì', 'Ó', 'Ô', '\u{F8FF}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚',
+        '¸', '˝', '˛', 'ˇ',
+    ];
+    if b < 0x80 {
+        b as char
+    } else {
+        HIGH[(b - 0x80) as usize]
+    }
+}
+
+// ============================================================================
+// LANGUAGE TAGS
+// ============================================================================
+
+/// Converts a record's language ID to a BCP 47 tag
+///
+/// Unknown IDs are kept visible as `und-x-win-XXXX` / `und-x-mac-N`.
+fn language_tag(platform_id: u16, language_id: u16, lang_tags: &[String]) -> String {
+    if language_id >= 0x8000 {
+        if let Some(tag) = lang_tags.get((language_id - 0x8000) as usize) {
+            return tag.clone();
+        }
+    }
+
+    let known = match platform_id {
+        PLATFORM_WINDOWS => windows_language(language_id),
+        PLATFORM_MACINTOSH => mac_language(language_id),
+        _ => None,
+    };
+    match (known, platform_id) {
+        (Some(tag), _) => tag.to_string(),
+        (None, PLATFORM_WINDOWS) => format!("und-x-win-{:04x}", language_id),
+        (None, PLATFORM_MACINTOSH) => format!("und-x-mac-{}", language_id),
+        (None, _) => "und".to_string(),
+    }
+}
+
+/// Windows LCIDs used in name records
+fn windows_language(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x0401 => "ar-SA",
+        0x0402 => "bg-BG",
+        0x0403 => "ca-ES",
+        0x0404 => "zh-TW",
+        0x0405 => "cs-CZ",
+        0x0406 => "da-DK",
+        0x0407 => "de-DE",
+        0x0408 => "el-GR",
+        0x0409 => "en-US",
+        0x040A => "es-ES-u-co-trad",
+        0x040B => "fi-FI",
+        0x040C => "fr-FR",
+        0x040D => "he-IL",
+        0x040E => "hu-HU",
+        0x040F => "is-IS",
+        0x0410 => "it-IT",
+        0x0411 => "ja-JP",
+        0x0412 => "ko-KR",
+        0x0413 => "nl-NL",
+        0x0414 => "nb-NO",
+        0x0415 => "pl-PL",
+        0x0416 => "pt-BR",
+        0x0417 => "rm-CH",
+        0x0418 => "ro-RO",
+        0x0419 => "ru-RU",
+        0x041A => "hr-HR",
+        0x041B => "sk-SK",
+        0x041C => "sq-AL",
+        0x041D => "sv-SE",
+        0x041E => "th-TH",
+        0x041F => "tr-TR",
+        0x0420 => "ur-PK",
+        0x0421 => "id-ID",
+        0x0422 => "uk-UA",
+        0x0423 => "be-BY",
+        0x0424 => "sl-SI",
+        0x0425 => "et-EE",
+        0x0426 => "lv-LV",
+        0x0427 => "lt-LT",
+        0x0429 => "fa-IR",
+        0x042A => "vi-VN",
+        0x042B => "hy-AM",
+        0x042C => "az-Latn-AZ",
+        0x042D => "eu-ES",
+        0x042F => "mk-MK",
+        0x0436 => "af-ZA",
+        0x0437 => "ka-GE",
+        0x0439 => "hi-IN",
+        0x043E => "ms-MY",
+        0x043F => "kk-KZ",
+        0x0441 => "sw-KE",
+        0x0443 => "uz-Latn-UZ",
+        0x0445 => "bn-IN",
+        0x0446 => "pa-IN",
+        0x0447 => "gu-IN",
+        0x0449 => "ta-IN",
+        0x044A => "te-IN",
+        0x044B => "kn-IN",
+        0x044C => "ml-IN",
+        0x044E => "mr-IN",
+        0x0450 => "mn-MN",
+        0x0452 => "cy-GB",
+        0x0453 => "km-KH",
+        0x0454 => "lo-LA",
+        0x0456 => "gl-ES",
+        0x045A => "syr-SY",
+        0x045B => "si-LK",
+        0x045E => "am-ET",
+        0x0461 => "ne-NP",
+        0x0462 => "fy-NL",
+        0x0463 => "ps-AF",
+        0x0464 => "fil-PH",
+        0x0465 => "dv-MV",
+        0x046A => "yo-NG",
+        0x0481 => "mi-NZ",
+        0x0804 => "zh-CN",
+        0x0807 => "de-CH",
+        0x0809 => "en-GB",
+        0x080A => "es-MX",
+        0x080C => "fr-BE",
+        0x0810 => "it-CH",
+        0x0813 => "nl-BE",
+        0x0814 => "nn-NO",
+        0x0816 => "pt-PT",
+        0x081A => "sr-Latn-CS",
+        0x081D => "sv-FI",
+        0x0C04 => "zh-HK",
+        0x0C07 => "de-AT",
+        0x0C09 => "en-AU",
+        0x0C0A => "es-ES",
+        0x0C0C => "fr-CA",
+        0x0C1A => "sr-Cyrl-CS",
+        0x1004 => "zh-SG",
+        0x1009 => "en-CA",
+        0x100C => "fr-CH",
+        0x1404 => "zh-MO",
+        0x1409 => "en-NZ",
+        0x1809 => "en-IE",
+        0x1C09 => "en-ZA",
+        0x4009 => "en-IN",
+        _ => return None,
+    })
+}
+
+/// Macintosh language codes used in name records
+fn mac_language(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0 => "en",
+        1 => "fr",
+        2 => "de",
+        3 => "it",
+        4 => "nl",
+        5 => "sv",
+        6 => "es",
+        7 => "da",
+        8 => "pt",
+        9 => "no",
+        10 => "he",
+        11 => "ja",
+        12 => "ar",
+        13 => "fi",
+        14 => "el",
+        15 => "is",
+        16 => "mt",
+        17 => "tr",
+        18 => "hr",
+        19 => "zh-Hant",
+        20 => "ur",
+        21 => "hi",
+        22 => "th",
+        23 => "ko",
+        24 => "lt",
+        25 => "pl",
+        26 => "hu",
+        27 => "et",
+        28 => "lv",
+        30 => "fo",
+        31 => "fa",
+        32 => "ru",
+        33 => "zh-Hans",
+        34 => "nl-BE",
+        35 => "ga",
+        36 => "sq",
+        37 => "ro",
+        38 => "cs",
+        39 => "sk",
+        40 => "sl",
+        42 => "sr",
+        43 => "mk",
+        44 => "bg",
+        45 => "uk",
+        46 => "be",
+        47 => "uz",
+        48 => "kk",
+        51 => "hy",
+        52 => "ka",
+        56 => "ky",
+        66 => "bn",
+        67 => "pa",
+        68 => "gu",
+        69 => "or",
+        70 => "ml",
+        71 => "kn",
+        72 => "ta",
+        73 => "te",
+        74 => "si",
+        77 => "my",
+        78 => "km",
+        79 => "lo",
+        80 => "vi",
+        81 => "id",
+        82 => "tl",
+        83 => "ms",
+        85 => "am",
+        128 => "cy",
+        129 => "eu",
+        130 => "ca",
+        131 => "la",
+        140 => "gl",
+        141 => "af",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    /// A name table with `(platform, encoding, language, name ID, bytes)`
+    /// records and, for version 1, language tags
+    fn name_table(records: &[(u16, u16, u16, u16, Vec<u8>)], lang_tags: &[&str]) -> Vec<u8> {
+        let version = !lang_tags.is_empty() as u16;
+        let header = 6 + records.len() * 12 + version as usize * (2 + lang_tags.len() * 4);
+        let mut storage = Vec::new();
+        let mut out = Vec::new();
+        for v in [version, records.len() as u16, header as u16] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        for (platform, encoding, language, name_id, bytes) in records {
+            for v in [*platform, *encoding, *language, *name_id] {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+            out.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+            storage.extend_from_slice(bytes);
+        }
+        if version == 1 {
+            out.extend_from_slice(&(lang_tags.len() as u16).to_be_bytes());
+            for tag in lang_tags {
+                let bytes = utf16(tag);
+                out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+                out.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+                storage.extend_from_slice(&bytes);
+            }
+        }
+        out.extend_from_slice(&storage);
+        out
+    }
+
+    #[test]
+    fn records_are_decoded_with_their_language() {
+        let data = name_table(
+            &[
+                (1, 0, 0, 1, b"Caf\x8E Sans".to_vec()), // Mac Roman é
+                (3, 1, 0x0409, 1, utf16("Café Sans")),
+                (3, 1, 0x0411, 1, utf16("カフェ")),
+                (3, 1, 0x1234, 4, utf16("Unknown LCID")),
+                (1, 1, 11, 1, vec![0x82, 0xA0]), // Mac Japanese is not decoded
+            ],
+            &[],
+        );
+        let names = parse(&data).unwrap();
+        let families = names.get(NameId::FAMILY);
+        assert_eq!(families.len(), 3);
+        assert_eq!(families[0].value, "Café Sans");
+        assert_eq!(families[0].language, "en");
+        assert_eq!(names.localized(NameId::FAMILY, "ja-jp"), Some("カフェ"));
+        assert_eq!(names.get(NameId::FULL_NAME)[0].language, "und-x-win-1234");
+    }
+
+    #[test]
+    fn version_1_language_tags() {
+        let data = name_table(
+            &[
+                (3, 1, 0x8000, 1, utf16("Sans")),
+                (3, 1, 0x8001, 1, utf16("Sans")),
+            ],
+            &["de-CH-1996"],
+        );
+        let names = parse(&data).unwrap();
+        let families = names.get(NameId::FAMILY);
+        assert_eq!(families[0].language, "de-CH-1996");
+        assert_eq!(families[1].language, "und-x-win-8001");
+    }
+
+    #[test]
+    fn records_outside_the_table_are_skipped() {
+        let mut data = name_table(&[(3, 1, 0x0409, 1, utf16("Sans"))], &[]);
+        data.truncate(data.len() - 2);
+        assert!(parse(&data).unwrap().is_empty());
+        assert!(parse(&data[..8]).is_err());
+    }
+
+    #[test]
+    fn best_prefers_windows_english() {
+        let data = name_table(
+            &[
+                (1, 0, 0, 1, b"Mac".to_vec()),
+                (0, 3, 0, 1, utf16("Unicode")),
+                (3, 1, 0x0407, 1, utf16("German")),
+                (3, 1, 0x0409, 1, utf16("")),
+                (3, 1, 0x0409, 16, utf16("Typographic")),
+                (1, 0, 0, 17, b"Condensed".to_vec()),
+            ],
+            &[],
+        );
+        let names = parse(&data).unwrap();
+        // The empty en-US record is ignored
+        assert_eq!(names.best(NameId::FAMILY), Some("German"));
+        assert_eq!(names.best(NameId::FULL_NAME), None);
+        // Legacy names, like the platform sources
+        assert_eq!(
+            names.family_and_style().unwrap(),
+            ("German".to_string(), "Regular".to_string())
+        );
+        assert_eq!(names.best(NameId::TYPOGRAPHIC_FAMILY), Some("Typographic"));
+    }
+
+    #[test]
+    fn family_and_style_need_a_family() {
+        let data = name_table(
+            &[
+                (3, 1, 0x0409, 2, utf16("Bold")),
+                (3, 1, 0x0409, 16, utf16("Typographic")),
+            ],
+            &[],
+        );
+        assert!(parse(&data).unwrap().family_and_style().is_err());
+        assert_eq!(NameId(300).to_string(), "Name 300");
+        assert_eq!(NameId::LICENSE_URL.to_string(), "License URL");
+    }
+}
@@ -0,0 +1,669 @@
+//! `OS/2` table - weight/width classes, style flags, embedding permissions,
+//! PANOSE and coverage ranges
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"OS/2");
+
+/// Decoded OS/2 table
+///
+/// Fields added in later table versions are `None` when the table is older.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Os2 {
+    pub version: u16,
+    pub avg_char_width: i16, // xAvgCharWidth: average advance width in design units
+    pub weight_class: u16,   // usWeightClass: 100-900
+    pub width_class: u16,    // usWidthClass: 1 (ultra-condensed) - 9 (ultra-expanded)
+    pub fs_type: FsType,     // Embedding permissions
+    pub family_class: FamilyClass, // sFamilyClass
+    pub panose: Panose,      // PANOSE classification
+    pub unicode_range: [u32; 4], // ulUnicodeRange1-4, bit 0 = LSB of the first word
+    pub vendor_id: Tag,      // achVendID, e.g. "MS  ", "ADBE"
+    pub fs_selection: FsSelection, // Style flags
+    pub typo_ascender: Option<i16>, // sTypoAscender (missing in some short version 0 tables)
+    pub typo_descender: Option<i16>, // sTypoDescender, negative below the baseline
+    pub typo_line_gap: Option<i16>, // sTypoLineGap
+    pub win_ascent: Option<u16>, // usWinAscent
+    pub win_descent: Option<u16>, // usWinDescent, positive below the baseline
+    pub code_page_range: Option<[u32; 2]>, // ulCodePageRange1-2 (version 1+)
+    pub x_height: Option<i16>, // sxHeight (version 2+)
+    pub cap_height: Option<i16>, // sCapHeight (version 2+)
+}
+
+impl Os2 {
+    /// Names of the Unicode ranges flagged in ulUnicodeRange
+    pub fn unicode_ranges(&self) -> Vec<&'static str> {
+        (0..128)
+            .filter(|&bit| self.unicode_range[bit / 32] & (1 << (bit % 32)) != 0)
+            .filter_map(|bit| UNICODE_RANGES.get(bit).copied())
+            .collect()
+    }
+
+    /// Names of the code pages flagged in ulCodePageRange
+    pub fn code_pages(&self) -> Vec<&'static str> {
+        let ranges = match self.code_page_range {
+            Some(ranges) => ranges,
+            None => return Vec::new(),
+        };
+        CODE_PAGES
+            .iter()
+            .filter(|(bit, _)| ranges[bit / 32] & (1 << (bit % 32)) != 0)
+            .map(|&(_, name)| name)
+            .collect()
+    }
+}
+
+// ============================================================================
+// FSSELECTION
+// ============================================================================
+
+/// The fsSelection style flags
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsSelection(pub u16);
+
+impl FsSelection {
+    pub const ITALIC: u16 = 1 << 0;
+    pub const UNDERSCORE: u16 = 1 << 1;
+    pub const NEGATIVE: u16 = 1 << 2;
+    pub const OUTLINED: u16 = 1 << 3;
+    pub const STRIKEOUT: u16 = 1 << 4;
+    pub const BOLD: u16 = 1 << 5;
+    pub const REGULAR: u16 = 1 << 6;
+    pub const USE_TYPO_METRICS: u16 = 1 << 7;
+    pub const WWS: u16 = 1 << 8;
+    pub const OBLIQUE: u16 = 1 << 9;
+
+    /// Flag names in bit order, for display
+    const NAMES: [&'static str; 10] = [
+        "ITALIC",
+        "UNDERSCORE",
+        "NEGATIVE",
+        "OUTLINED",
+        "STRIKEOUT",
+        "BOLD",
+        "REGULAR",
+        "USE_TYPO_METRICS",
+        "WWS",
+        "OBLIQUE",
+    ];
+
+    pub fn contains(self, flag: u16) -> bool {
+        self.0 & flag != 0
+    }
+
+    pub fn is_italic(self) -> bool {
+        self.contains(Self::ITALIC)
+    }
+
+    pub fn is_bold(self) -> bool {
+        self.contains(Self::BOLD)
+    }
+
+    pub fn is_regular(self) -> bool {
+        self.contains(Self::REGULAR)
+    }
+
+    pub fn is_oblique(self) -> bool {
+        self.contains(Self::OBLIQUE)
+    }
+
+    pub fn is_wws(self) -> bool {
+        self.contains(Self::WWS)
+    }
+
+    pub fn use_typo_metrics(self) -> bool {
+        self.contains(Self::USE_TYPO_METRICS)
+    }
+
+    /// Names of the flags that are set, e.g. `["BOLD", "ITALIC"]`
+    pub fn names(self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, &name)| name)
+            .collect()
+    }
+}
+
+// ============================================================================
+// FSTYPE
+// ============================================================================
+
+/// The fsType embedding permission flags
+///
+/// Bits 1-3 are the usage permission (none set = installable); older fonts
+/// may set several, in which case the least restrictive one applies. Bit 0
+/// is reserved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsType(pub u16);
+
+impl FsType {
+    pub const RESTRICTED: u16 = 1 << 1;
+    pub const PREVIEW_AND_PRINT: u16 = 1 << 2;
+    pub const EDITABLE: u16 = 1 << 3;
+    pub const NO_SUBSETTING: u16 = 1 << 8;
+    pub const BITMAP_ONLY: u16 = 1 << 9;
+
+    /// The usage permission bits
+    const PERMISSIONS: u16 = Self::RESTRICTED | Self::PREVIEW_AND_PRINT | Self::EDITABLE;
+
+    pub fn contains(self, flag: u16) -> bool {
+        self.0 & flag != 0
+    }
+
+    /// The usage permission, resolving combined bits to the least restrictive
+    pub fn permission(self) -> Embedding {
+        if self.0 & Self::PERMISSIONS == 0 {
+            Embedding::Installable
+        } else if self.contains(Self::EDITABLE) {
+            Embedding::Editable
+        } else if self.contains(Self::PREVIEW_AND_PRINT) {
+            Embedding::PreviewAndPrint
+        } else {
+            Embedding::Restricted
+        }
+    }
+
+    /// The font may not be subset before embedding
+    pub fn no_subsetting(self) -> bool {
+        self.contains(Self::NO_SUBSETTING)
+    }
+
+    /// Only the font's bitmaps may be embedded, not its outlines
+    pub fn bitmap_only(self) -> bool {
+        self.contains(Self::BITMAP_ONLY)
+    }
+}
+
+/// An fsType usage permission, from least to most restrictive
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Embedding {
+    Installable,     // May be embedded and permanently installed
+    Editable,        // May be embedded; documents may be edited
+    PreviewAndPrint, // May be embedded; documents are read-only
+    Restricted,      // Must not be embedded without the owner's permission
+}
+
+impl Embedding {
+    /// Every permission level, least restrictive first
+    pub const ALL: &'static [Embedding] = &[
+        Embedding::Installable,
+        Embedding::Editable,
+        Embedding::PreviewAndPrint,
+        Embedding::Restricted,
+    ];
+
+    /// Short lowercase name, as used in filters
+    pub fn name(self) -> &'static str {
+        match self {
+            Embedding::Installable => "installable",
+            Embedding::Editable => "editable",
+            Embedding::PreviewAndPrint => "print",
+            Embedding::Restricted => "restricted",
+        }
+    }
+
+    /// Looks up a permission by its short name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Embedding> {
+        Embedding::ALL
+            .iter()
+            .copied()
+            .find(|e| e.name().eq_ignore_ascii_case(name))
+    }
+
+    /// True if documents (e.g. PDFs) may carry the font
+    pub fn allows_embedding(self) -> bool {
+        self != Embedding::Restricted
+    }
+}
+
+impl fmt::Display for Embedding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Embedding::Installable => "Installable",
+            Embedding::Editable => "Editable",
+            Embedding::PreviewAndPrint => "Preview & Print",
+            Embedding::Restricted => "Restricted",
+        })
+    }
+}
+
+// ============================================================================
+// SFAMILYCLASS & PANOSE
+// ============================================================================
+
+/// IBM font family class (sFamilyClass): class in the high byte, subclass in the low
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FamilyClass(pub i16);
+
+impl FamilyClass {
+    pub fn class(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub fn subclass(self) -> u8 {
+        (self.0 & 0xFF) as u8
+    }
+
+    /// Name of the class, e.g. "Sans Serif"
+    pub fn class_name(self) -> &'static str {
+        match self.class() {
+            0 => "No Classification",
+            1 => "Oldstyle Serifs",
+            2 => "Transitional Serifs",
+            3 => "Modern Serifs",
+            4 => "Clarendon Serifs",
+            5 => "Slab Serifs",
+            7 => "Freeform Serifs",
+            8 => "Sans Serif",
+            9 => "Ornamentals",
+            10 => "Scripts",
+            12 => "Symbolic",
+            _ => "Reserved",
+        }
+    }
+}
+
+/// The 10-byte PANOSE classification
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Panose(pub [u8; 10]);
+
+impl Panose {
+    pub fn family_type(self) -> u8 {
+        self.0[0]
+    }
+
+    pub fn serif_style(self) -> u8 {
+        self.0[1]
+    }
+
+    pub fn weight(self) -> u8 {
+        self.0[2]
+    }
+
+    /// Proportion (Latin Text family) - 9 means monospaced
+    pub fn proportion(self) -> u8 {
+        self.0[3]
+    }
+
+    pub fn contrast(self) -> u8 {
+        self.0[4]
+    }
+
+    pub fn stroke_variation(self) -> u8 {
+        self.0[5]
+    }
+
+    pub fn arm_style(self) -> u8 {
+        self.0[6]
+    }
+
+    pub fn letterform(self) -> u8 {
+        self.0[7]
+    }
+
+    pub fn midline(self) -> u8 {
+        self.0[8]
+    }
+
+    pub fn x_height(self) -> u8 {
+        self.0[9]
+    }
+
+    /// Name of the family kind (first PANOSE digit)
+    pub fn family_name(self) -> &'static str {
+        match self.family_type() {
+            0 => "Any",
+            1 => "No Fit",
+            2 => "Latin Text",
+            3 => "Latin Hand Written",
+            4 => "Latin Decorative",
+            5 => "Latin Symbol",
+            _ => "Reserved",
+        }
+    }
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Parses an OS/2 table of any version
+pub(crate) fn parse(data: &[u8]) -> Result<Os2> {
+    let mut r = Reader::new(data);
+    let version = r.u16()?;
+    let avg_char_width = r.i16()?;
+    let weight_class = r.u16()?;
+    let width_class = r.u16()?;
+    let fs_type = FsType(r.u16()?);
+    r.skip(20)?; // subscript/superscript sizes and offsets, strikeout size/position
+    let family_class = FamilyClass(r.i16()?);
+
+    let mut panose = [0u8; 10];
+    panose.copy_from_slice(r.bytes(10)?);
+
+    let mut unicode_range = [0u32; 4];
+    for range in &mut unicode_range {
+        *range = r.u32()?;
+    }
+    let vendor_id = r.tag()?;
+    let fs_selection = FsSelection(r.u16()?);
+    let _first_char_index = r.u16()?;
+    let _last_char_index = r.u16()?;
+
+    // Version 0 tables may end here or after the typo/win metrics
+    let mut typo = None;
+    if version >= 1 || r.remaining() >= 10 {
+        typo = Some((r.i16()?, r.i16()?, r.i16()?, r.u16()?, r.u16()?));
+    }
+    let code_page_range = if version >= 1 {
+        Some([r.u32()?, r.u32()?])
+    } else {
+        None
+    };
+    let (x_height, cap_height) = if version >= 2 {
+        (Some(r.i16()?), Some(r.i16()?))
+    } else {
+        (None, None)
+    };
+
+    Ok(Os2 {
+        version,
+        avg_char_width,
+        weight_class,
+        width_class,
+        fs_type,
+        family_class,
+        panose: Panose(panose),
+        unicode_range,
+        vendor_id,
+        fs_selection,
+        typo_ascender: typo.map(|t| t.0),
+        typo_descender: typo.map(|t| t.1),
+        typo_line_gap: typo.map(|t| t.2),
+        win_ascent: typo.map(|t| t.3),
+        win_descent: typo.map(|t| t.4),
+        code_page_range,
+        x_height,
+        cap_height,
+    })
+}
+
+// ============================================================================
+// RANGE NAMES
+// ============================================================================
+
+/// ulUnicodeRange bit names (bits 0-122; 123-127 are reserved)
+const UNICODE_RANGES: [&str; 123] = [
+    "Basic Latin",
+    "Latin-1 Supplement",
+    "Latin Extended-A",
+    "Latin Extended-B",
+    "IPA Extensions",
+    "Spacing Modifier Letters",
+    "Combining Diacritical Marks",
+    "Greek and Coptic",
+    "Coptic",
+    "Cyrillic",
+    "Armenian",
+    "Hebrew",
+    "Vai",
+    "Arabic",
+    "NKo",
+    "Devanagari",
+    "Bengali",
+    "Gurmukhi",
+    "Gujarati",
+    "Oriya",
+    "Tamil",
+    "Telugu",
+    "Kannada",
+    "Malayalam",
+    "Thai",
+    "Lao",
+    "Georgian",
+    "Balinese",
+    "Hangul Jamo",
+    "Latin Extended Additional",
+    "Greek Extended",
+    "General Punctuation",
+    "Superscripts And Subscripts",
+    "Currency Symbols",
+    "Combining Diacritical Marks For Symbols",
+    "Letterlike Symbols",
+    "Number Forms",
+    "Arrows",
+    "Mathematical Operators",
+    "Miscellaneous Technical",
+    "Control Pictures",
+    "Optical Character Recognition",
+    "Enclosed Alphanumerics",
+    "Box Drawing",
+    "Block Elements",
+    "Geometric Shapes",
+    "Miscellaneous Symbols",
+    "Dingbats",
+    "CJK Symbols And Punctuation",
+    "Hiragana",
+    "Katakana",
+    "Bopomofo",
+    "Hangul Compatibility Jamo",
+    "Phags-pa",
+    "Enclosed CJK Letters And Months",
+    "CJK Compatibility",
+    "Hangul Syllables",
+    "Non-Plane 0",
+    "Phoenician",
+    "CJK Unified Ideographs",
+    "Private Use Area (plane 0)",
+    "CJK Strokes",
+    "Alphabetic Presentation Forms",
+    "Arabic Presentation Forms-A",
+    "Combining Half Marks",
+    "Vertical Forms",
+    "Small Form Variants",
+    "Arabic Presentation Forms-B",
+    "Halfwidth And Fullwidth Forms",
+    "Specials",
+    "Tibetan",
+    "Syriac",
+    "Thaana",
+    "Sinhala",
+    "Myanmar",
+    "Ethiopic",
+    "Cherokee",
+    "Unified Canadian Aboriginal Syllabics",
+    "Ogham",
+    "Runic",
+    "Khmer",
+    "Mongolian",
+    "Braille Patterns",
+    "Yi Syllables",
+    "Tagalog",
+    "Old Italic",
+    "Gothic",
+    "Deseret",
+    "Byzantine Musical Symbols",
+    "Mathematical Alphanumeric Symbols",
+    "Private Use (plane 15)",
+    "Variation Selectors",
+    "Tags",
+    "Limbu",
+    "Tai Le",
+    "New Tai Lue",
+    "Buginese",
+    "Glagolitic",
+    "Tifinagh",
+    "Yijing Hexagram Symbols",
+    "Syloti Nagri",
+    "Linear B Syllabary",
+    "Ancient Greek Numbers",
+    "Ugaritic",
+    "Old Persian",
+    "Shavian",
+    "Osmanya",
+    "Cypriot Syllabary",
+    "Kharoshthi",
+    "Tai Xuan Jing Symbols",
+    "Cuneiform",
+    "Counting Rod Numerals",
+    "Sundanese",
+    "Lepcha",
+    "Ol Chiki",
+    "Saurashtra",
+    "Kayah Li",
+    "Rejang",
+    "Cham",
+    "Ancient Symbols",
+    "Phaistos Disc",
+    "Carian",
+    "Domino Tiles",
+];
+
+/// ulCodePageRange bits that are assigned, with their code page names
+const CODE_PAGES: [(usize, &str); 34] = [
+    (0, "1252 Latin 1"),
+    (1, "1250 Latin 2"),
+    (2, "1251 Cyrillic"),
+    (3, "1253 Greek"),
+    (4, "1254 Turkish"),
+    (5, "1255 Hebrew"),
+    (6, "1256 Arabic"),
+    (7, "1257 Baltic"),
+    (8, "1258 Vietnamese"),
+    (16, "874 Thai"),
+    (17, "932 Japanese"),
+    (18, "936 Simplified Chinese"),
+    (19, "949 Korean Wansung"),
+    (20, "950 Traditional Chinese"),
+    (21, "1361 Korean Johab"),
+    (29, "Macintosh Roman"),
+    (30, "OEM Character Set"),
+    (31, "Symbol Character Set"),
+    (48, "869 IBM Greek"),
+    (49, "866 MS-DOS Russian"),
+    (50, "865 MS-DOS Nordic"),
+    (51, "864 Arabic"),
+    (52, "863 MS-DOS Canadian French"),
+    (53, "862 Hebrew"),
+    (54, "861 MS-DOS Icelandic"),
+    (55, "860 MS-DOS Portuguese"),
+    (56, "857 IBM Turkish"),
+    (57, "855 IBM Cyrillic"),
+    (58, "852 Latin 2"),
+    (59, "775 MS-DOS Baltic"),
+    (60, "737 Greek"),
+    (61, "708 Arabic ASMO 708"),
+    (62, "850 WE/Latin 1"),
+    (63, "437 US"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 5 OS/2 table relabelled as `version` and cut to `len` bytes
+    fn os2(version: u16, len: usize) -> Vec<u8> {
+        let mut out = vec![0u8; 100];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            out[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(0, &version.to_be_bytes());
+        put(2, &500i16.to_be_bytes());
+        put(4, &700u16.to_be_bytes());
+        put(6, &3u16.to_be_bytes());
+        put(8, &0x0104u16.to_be_bytes()); // Preview & print, no subsetting
+        put(30, &0x0805i16.to_be_bytes());
+        put(32, &[2, 11, 6, 9, 3, 0, 0, 0, 0, 4]);
+        put(42, &(1u32 | 1 << 9).to_be_bytes()); // Basic Latin, Cyrillic
+        put(58, b"TEST");
+        put(62, &0x0021u16.to_be_bytes()); // ITALIC | BOLD
+        put(68, &800i16.to_be_bytes());
+        put(70, &(-200i16).to_be_bytes());
+        put(72, &100i16.to_be_bytes());
+        put(74, &900u16.to_be_bytes());
+        put(76, &300u16.to_be_bytes());
+        put(78, &(1u32 | 1 << 2).to_be_bytes()); // Latin 1, Cyrillic
+        put(86, &480i16.to_be_bytes());
+        put(88, &690i16.to_be_bytes());
+        out.truncate(len);
+        out
+    }
+
+    #[test]
+    fn version_0_tables_of_either_length() {
+        let short = parse(&os2(0, 68)).unwrap();
+        assert_eq!(short.weight_class, 700);
+        assert_eq!(short.width_class, 3);
+        assert_eq!(short.vendor_id, Tag::new(b"TEST"));
+        assert_eq!(short.typo_ascender, None);
+        assert_eq!(short.win_ascent, None);
+        assert_eq!(short.code_page_range, None);
+
+        let long = parse(&os2(0, 78)).unwrap();
+        assert_eq!(long.typo_ascender, Some(800));
+        assert_eq!(long.typo_descender, Some(-200));
+        assert_eq!(long.win_descent, Some(300));
+        assert_eq!(long.code_page_range, None);
+        assert_eq!(long.x_height, None);
+    }
+
+    #[test]
+    fn later_versions_add_fields() {
+        let v1 = parse(&os2(1, 86)).unwrap();
+        assert_eq!(v1.code_page_range, Some([5, 0]));
+        assert_eq!(v1.code_pages(), ["1252 Latin 1", "1251 Cyrillic"]);
+        assert_eq!(v1.x_height, None);
+
+        for version in 2..=5 {
+            let len = if version == 5 { 100 } else { 96 };
+            let os2 = parse(&os2(version, len)).unwrap();
+            assert_eq!(os2.version, version);
+            assert_eq!(os2.x_height, Some(480));
+            assert_eq!(os2.cap_height, Some(690));
+        }
+    }
+
+    #[test]
+    fn truncated_tables_are_errors() {
+        assert!(parse(&os2(0, 60)).is_err());
+        assert!(parse(&os2(1, 80)).is_err());
+        assert!(parse(&os2(2, 88)).is_err());
+    }
+
+    #[test]
+    fn flags_and_classifications() {
+        let os2 = parse(&os2(4, 96)).unwrap();
+        assert_eq!(os2.avg_char_width, 500);
+        assert!(os2.fs_selection.is_italic() && os2.fs_selection.is_bold());
+        assert_eq!(os2.fs_selection.names(), ["ITALIC", "BOLD"]);
+        assert_eq!(os2.fs_type.permission(), Embedding::PreviewAndPrint);
+        assert!(os2.fs_type.no_subsetting());
+        assert_eq!(os2.family_class.class(), 8);
+        assert_eq!(os2.family_class.subclass(), 5);
+        assert_eq!(os2.panose.family_type(), 2);
+        assert_eq!(os2.panose.proportion(), 9);
+        assert_eq!(os2.unicode_ranges(), ["Basic Latin", "Cyrillic"]);
+    }
+
+    #[test]
+    fn least_restrictive_permission_wins() {
+        assert_eq!(FsType(0).permission(), Embedding::Installable);
+        assert_eq!(FsType(0x000A).permission(), Embedding::Editable);
+        assert_eq!(FsType(0x0002).permission(), Embedding::Restricted);
+        // Bit 0 is reserved
+        assert_eq!(FsType(0x0001).permission(), Embedding::Installable);
+        assert_eq!(FsType(0x0003).permission(), Embedding::Restricted);
+        assert_eq!(
+            Embedding::from_name("PRINT"),
+            Some(Embedding::PreviewAndPrint)
+        );
+    }
+}
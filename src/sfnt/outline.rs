@@ -0,0 +1,178 @@
+//! Outline technology of a face, detected from its table directory
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+
+pub(crate) const GLYF: Tag = Tag::new(b"glyf");
+pub(crate) const LOCA: Tag = Tag::new(b"loca");
+pub(crate) const CFF: Tag = Tag::new(b"CFF ");
+pub(crate) const CFF2: Tag = Tag::new(b"CFF2");
+pub(crate) const EBDT: Tag = Tag::new(b"EBDT");
+pub(crate) const CBDT: Tag = Tag::new(b"CBDT");
+pub(crate) const SBIX: Tag = Tag::new(b"sbix");
+pub(crate) const BDAT: Tag = Tag::new(b"bdat");
+
+/// Offset of indexToLocFormat (0 for short loca offsets) in head
+pub(crate) const INDEX_TO_LOC_FORMAT: usize = 50;
+
+/// How a font's glyphs are described
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OutlineFormat {
+    TrueType, // Quadratic outlines in glyf/loca
+    Cff,      // Cubic outlines in a CFF table (PostScript flavored OpenType)
+    Cff2,     // Cubic outlines in CFF2, usually variable
+    Bitmap,   // No outlines, only EBDT, CBDT, sbix or bdat bitmaps
+}
+
+impl OutlineFormat {
+    /// Every outline format
+    pub const ALL: &'static [OutlineFormat] = &[
+        OutlineFormat::TrueType,
+        OutlineFormat::Cff,
+        OutlineFormat::Cff2,
+        OutlineFormat::Bitmap,
+    ];
+
+    /// Short lowercase name, as used in filters
+    pub fn name(self) -> &'static str {
+        match self {
+            OutlineFormat::TrueType => "truetype",
+            OutlineFormat::Cff => "cff",
+            OutlineFormat::Cff2 => "cff2",
+            OutlineFormat::Bitmap => "bitmap",
+        }
+    }
+
+    /// Looks up a format by its short name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<OutlineFormat> {
+        OutlineFormat::ALL
+            .iter()
+            .copied()
+            .find(|o| o.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Detects the outline format from which tables a face has
+    ///
+    /// Color bitmap fonts often carry a glyf table of empty glyphs as well,
+    /// so glyf only counts when `glyf_outlines` (see `has_glyf_outlines`)
+    /// says it holds outlines; otherwise the bitmaps win. None if the face
+    /// has neither outline nor bitmap tables.
+    pub(crate) fn detect(
+        has: impl Fn(Tag) -> bool,
+        glyf_outlines: impl FnOnce() -> bool,
+    ) -> Option<OutlineFormat> {
+        let has_glyf = has(GLYF);
+        if has_glyf && glyf_outlines() {
+            Some(OutlineFormat::TrueType)
+        } else if has(CFF2) {
+            Some(OutlineFormat::Cff2)
+        } else if has(CFF) {
+            Some(OutlineFormat::Cff)
+        } else if [EBDT, CBDT, SBIX, BDAT].into_iter().any(has) {
+            Some(OutlineFormat::Bitmap)
+        } else if has_glyf {
+            Some(OutlineFormat::TrueType)
+        } else {
+            None
+        }
+    }
+}
+
+/// True if loca gives any glyph after .notdef data in glyf
+///
+/// Glyphs without outlines have no data, so a glyf table whose only
+/// outline is the .notdef box is a placeholder. Without head or loca the
+/// glyf table is taken at its word.
+pub(crate) fn has_glyf_outlines(head: Option<&[u8]>, loca: Option<&[u8]>) -> bool {
+    let (Some(head), Some(loca)) = (head, loca) else {
+        return true;
+    };
+    let Ok(format) = Reader::at(head, INDEX_TO_LOC_FORMAT).and_then(|mut r| r.i16()) else {
+        return true;
+    };
+    let entry_size = if format != 0 { 4 } else { 2 };
+    let offset = |i: usize| {
+        let mut r = Reader::at(loca, i * entry_size).ok()?;
+        match entry_size {
+            4 => r.u32().ok(),
+            _ => r.u16().ok().map(|o| o as u32 * 2),
+        }
+    };
+    // numGlyphs + 1 entries: the end of .notdef and the end of the last glyph
+    let entries = loca.len() / entry_size;
+    match (offset(1), entries.checked_sub(1).and_then(offset)) {
+        (Some(notdef_end), Some(end)) => entries > 2 && end > notdef_end,
+        _ => false,
+    }
+}
+
+impl fmt::Display for OutlineFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutlineFormat::TrueType => "TrueType",
+            OutlineFormat::Cff => "CFF",
+            OutlineFormat::Cff2 => "CFF2",
+            OutlineFormat::Bitmap => "Bitmap",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(tables: &[Tag], glyf_outlines: bool) -> Option<OutlineFormat> {
+        OutlineFormat::detect(|tag| tables.contains(&tag), || glyf_outlines)
+    }
+
+    #[test]
+    fn outlines_win_over_bitmaps() {
+        assert_eq!(detect(&[GLYF, CBDT], true), Some(OutlineFormat::TrueType));
+        assert_eq!(detect(&[CFF, CFF2], false), Some(OutlineFormat::Cff2));
+        assert_eq!(detect(&[CFF], false), Some(OutlineFormat::Cff));
+        assert_eq!(detect(&[SBIX], false), Some(OutlineFormat::Bitmap));
+        assert_eq!(detect(&[BDAT], false), Some(OutlineFormat::Bitmap));
+        assert_eq!(detect(&[Tag::new(b"name")], true), None);
+    }
+
+    #[test]
+    fn placeholder_glyf_tables_do_not_count() {
+        assert_eq!(detect(&[GLYF, CBDT], false), Some(OutlineFormat::Bitmap));
+        assert_eq!(detect(&[GLYF, SBIX], false), Some(OutlineFormat::Bitmap));
+        // A font of empty glyphs is still a TrueType font
+        assert_eq!(detect(&[GLYF], false), Some(OutlineFormat::TrueType));
+
+        let mut head = vec![0u8; 54];
+        let short =
+            |offsets: &[u16]| -> Vec<u8> { offsets.iter().flat_map(|o| o.to_be_bytes()).collect() };
+        // Only .notdef has data, or no glyph at all does
+        assert!(!has_glyf_outlines(Some(&head), Some(&short(&[0, 8, 8, 8]))));
+        assert!(!has_glyf_outlines(Some(&head), Some(&short(&[0, 0, 0]))));
+        assert!(!has_glyf_outlines(Some(&head), Some(&short(&[0, 8]))));
+        assert!(has_glyf_outlines(Some(&head), Some(&short(&[0, 8, 8, 20]))));
+        head[51] = 1; // long offsets
+        let long: Vec<u8> = [0u32, 16, 40]
+            .iter()
+            .flat_map(|o| o.to_be_bytes())
+            .collect();
+        assert!(has_glyf_outlines(Some(&head), Some(&long)));
+        assert!(has_glyf_outlines(None, Some(&long)));
+        assert!(has_glyf_outlines(Some(&head), None));
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for &format in OutlineFormat::ALL {
+            assert_eq!(OutlineFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(
+            OutlineFormat::from_name("TrueType"),
+            Some(OutlineFormat::TrueType)
+        );
+        assert_eq!(OutlineFormat::from_name("type1"), None);
+    }
+}
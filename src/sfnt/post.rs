@@ -0,0 +1,31 @@
+//! `post` table - PostScript information
+
+use super::reader::Reader;
+use super::Tag;
+use crate::Result;
+
+pub(crate) const TAG: Tag = Tag::new(b"post");
+
+/// The subset of the post table used by `FontInfo`
+pub(crate) struct Post {
+    pub italic_angle: f32, // Degrees counter-clockwise from vertical
+    pub underline_position: i16,
+    pub underline_thickness: i16,
+    pub is_fixed_pitch: bool,
+}
+
+/// Parses the post table header
+pub(crate) fn parse(data: &[u8]) -> Result<Post> {
+    let mut r = Reader::at(data, 4)?; // version
+    let italic_angle = r.fixed()?;
+    let underline_position = r.i16()?;
+    let underline_thickness = r.i16()?;
+    let is_fixed_pitch = r.u32()? != 0;
+
+    Ok(Post {
+        italic_angle,
+        underline_position,
+        underline_thickness,
+        is_fixed_pitch,
+    })
+}
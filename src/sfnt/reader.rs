@@ -0,0 +1,100 @@
+//! Big-endian cursor over font table data
+
+use crate::sfnt::Tag;
+use crate::{Error, Result};
+
+/// A bounds-checked big-endian reader
+///
+/// Every read advances the cursor; reading past the end of the slice
+/// returns `Error::Parse` instead of panicking.
+#[derive(Clone, Copy)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader positioned at the start of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    /// Creates a reader positioned at `offset` within `data`
+    pub fn at(data: &'a [u8], offset: usize) -> Result<Self> {
+        if offset > data.len() {
+            return Err(Error::Parse(format!(
+                "offset {} is outside of {} bytes of data",
+                offset,
+                data.len()
+            )));
+        }
+        Ok(Reader { data, pos: offset })
+    }
+
+    /// Number of bytes left after the cursor
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Skips `len` bytes
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    /// Reads `len` raw bytes
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len());
+        match end {
+            Some(end) => {
+                let bytes = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(bytes)
+            }
+            None => Err(Error::Parse(format!(
+                "unexpected end of data reading {} bytes at offset {}",
+                len, self.pos
+            ))),
+        }
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub fn i16(&mut self) -> Result<i16> {
+        Ok(self.u16()? as i16)
+    }
+
+    pub fn u24(&mut self) -> Result<u32> {
+        let b = self.bytes(3)?;
+        Ok(u32::from_be_bytes([0, b[0], b[1], b[2]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn i32(&mut self) -> Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    /// Reads a 16.16 fixed-point number
+    pub fn fixed(&mut self) -> Result<f32> {
+        Ok(self.i32()? as f32 / 65536.0)
+    }
+
+    /// Reads a 4-byte table or axis tag
+    pub fn tag(&mut self) -> Result<Tag> {
+        let b = self.bytes(4)?;
+        Ok(Tag([b[0], b[1], b[2], b[3]]))
+    }
+}
@@ -0,0 +1,531 @@
+//! Structural checks of a face's table directory and core tables
+
+use super::outline::{self, OutlineFormat, LOCA};
+use super::reader::Reader;
+use super::woff::{checksum, table_checksum};
+use super::{decode_container, face_offsets, head, is_collection, maxp, read_face, Face, Tag};
+use crate::validate::{Check, Finding, FontReport, Severity};
+use crate::{Container, FontInfo};
+
+/// head.magicNumber
+const HEAD_MAGIC: u32 = 0x5F0F_3CF5;
+
+/// The whole-file checksum that checkSumAdjustment makes the file sum to
+const CHECKSUM_MAGIC: u32 = 0xB1B0_AFBA;
+
+/// Tables every outline font needs (OS/2 is checked separately)
+const REQUIRED_TABLES: &[&[u8; 4]] = &[
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"post",
+];
+
+/// Validates every face in a file, see `crate::validate_font_data`
+///
+/// Each report comes with the face's `FontInfo` when it could be read, for
+/// the style-linking checks.
+pub(crate) fn validate_faces(data: &[u8], file_path: &str) -> Vec<(FontReport, Option<FontInfo>)> {
+    let report = |face_index, findings| FontReport {
+        file_path: file_path.to_string(),
+        face_index,
+        name: None,
+        findings,
+    };
+    let decode_error = |e: crate::Error| {
+        vec![Finding::new(
+            Severity::Error,
+            Check::Decode,
+            None,
+            e.to_string(),
+        )]
+    };
+
+    let (data, container) = match decode_container(data) {
+        Ok(decoded) => decoded,
+        Err(e) => return vec![(report(None, decode_error(e)), None)],
+    };
+    let offsets = match face_offsets(&data) {
+        Ok(offsets) => offsets,
+        Err(e) => return vec![(report(None, decode_error(e)), None)],
+    };
+
+    let collection = is_collection(&data);
+    offsets
+        .into_iter()
+        .enumerate()
+        .map(|(index, offset)| {
+            let face_index = collection.then_some(index as u32);
+            let face = match Face::parse(&data, offset) {
+                Ok(face) => face,
+                Err(e) => return (report(face_index, decode_error(e)), None),
+            };
+
+            let mut findings = Vec::new();
+            check_directory(&face, &mut findings);
+            check_checksums(&face, container, collection, &mut findings);
+            check_required_tables(&face, &mut findings);
+            check_loca(&face, &mut findings);
+
+            match read_face(&face, file_path) {
+                Ok(mut font) => {
+                    font.face_index = face_index;
+                    font.container = container;
+                    let name = Some(format!("{} {}", font.family_name, font.style_name));
+                    (
+                        FontReport {
+                            name,
+                            ..report(face_index, findings)
+                        },
+                        Some(font),
+                    )
+                }
+                Err(e) => {
+                    findings.extend(decode_error(e));
+                    (report(face_index, findings), None)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Bounds, alignment, ordering, duplicates and overlaps of the table directory
+fn check_directory(face: &Face, findings: &mut Vec<Finding>) {
+    for (i, record) in face.tables.iter().enumerate() {
+        let end = record.offset as u64 + record.length as u64;
+        if end > face.data.len() as u64 {
+            findings.push(Finding::new(
+                Severity::Error,
+                Check::TableBounds,
+                Some(record.tag),
+                format!(
+                    "ends at byte {}, past the end of the {}-byte file",
+                    end,
+                    face.data.len()
+                ),
+            ));
+        }
+        if record.offset % 4 != 0 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Check::TableAlignment,
+                Some(record.tag),
+                format!("offset {} is not a multiple of 4", record.offset),
+            ));
+        }
+        if face.tables[..i].iter().any(|t| t.tag == record.tag) {
+            findings.push(Finding::new(
+                Severity::Error,
+                Check::DuplicateTable,
+                Some(record.tag),
+                "listed more than once".to_string(),
+            ));
+        } else if let Some(previous) = i.checked_sub(1).map(|p| &face.tables[p]) {
+            if previous.tag > record.tag {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    Check::TableOrder,
+                    Some(record.tag),
+                    format!(
+                        "listed after '{}'; the directory must be sorted by tag",
+                        previous.tag
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut by_offset: Vec<_> = face.tables.iter().filter(|t| t.length > 0).collect();
+    by_offset.sort_by_key(|t| t.offset);
+    for pair in by_offset.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if a.offset as u64 + a.length as u64 > b.offset as u64 {
+            findings.push(Finding::new(
+                Severity::Error,
+                Check::TableOverlap,
+                Some(b.tag),
+                format!("starts at byte {}, inside '{}'", b.offset, a.tag),
+            ));
+        }
+    }
+}
+
+/// Table checksums, head.magicNumber and head.checkSumAdjustment
+///
+/// checkSumAdjustment covers the original file, so it can only be checked
+/// for plain single-face sfnt files; WOFF/WOFF2 decoding and collections
+/// lay the tables out differently.
+fn check_checksums(
+    face: &Face,
+    container: Container,
+    collection: bool,
+    findings: &mut Vec<Finding>,
+) {
+    for record in &face.tables {
+        let Some(table) = face.table(record.tag) else {
+            continue;
+        };
+        let actual = table_checksum(record.tag, table);
+        if actual != record.checksum {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Check::TableChecksum,
+                Some(record.tag),
+                format!(
+                    "directory says 0x{:08X}, data sums to 0x{:08X}",
+                    record.checksum, actual
+                ),
+            ));
+        }
+    }
+
+    let Some(head) = face.table(head::TAG) else {
+        return;
+    };
+    let fields = Reader::at(head, 8).and_then(|mut r| Ok((r.u32()?, r.u32()?)));
+    let Ok((adjustment, magic)) = fields else {
+        findings.push(Finding::new(
+            Severity::Error,
+            Check::TableBounds,
+            Some(head::TAG),
+            format!("only {} bytes long", head.len()),
+        ));
+        return;
+    };
+
+    if magic != HEAD_MAGIC {
+        findings.push(Finding::new(
+            Severity::Error,
+            Check::HeadMagic,
+            Some(head::TAG),
+            format!("magicNumber is 0x{:08X}", magic),
+        ));
+    }
+
+    if container != Container::Sfnt || collection {
+        let why = if collection {
+            "a collection member"
+        } else {
+            "a decoded web font"
+        };
+        findings.push(Finding::new(
+            Severity::Info,
+            Check::ChecksumAdjustment,
+            Some(head::TAG),
+            format!("not checked for {}", why),
+        ));
+        return;
+    }
+    let expected = CHECKSUM_MAGIC.wrapping_sub(checksum(face.data).wrapping_sub(adjustment));
+    if adjustment != expected {
+        findings.push(Finding::new(
+            Severity::Warning,
+            Check::ChecksumAdjustment,
+            Some(head::TAG),
+            format!("is 0x{:08X}, expected 0x{:08X}", adjustment, expected),
+        ));
+    }
+}
+
+/// The tables every font needs plus those of its outline format
+fn check_required_tables(face: &Face, findings: &mut Vec<Finding>) {
+    let mut missing = |severity, tag: Tag, why: &str| {
+        findings.push(Finding::new(
+            severity,
+            Check::MissingTable,
+            Some(tag),
+            why.to_string(),
+        ));
+    };
+
+    for &tag in REQUIRED_TABLES {
+        let tag = Tag::new(tag);
+        if !face.has_table(tag) {
+            missing(Severity::Error, tag, "required in every font");
+        }
+    }
+    if !face.has_table(Tag::new(b"OS/2")) {
+        missing(Severity::Warning, Tag::new(b"OS/2"), "required on Windows");
+    }
+
+    match face.outline_format() {
+        Some(OutlineFormat::TrueType) if !face.has_table(LOCA) => {
+            missing(Severity::Error, LOCA, "required with glyf outlines");
+        }
+        Some(OutlineFormat::Bitmap) => {
+            for (data, index) in [(outline::EBDT, b"EBLC"), (outline::CBDT, b"CBLC")] {
+                if face.has_table(data) && !face.has_table(Tag::new(index)) {
+                    missing(
+                        Severity::Error,
+                        Tag::new(index),
+                        &format!("required with '{}' bitmaps", data),
+                    );
+                }
+            }
+        }
+        Some(_) => {}
+        None => findings.push(Finding::new(
+            Severity::Error,
+            Check::MissingTable,
+            None,
+            "no glyf, CFF, CFF2 or bitmap glyph table".to_string(),
+        )),
+    }
+}
+
+/// loca must have numGlyphs + 1 ascending offsets that stay inside glyf
+fn check_loca(face: &Face, findings: &mut Vec<Finding>) {
+    let (Some(loca), Some(glyf), Some(head), Some(maxp)) = (
+        face.table(LOCA),
+        face.table(outline::GLYF),
+        face.table(head::TAG),
+        face.table(maxp::TAG),
+    ) else {
+        return;
+    };
+    let (Ok(long_offsets), Ok(num_glyphs)) = (
+        Reader::at(head, outline::INDEX_TO_LOC_FORMAT)
+            .and_then(|mut r| r.i16())
+            .map(|format| format != 0),
+        maxp::parse_num_glyphs(maxp),
+    ) else {
+        return;
+    };
+
+    let mut problem = |severity, message: String| {
+        findings.push(Finding::new(severity, Check::Loca, Some(LOCA), message));
+    };
+
+    let entry_size = if long_offsets { 4 } else { 2 };
+    let expected = (num_glyphs as usize + 1) * entry_size;
+    if loca.len() < expected {
+        problem(
+            Severity::Error,
+            format!(
+                "{} bytes, {} glyphs need {}",
+                loca.len(),
+                num_glyphs,
+                expected
+            ),
+        );
+        return;
+    }
+    if loca.len() > expected {
+        problem(
+            Severity::Warning,
+            format!(
+                "{} bytes, {} more than {} glyphs need",
+                loca.len(),
+                loca.len() - expected,
+                num_glyphs
+            ),
+        );
+    }
+
+    let mut r = Reader::new(loca);
+    let mut previous = 0;
+    for glyph in 0..=num_glyphs as usize {
+        let offset = if long_offsets {
+            r.u32()
+        } else {
+            r.u16().map(|o| o as u32 * 2)
+        };
+        let offset = offset.unwrap_or(previous as u32) as usize;
+        if offset < previous {
+            problem(
+                Severity::Error,
+                format!("offset of glyph {} goes backwards", glyph),
+            );
+            return;
+        }
+        if offset > glyf.len() {
+            problem(
+                Severity::Error,
+                format!(
+                    "glyph {} starts at {}, past the {}-byte glyf table",
+                    glyph,
+                    offset,
+                    glyf.len()
+                ),
+            );
+            return;
+        }
+        previous = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{name_table, sfnt};
+    use super::*;
+
+    fn head(index_to_loc_format: i16) -> Vec<u8> {
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&HEAD_MAGIC.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[50..52].copy_from_slice(&index_to_loc_format.to_be_bytes());
+        head
+    }
+
+    fn hhea() -> Vec<u8> {
+        let mut hhea = vec![0u8; 36];
+        hhea[34..36].copy_from_slice(&1u16.to_be_bytes());
+        hhea
+    }
+
+    /// Every table of a two-glyph TrueType font, sorted by tag
+    fn tables(loca: &[u16]) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let mut os2 = vec![0u8; 78];
+        os2[4..6].copy_from_slice(&400u16.to_be_bytes());
+        vec![
+            (b"OS/2", os2),
+            (b"cmap", vec![0, 0, 0, 0]),
+            (b"glyf", vec![0; 8]),
+            (b"head", head(0)),
+            (b"hhea", hhea()),
+            (b"hmtx", vec![0, 100, 0, 0, 0, 0]),
+            (b"loca", loca.iter().flat_map(|o| o.to_be_bytes()).collect()),
+            (b"maxp", vec![0, 0, 0x50, 0, 0, 2]),
+            (b"name", name_table("Test Sans")),
+            (
+                b"post",
+                vec![0, 3, 0, 0].into_iter().chain([0; 28]).collect(),
+            ),
+        ]
+    }
+
+    /// An sfnt with a correct head.checkSumAdjustment
+    fn font(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = sfnt(tables);
+        let head = Face::parse(&data, 0)
+            .unwrap()
+            .tables
+            .iter()
+            .find(|t| t.tag == head::TAG)
+            .map(|t| t.offset as usize);
+        if let Some(head) = head {
+            let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&data));
+            data[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+        }
+        data
+    }
+
+    fn findings(data: &[u8]) -> Vec<(Severity, Check, Option<Tag>)> {
+        validate_faces(data, "test.ttf")
+            .into_iter()
+            .flat_map(|(report, _)| report.findings)
+            .map(|f| (f.severity, f.check, f.table))
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_font_has_no_findings() {
+        let faces = validate_faces(&font(&tables(&[0, 2, 4])), "test.ttf");
+        assert_eq!(faces.len(), 1);
+        assert_eq!(faces[0].0.findings, []);
+        assert_eq!(faces[0].0.name.as_deref(), Some("Test Sans Regular"));
+        assert!(faces[0].1.is_some());
+    }
+
+    #[test]
+    fn undecodable_files() {
+        assert_eq!(findings(b"wOF2"), [(Severity::Error, Check::Decode, None)]);
+        assert_eq!(findings(&[0; 8]), [(Severity::Error, Check::Decode, None)]);
+    }
+
+    #[test]
+    fn changed_data_breaks_the_checksums() {
+        let mut data = font(&tables(&[0, 2, 4]));
+        let glyf = Face::parse(&data, 0).unwrap().tables[2].offset as usize;
+        data[glyf] = 1;
+        assert_eq!(
+            findings(&data),
+            [
+                (Severity::Warning, Check::TableChecksum, Some(outline::GLYF)),
+                (
+                    Severity::Warning,
+                    Check::ChecksumAdjustment,
+                    Some(head::TAG)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn directory_problems() {
+        let mut data = font(&tables(&[0, 2, 4]));
+        // Entries start at byte 12, 16 bytes each: tag, checksum, offset, length
+        let entry = |i: usize| 12 + i * 16;
+        // Swap the tags of cmap and OS/2
+        data.copy_within(entry(1)..entry(1) + 4, entry(0));
+        data[entry(1)..entry(1) + 4].copy_from_slice(b"OS/2");
+        // Make glyf run into head and post run past the end of the file
+        data[entry(2) + 12..entry(2) + 16].copy_from_slice(&12u32.to_be_bytes());
+        data[entry(9) + 12..entry(9) + 16].copy_from_slice(&1000u32.to_be_bytes());
+        // Misalign hmtx
+        let hmtx = u32::from_be_bytes(data[entry(5) + 8..entry(5) + 12].try_into().unwrap());
+        data[entry(5) + 8..entry(5) + 12].copy_from_slice(&(hmtx + 1).to_be_bytes());
+
+        let checks: Vec<(Check, Option<Tag>)> = findings(&data)
+            .into_iter()
+            .filter(|(_, check, _)| *check != Check::TableChecksum)
+            .map(|(_, check, tag)| (check, tag))
+            .collect();
+        assert!(checks.contains(&(Check::TableOrder, Some(Tag::new(b"OS/2")))));
+        assert!(checks.contains(&(Check::TableOverlap, Some(head::TAG))));
+        assert!(checks.contains(&(Check::TableAlignment, Some(Tag::new(b"hmtx")))));
+        assert!(checks.contains(&(Check::TableBounds, Some(Tag::new(b"post")))));
+
+        let mut data = font(&tables(&[0, 2, 4]));
+        data[entry(1)..entry(1) + 4].copy_from_slice(b"OS/2");
+        assert!(findings(&data).contains(&(
+            Severity::Error,
+            Check::DuplicateTable,
+            Some(Tag::new(b"OS/2"))
+        )));
+    }
+
+    #[test]
+    fn missing_tables() {
+        let data = font(&[(b"glyf", vec![0; 4]), (b"name", name_table("Test Sans"))]);
+        let missing: Vec<(Severity, Option<Tag>)> = findings(&data)
+            .into_iter()
+            .filter(|(_, check, _)| *check == Check::MissingTable)
+            .map(|(severity, _, tag)| (severity, tag))
+            .collect();
+        let error = |tag: &[u8; 4]| (Severity::Error, Some(Tag::new(tag)));
+        assert_eq!(
+            missing,
+            [
+                error(b"cmap"),
+                error(b"head"),
+                error(b"hhea"),
+                error(b"hmtx"),
+                error(b"maxp"),
+                error(b"post"),
+                (Severity::Warning, Some(Tag::new(b"OS/2"))),
+                error(b"loca"),
+            ]
+        );
+
+        let data = font(&[(b"CBDT", vec![0; 4]), (b"name", name_table("Emoji"))]);
+        assert!(findings(&data).contains(&(
+            Severity::Error,
+            Check::MissingTable,
+            Some(Tag::new(b"CBLC"))
+        )));
+    }
+
+    #[test]
+    fn loca_offsets() {
+        let loca = |offsets: &[u16]| {
+            findings(&font(&tables(offsets)))
+                .into_iter()
+                .filter(|(_, check, _)| *check == Check::Loca)
+                .map(|(severity, _, _)| severity)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(loca(&[0, 2]), [Severity::Error]); // Too short
+        assert_eq!(loca(&[0, 2, 4, 4]), [Severity::Warning]); // Too long
+        assert_eq!(loca(&[0, 3, 2]), [Severity::Error]); // Backwards
+        assert_eq!(loca(&[0, 2, 5]), [Severity::Error]); // Past glyf
+    }
+}
@@ -0,0 +1,267 @@
+//! WOFF 1.0 decoding, and the sfnt writer shared with WOFF2
+
+use std::fmt;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+
+use super::reader::Reader;
+use super::Tag;
+use crate::{Error, Result};
+
+pub(crate) const WOFF_SIGNATURE: Tag = Tag::new(b"wOFF");
+
+/// The file format a face was stored in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Container {
+    #[default]
+    Sfnt, // Plain .ttf/.otf/.ttc/.otc (or a system API that doesn't say)
+    Woff,  // WOFF 1.0 - zlib-compressed tables
+    Woff2, // WOFF 2.0 - Brotli-compressed, optionally transformed tables
+}
+
+impl Container {
+    /// Every container format
+    pub const ALL: &'static [Container] = &[Container::Sfnt, Container::Woff, Container::Woff2];
+
+    /// Short lowercase name, as used in filters and exports
+    pub fn name(self) -> &'static str {
+        match self {
+            Container::Sfnt => "sfnt",
+            Container::Woff => "woff",
+            Container::Woff2 => "woff2",
+        }
+    }
+
+    /// Looks up a container by its short name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Container> {
+        Container::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Container {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Container::Sfnt => "SFNT",
+            Container::Woff => "WOFF",
+            Container::Woff2 => "WOFF2",
+        })
+    }
+}
+
+// ============================================================================
+// WOFF 1.0
+// ============================================================================
+
+/// Decodes a WOFF 1.0 file back into sfnt bytes
+///
+/// Each table is stored zlib-compressed unless compression didn't help,
+/// in which case it is stored as-is (compLength == origLength).
+pub(crate) fn decode_woff(data: &[u8]) -> Result<Vec<u8>> {
+    let mut r = Reader::new(data);
+    let _signature = r.tag()?;
+    let flavor = r.u32()?;
+    let _length = r.u32()?;
+    let num_tables = r.u16()?;
+    r.skip(2)?; // reserved
+    let total_sfnt_size = r.u32()? as usize;
+    r.skip(24)?; // version, metadata and private data blocks
+
+    // The decoded font may not exceed the size the header declares
+    let mut sfnt_size = 12 + num_tables as usize * 16;
+    let mut tables = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let tag = r.tag()?;
+        let offset = r.u32()? as usize;
+        let comp_length = r.u32()? as usize;
+        let orig_length = r.u32()? as usize;
+        let _orig_checksum = r.u32()?;
+
+        sfnt_size += padded_len(orig_length);
+        if sfnt_size > total_sfnt_size {
+            return Err(Error::Parse(format!(
+                "WOFF tables exceed the declared font size of {} bytes",
+                total_sfnt_size
+            )));
+        }
+
+        let stored = Reader::at(data, offset)?.bytes(comp_length)?;
+        let table = if comp_length < orig_length {
+            let mut table = Vec::new();
+            ZlibDecoder::new(stored)
+                .take(orig_length as u64 + 1)
+                .read_to_end(&mut table)
+                .map_err(|e| Error::Parse(format!("WOFF table '{}': {}", tag, e)))?;
+            table
+        } else {
+            stored.to_vec()
+        };
+        if table.len() != orig_length {
+            return Err(Error::Parse(format!(
+                "WOFF table '{}' has the wrong length",
+                tag
+            )));
+        }
+        tables.push((tag, table));
+    }
+
+    Ok(build_sfnt(flavor, &tables))
+}
+
+// ============================================================================
+// SFNT WRITER
+// ============================================================================
+
+/// Assembles an sfnt file from its tables
+///
+/// Tables are written in tag order, 4-byte aligned, with freshly computed
+/// checksums. The head table's checkSumAdjustment is left as stored.
+pub(crate) fn build_sfnt(flavor: u32, tables: &[(Tag, Vec<u8>)]) -> Vec<u8> {
+    let mut sorted: Vec<&(Tag, Vec<u8>)> = tables.iter().collect();
+    sorted.sort_by_key(|(tag, _)| *tag);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&flavor.to_be_bytes());
+    push_search_fields(&mut out, sorted.len());
+
+    let mut offset = 12 + sorted.len() * 16;
+    for (tag, table) in &sorted {
+        out.extend_from_slice(&tag.0);
+        out.extend_from_slice(&table_checksum(*tag, table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += padded_len(table.len());
+    }
+    for (_, table) in &sorted {
+        out.extend_from_slice(table);
+        out.resize(padded_len(out.len()), 0);
+    }
+    out
+}
+
+/// Appends numTables, searchRange, entrySelector and rangeShift
+///
+/// The products are computed in u32, so a directory of more than 4095
+/// tables doesn't overflow; values that don't fit the u16 fields saturate.
+pub(crate) fn push_search_fields(out: &mut Vec<u8>, num_tables: usize) {
+    let field = |value: u32| value.min(u16::MAX as u32) as u16;
+    let num_tables = num_tables.min(u16::MAX as usize) as u32;
+    let entry_selector = num_tables.max(1).ilog2();
+    let search_range = (1u32 << entry_selector) * 16;
+    out.extend_from_slice(&field(num_tables).to_be_bytes());
+    out.extend_from_slice(&field(search_range).to_be_bytes());
+    out.extend_from_slice(&field(entry_selector).to_be_bytes());
+    out.extend_from_slice(&field(num_tables * 16 - search_range).to_be_bytes());
+}
+
+/// The OpenType table checksum: the sum of big-endian u32 words, zero-padded
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// A table directory checksum, which for head excludes checkSumAdjustment
+pub(crate) fn table_checksum(tag: Tag, data: &[u8]) -> u32 {
+    let sum = checksum(data);
+    match data.get(8..12) {
+        Some(adjustment) if tag == super::head::TAG => sum.wrapping_sub(u32::from_be_bytes([
+            adjustment[0],
+            adjustment[1],
+            adjustment[2],
+            adjustment[3],
+        ])),
+        _ => sum,
+    }
+}
+
+/// Rounds a length up to a multiple of 4
+pub(crate) fn padded_len(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A WOFF file with one table stored as `stored`
+    fn woff(total_sfnt_size: u32, tag: &[u8; 4], stored: &[u8], orig_length: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"wOFF");
+        out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes()); // length
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&total_sfnt_size.to_be_bytes());
+        out.extend_from_slice(&[0; 24]);
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&64u32.to_be_bytes()); // offset: header + 1 entry
+        out.extend_from_slice(&(stored.len() as u32).to_be_bytes());
+        out.extend_from_slice(&orig_length.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes());
+        out.extend_from_slice(stored);
+        out
+    }
+
+    #[test]
+    fn compressed_and_stored_tables_are_decoded() {
+        let table = vec![7u8; 100];
+        let sfnt = decode_woff(&woff(128, b"name", &zlib(&table), 100)).unwrap();
+        assert_eq!(&sfnt[12..16], b"name");
+        assert_eq!(&sfnt[28..], &table[..]);
+
+        let sfnt = decode_woff(&woff(32, b"name", &[1, 2, 3, 4], 4)).unwrap();
+        assert_eq!(&sfnt[28..], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn tables_larger_than_declared_are_rejected() {
+        // The declared font size can't hold a 100-byte table
+        assert!(decode_woff(&woff(64, b"name", &zlib(&[7; 100]), 100)).is_err());
+        // The zlib stream expands past origLength
+        let bomb = zlib(&vec![0; 1 << 20]);
+        assert!(decode_woff(&woff(1 << 21, b"name", &bomb, 1 << 16)).is_err());
+    }
+
+    #[test]
+    fn search_fields() {
+        let mut out = Vec::new();
+        push_search_fields(&mut out, 12);
+        assert_eq!(out, [12u16, 128, 3, 64].map(u16::to_be_bytes).concat());
+
+        // More than 4095 tables: u16 products would overflow
+        let mut out = Vec::new();
+        push_search_fields(&mut out, 5000);
+        assert_eq!(
+            out,
+            [5000u16, 65535, 12, 14464].map(u16::to_be_bytes).concat()
+        );
+    }
+
+    #[test]
+    fn head_checksum_skips_the_adjustment() {
+        let mut head = vec![0u8; 12];
+        head[3] = 1;
+        head[8..12].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+        assert_eq!(checksum(&head), 0x1234_5679);
+        assert_eq!(table_checksum(super::super::head::TAG, &head), 1);
+        assert_eq!(checksum(&[0, 0, 1]), 0x100);
+    }
+}
@@ -0,0 +1,873 @@
+//! WOFF 2.0 decoding
+//!
+//! WOFF2 compresses all tables as one Brotli stream and may additionally
+//! store `glyf`/`loca` and `hmtx` in transformed forms that compress
+//! better. Decoding reverses both steps and reassembles a plain sfnt (or,
+//! for font collections, a .ttc) that the rest of the parser reads as usual.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use super::reader::Reader;
+use super::woff::{build_sfnt, padded_len, push_search_fields, table_checksum};
+use super::Tag;
+use crate::{Error, Result};
+
+pub(crate) const WOFF2_SIGNATURE: Tag = Tag::new(b"wOF2");
+
+const GLYF: Tag = Tag::new(b"glyf");
+const LOCA: Tag = Tag::new(b"loca");
+const HMTX: Tag = Tag::new(b"hmtx");
+const HHEA: Tag = Tag::new(b"hhea");
+const TTCF: u32 = 0x7474_6366;
+
+/// Tags that the table directory can refer to by index (flags & 0x3F)
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// One entry of the WOFF2 table directory
+struct TableEntry {
+    tag: Tag,
+    transformed: bool, // Stored in a WOFF2 transform rather than as-is
+    stored_length: usize,
+}
+
+/// One member font of a WOFF2 collection
+struct CollectionFont {
+    flavor: u32,
+    tables: Vec<usize>, // Indices into the table directory
+}
+
+/// Decodes a WOFF 2.0 file into sfnt (or TTC) bytes
+pub(crate) fn decode_woff2(data: &[u8]) -> Result<Vec<u8>> {
+    let mut r = Reader::new(data);
+    let _signature = r.tag()?;
+    let flavor = r.u32()?;
+    let _length = r.u32()?;
+    let num_tables = r.u16()?;
+    r.skip(2)?; // reserved
+    let _total_sfnt_size = r.u32()?;
+    let total_compressed_size = r.u32()? as usize;
+    r.skip(24)?; // version, metadata and private data blocks
+
+    let mut entries = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let flags = r.u8()?;
+        let tag = match flags & 0x3F {
+            63 => r.tag()?,
+            i => Tag::new(KNOWN_TAGS[i as usize]),
+        };
+        // glyf/loca use version 0 for their transform and 3 for none;
+        // every other table uses 0 for none
+        let version = flags >> 6;
+        let transformed = match tag {
+            GLYF | LOCA => version == 0,
+            _ => version != 0,
+        };
+        let orig_length = base128(&mut r)? as usize;
+        let stored_length = if transformed {
+            base128(&mut r)? as usize
+        } else {
+            orig_length
+        };
+        entries.push(TableEntry {
+            tag,
+            transformed,
+            stored_length,
+        });
+    }
+
+    let fonts = if flavor == TTCF {
+        read_collection_header(&mut r, entries.len())?
+    } else {
+        vec![CollectionFont {
+            flavor,
+            tables: (0..entries.len()).collect(),
+        }]
+    };
+
+    // All table data is one Brotli stream, exactly as long as the stored
+    // tables; the limit keeps a small file from expanding without bound
+    let expected_len: u64 = entries.iter().map(|e| e.stored_length as u64).sum();
+    let compressed = r.bytes(total_compressed_size)?;
+    let mut stream = Vec::new();
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .take(expected_len + 1)
+        .read_to_end(&mut stream)
+        .map_err(|e| Error::Parse(format!("WOFF2 Brotli stream: {}", e)))?;
+    if stream.len() as u64 != expected_len {
+        return Err(Error::Parse(format!(
+            "WOFF2 Brotli stream is {} bytes, the table directory needs {}",
+            stream.len(),
+            expected_len
+        )));
+    }
+
+    let mut stored = Vec::with_capacity(entries.len());
+    let mut offset = 0;
+    for entry in &entries {
+        let end = offset + entry.stored_length;
+        let bytes = stream
+            .get(offset..end)
+            .ok_or_else(|| Error::Parse(format!("WOFF2 table '{}' is truncated", entry.tag)))?;
+        stored.push(bytes);
+        offset = end;
+    }
+
+    // Undo the transforms; each font resolves its own glyf/loca/hmtx
+    let mut tables: Vec<Option<Vec<u8>>> = entries
+        .iter()
+        .zip(&stored)
+        .map(|(e, bytes)| (!e.transformed).then(|| bytes.to_vec()))
+        .collect();
+    let mut x_mins = HashMap::new();
+    for font in &fonts {
+        reconstruct_font(font, &entries, &stored, &mut tables, &mut x_mins)?;
+    }
+
+    let tables: Vec<(Tag, Vec<u8>)> = entries
+        .iter()
+        .zip(tables)
+        .map(|(e, table)| (e.tag, table.unwrap_or_default()))
+        .collect();
+
+    if flavor == TTCF {
+        Ok(build_collection(&fonts, &tables))
+    } else {
+        Ok(build_sfnt(flavor, &tables))
+    }
+}
+
+/// Reads the CollectionHeader that follows the table directory
+fn read_collection_header(r: &mut Reader, num_tables: usize) -> Result<Vec<CollectionFont>> {
+    let _version = r.u32()?;
+    let num_fonts = uint255(r)?;
+    let mut fonts = Vec::with_capacity(num_fonts as usize);
+    for _ in 0..num_fonts {
+        let font_num_tables = uint255(r)?;
+        let flavor = r.u32()?;
+        let mut tables = Vec::with_capacity(font_num_tables as usize);
+        for _ in 0..font_num_tables {
+            let index = uint255(r)? as usize;
+            if index >= num_tables {
+                return Err(Error::Parse(format!(
+                    "WOFF2 collection table index {} out of range",
+                    index
+                )));
+            }
+            tables.push(index);
+        }
+        fonts.push(CollectionFont { flavor, tables });
+    }
+    Ok(fonts)
+}
+
+/// Reconstructs the transformed tables of one font
+///
+/// Collection fonts can share a transformed glyf; the xMins of each
+/// reconstructed glyf are kept in `x_mins` by table index, so every font's
+/// hmtx can be rebuilt from them.
+fn reconstruct_font(
+    font: &CollectionFont,
+    entries: &[TableEntry],
+    stored: &[&[u8]],
+    tables: &mut [Option<Vec<u8>>],
+    x_mins: &mut HashMap<usize, Vec<i16>>,
+) -> Result<()> {
+    let find = |tag: Tag| font.tables.iter().copied().find(|&i| entries[i].tag == tag);
+
+    let glyf = find(GLYF);
+    if let (Some(glyf), Some(loca)) = (glyf, find(LOCA)) {
+        if entries[glyf].transformed && tables[glyf].is_none() {
+            let (glyf_data, loca_data, mins) = reconstruct_glyf(stored[glyf])?;
+            tables[glyf] = Some(glyf_data);
+            tables[loca] = Some(loca_data);
+            x_mins.insert(glyf, mins);
+        }
+    }
+    let x_mins = glyf
+        .and_then(|i| x_mins.get(&i))
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+
+    if let Some(hmtx) = find(HMTX) {
+        if entries[hmtx].transformed && tables[hmtx].is_none() {
+            let hhea = find(HHEA).and_then(|i| tables[i].as_deref());
+            let num_h_metrics = match hhea {
+                Some(hhea) => Reader::at(hhea, 34)?.u16()? as usize,
+                None => {
+                    return Err(Error::Parse(
+                        "WOFF2 hmtx transform without hhea".to_string(),
+                    ))
+                }
+            };
+            tables[hmtx] = Some(reconstruct_hmtx(stored[hmtx], num_h_metrics, x_mins)?);
+        }
+    }
+
+    // A transformed table this decoder doesn't know can't be recovered
+    for &i in &font.tables {
+        if tables[i].is_none() {
+            return Err(Error::Parse(format!(
+                "unsupported WOFF2 transform of '{}'",
+                entries[i].tag
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// GLYF / LOCA TRANSFORM
+// ============================================================================
+
+// Simple glyph flags
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// Composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+/// Rebuilds `glyf` and `loca` from the transformed glyf stream
+///
+/// Returns the two tables plus each glyph's xMin, which the hmtx
+/// transform needs to restore left side bearings.
+fn reconstruct_glyf(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<i16>)> {
+    let mut r = Reader::new(data);
+    r.skip(2)?; // reserved
+    let option_flags = r.u16()?;
+    let num_glyphs = r.u16()? as usize;
+    let index_format = r.u16()?;
+    let mut sizes = [0usize; 7];
+    for size in &mut sizes {
+        *size = r.u32()? as usize;
+    }
+
+    // The seven streams follow the header back to back
+    let mut streams = Vec::with_capacity(7);
+    for &size in &sizes {
+        streams.push(Reader::new(r.bytes(size)?));
+    }
+    let [mut n_contours, mut n_points, mut flags, mut glyphs, mut composites, mut bboxes, mut instructions] =
+        <[Reader; 7]>::try_from(streams)
+            .map_err(|_| Error::Parse("WOFF2 glyf streams".to_string()))?;
+
+    let overlap_bitmap = if option_flags & 0x0001 != 0 {
+        Some(r.bytes(num_glyphs.div_ceil(8))?)
+    } else {
+        None
+    };
+    let bbox_bitmap = bboxes.bytes(num_glyphs.div_ceil(32) * 4)?;
+    let bit_set = |bitmap: &[u8], i: usize| bitmap[i / 8] & (0x80 >> (i % 8)) != 0;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+
+    for i in 0..num_glyphs {
+        offsets.push(glyf.len());
+        let contour_count = n_contours.i16()?;
+        let explicit_bbox = bit_set(bbox_bitmap, i);
+
+        if contour_count == 0 {
+            // Empty glyph
+            x_mins.push(0);
+            continue;
+        }
+
+        let start = glyf.len();
+        if contour_count < 0 {
+            // Composite: component records are copied verbatim
+            if !explicit_bbox {
+                return Err(Error::Parse(format!(
+                    "WOFF2 composite glyph {} has no bbox",
+                    i
+                )));
+            }
+            let bbox = read_bbox(&mut bboxes)?;
+            push_glyph_header(&mut glyf, -1, bbox);
+            let have_instructions = copy_components(&mut composites, &mut glyf)?;
+            if have_instructions {
+                let len = uint255(&mut glyphs)? as usize;
+                glyf.extend_from_slice(&(len as u16).to_be_bytes());
+                glyf.extend_from_slice(instructions.bytes(len)?);
+            }
+            x_mins.push(bbox[0]);
+        } else {
+            // Simple: decode the point triplets, then re-encode as glyf
+            let mut end_points = Vec::with_capacity(contour_count as usize);
+            let mut total = 0usize;
+            for _ in 0..contour_count {
+                // Contours have at least one point, and end points are u16
+                let points = uint255(&mut n_points)? as usize;
+                total += points;
+                let end = total
+                    .checked_sub(1)
+                    .filter(|_| points > 0)
+                    .and_then(|end| u16::try_from(end).ok())
+                    .ok_or_else(|| {
+                        Error::Parse(format!("WOFF2 glyph {} has an invalid contour", i))
+                    })?;
+                end_points.push(end);
+            }
+            let points = decode_points(total, &mut flags, &mut glyphs)?;
+            let instruction_len = uint255(&mut glyphs)? as usize;
+
+            let bbox = if explicit_bbox {
+                read_bbox(&mut bboxes)?
+            } else {
+                compute_bbox(&points)
+            };
+            push_glyph_header(&mut glyf, contour_count, bbox);
+            for end in &end_points {
+                glyf.extend_from_slice(&end.to_be_bytes());
+            }
+            glyf.extend_from_slice(&(instruction_len as u16).to_be_bytes());
+            glyf.extend_from_slice(instructions.bytes(instruction_len)?);
+            let overlap = overlap_bitmap.map(|b| bit_set(b, i)).unwrap_or(false);
+            encode_points(&mut glyf, &points, overlap);
+            x_mins.push(bbox[0]);
+        }
+
+        // Pad each glyph so short loca offsets (offset / 2) stay exact
+        let padded = start + padded_len(glyf.len() - start);
+        glyf.resize(padded, 0);
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::with_capacity(offsets.len() * 4);
+    for offset in offsets {
+        match index_format {
+            0 => {
+                // Short offsets only reach 128 KiB of glyf
+                let short = u16::try_from(offset / 2).map_err(|_| {
+                    Error::Parse("WOFF2 glyf is too large for short loca offsets".to_string())
+                })?;
+                loca.extend_from_slice(&short.to_be_bytes());
+            }
+            _ => loca.extend_from_slice(&(offset as u32).to_be_bytes()),
+        }
+    }
+    Ok((glyf, loca, x_mins))
+}
+
+/// A decoded glyph point, in absolute coordinates
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+    on_curve: bool,
+}
+
+/// Decodes `count` points from the flag stream and the glyph stream triplets
+fn decode_points(count: usize, flags: &mut Reader, glyphs: &mut Reader) -> Result<Vec<Point>> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+
+    let mut points = Vec::with_capacity(count);
+    let (mut x, mut y) = (0i32, 0i32);
+    for _ in 0..count {
+        let flag = flags.u8()?;
+        let on_curve = flag & 0x80 == 0;
+        let f = flag & 0x7F;
+
+        let (dx, dy) = if f < 10 {
+            let b0 = glyphs.u8()? as i32;
+            (0, with_sign(f, (((f & 14) as i32) << 7) + b0))
+        } else if f < 20 {
+            let b0 = glyphs.u8()? as i32;
+            (with_sign(f, ((((f - 10) & 14) as i32) << 7) + b0), 0)
+        } else if f < 84 {
+            let b0 = (f - 20) as i32;
+            let b1 = glyphs.u8()? as i32;
+            (
+                with_sign(f, 1 + (b0 & 0x30) + (b1 >> 4)),
+                with_sign(f >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+            )
+        } else if f < 120 {
+            let b0 = (f - 84) as i32;
+            let b1 = glyphs.u8()? as i32;
+            let b2 = glyphs.u8()? as i32;
+            (
+                with_sign(f, 1 + ((b0 / 12) << 8) + b1),
+                with_sign(f >> 1, 1 + (((b0 % 12) >> 2) << 8) + b2),
+            )
+        } else if f < 124 {
+            let b1 = glyphs.u8()? as i32;
+            let b2 = glyphs.u8()? as i32;
+            let b3 = glyphs.u8()? as i32;
+            (
+                with_sign(f, (b1 << 4) + (b2 >> 4)),
+                with_sign(f >> 1, ((b2 & 0x0F) << 8) + b3),
+            )
+        } else {
+            let dx = glyphs.u16()? as i32;
+            let dy = glyphs.u16()? as i32;
+            (with_sign(f, dx), with_sign(f >> 1, dy))
+        };
+
+        x += dx;
+        y += dy;
+        points.push(Point { x, y, on_curve });
+    }
+    Ok(points)
+}
+
+/// Appends the flags and coordinate arrays of a simple glyph
+fn encode_points(out: &mut Vec<u8>, points: &[Point], overlap: bool) {
+    let mut flags = Vec::with_capacity(points.len());
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut last_x, mut last_y) = (0i32, 0i32);
+
+    for (i, p) in points.iter().enumerate() {
+        let mut flag = if p.on_curve { ON_CURVE_POINT } else { 0 };
+        if i == 0 && overlap {
+            flag |= OVERLAP_SIMPLE;
+        }
+        encode_delta(
+            p.x - last_x,
+            X_SHORT_VECTOR,
+            X_IS_SAME_OR_POSITIVE,
+            &mut flag,
+            &mut xs,
+        );
+        encode_delta(
+            p.y - last_y,
+            Y_SHORT_VECTOR,
+            Y_IS_SAME_OR_POSITIVE,
+            &mut flag,
+            &mut ys,
+        );
+        flags.push(flag);
+        last_x = p.x;
+        last_y = p.y;
+    }
+
+    out.extend_from_slice(&flags);
+    out.extend_from_slice(&xs);
+    out.extend_from_slice(&ys);
+}
+
+/// Encodes one coordinate delta in the smallest glyf form
+fn encode_delta(delta: i32, short: u8, same_or_positive: u8, flag: &mut u8, out: &mut Vec<u8>) {
+    if delta == 0 {
+        *flag |= same_or_positive;
+    } else if (-255..=255).contains(&delta) {
+        *flag |= short;
+        if delta > 0 {
+            *flag |= same_or_positive;
+        }
+        out.push(delta.unsigned_abs() as u8);
+    } else {
+        out.extend_from_slice(&(delta as i16).to_be_bytes());
+    }
+}
+
+/// Copies composite component records; returns true if instructions follow
+fn copy_components(r: &mut Reader, out: &mut Vec<u8>) -> Result<bool> {
+    let mut have_instructions = false;
+    loop {
+        let flags = r.u16()?;
+        let glyph_index = r.u16()?;
+        out.extend_from_slice(&flags.to_be_bytes());
+        out.extend_from_slice(&glyph_index.to_be_bytes());
+
+        let mut len = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            len += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            len += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            len += 8;
+        }
+        out.extend_from_slice(r.bytes(len)?);
+
+        have_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(have_instructions);
+        }
+    }
+}
+
+fn read_bbox(r: &mut Reader) -> Result<[i16; 4]> {
+    Ok([r.i16()?, r.i16()?, r.i16()?, r.i16()?])
+}
+
+/// xMin, yMin, xMax, yMax of a glyph's points
+fn compute_bbox(points: &[Point]) -> [i16; 4] {
+    let mut bbox = [i16::MAX, i16::MAX, i16::MIN, i16::MIN];
+    for p in points {
+        bbox[0] = bbox[0].min(p.x as i16);
+        bbox[1] = bbox[1].min(p.y as i16);
+        bbox[2] = bbox[2].max(p.x as i16);
+        bbox[3] = bbox[3].max(p.y as i16);
+    }
+    if points.is_empty() {
+        [0; 4]
+    } else {
+        bbox
+    }
+}
+
+fn push_glyph_header(out: &mut Vec<u8>, contour_count: i16, bbox: [i16; 4]) {
+    out.extend_from_slice(&contour_count.to_be_bytes());
+    for value in bbox {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+// ============================================================================
+// HMTX TRANSFORM
+// ============================================================================
+
+/// Rebuilds `hmtx`, restoring left side bearings omitted in favour of xMin
+fn reconstruct_hmtx(data: &[u8], num_h_metrics: usize, x_mins: &[i16]) -> Result<Vec<u8>> {
+    let mut r = Reader::new(data);
+    let flags = r.u8()?;
+    let num_glyphs = x_mins.len();
+    if num_h_metrics == 0 || num_h_metrics > num_glyphs {
+        return Err(Error::Parse(
+            "WOFF2 hmtx transform needs glyf data".to_string(),
+        ));
+    }
+
+    let mut advances = Vec::with_capacity(num_h_metrics);
+    for _ in 0..num_h_metrics {
+        advances.push(r.u16()?);
+    }
+    // Bit 0: proportional lsbs omitted; bit 1: monospaced lsbs omitted
+    let mut lsbs = Vec::with_capacity(num_glyphs);
+    for (i, &x_min) in x_mins.iter().enumerate() {
+        let omitted = if i < num_h_metrics {
+            flags & 0x01 != 0
+        } else {
+            flags & 0x02 != 0
+        };
+        lsbs.push(if omitted { x_min } else { r.i16()? });
+    }
+
+    let mut hmtx = Vec::with_capacity(num_h_metrics * 4 + (num_glyphs - num_h_metrics) * 2);
+    for (i, lsb) in lsbs.iter().enumerate() {
+        if i < num_h_metrics {
+            hmtx.extend_from_slice(&advances[i].to_be_bytes());
+        }
+        hmtx.extend_from_slice(&lsb.to_be_bytes());
+    }
+    Ok(hmtx)
+}
+
+// ============================================================================
+// COLLECTIONS
+// ============================================================================
+
+/// Assembles a version 1.0 TTC whose member fonts share table data
+fn build_collection(fonts: &[CollectionFont], tables: &[(Tag, Vec<u8>)]) -> Vec<u8> {
+    let header_len = 12 + fonts.len() * 4;
+    let directories_len: usize = fonts.iter().map(|f| 12 + f.tables.len() * 16).sum();
+
+    // Every table is stored once, after all the table directories
+    let mut table_offsets = Vec::with_capacity(tables.len());
+    let mut offset = header_len + directories_len;
+    for (_, table) in tables {
+        table_offsets.push(offset);
+        offset += padded_len(table.len());
+    }
+
+    let mut out = Vec::with_capacity(offset);
+    out.extend_from_slice(&TTCF.to_be_bytes());
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+    let mut directory_offset = header_len;
+    for font in fonts {
+        out.extend_from_slice(&(directory_offset as u32).to_be_bytes());
+        directory_offset += 12 + font.tables.len() * 16;
+    }
+
+    for font in fonts {
+        let mut indices = font.tables.clone();
+        indices.sort_by_key(|&i| tables[i].0);
+        out.extend_from_slice(&font.flavor.to_be_bytes());
+        push_search_fields(&mut out, indices.len());
+        for i in indices {
+            let (tag, table) = &tables[i];
+            out.extend_from_slice(&tag.0);
+            out.extend_from_slice(&table_checksum(*tag, table).to_be_bytes());
+            out.extend_from_slice(&(table_offsets[i] as u32).to_be_bytes());
+            out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        }
+    }
+
+    for (_, table) in tables {
+        out.extend_from_slice(table);
+        out.resize(padded_len(out.len()), 0);
+    }
+    out
+}
+
+// ============================================================================
+// VARIABLE-LENGTH INTEGERS
+// ============================================================================
+
+/// UIntBase128: 1-5 bytes, 7 bits each, most significant first
+fn base128(r: &mut Reader) -> Result<u32> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = r.u8()?;
+        if i == 0 && byte == 0x80 {
+            return Err(Error::Parse("UIntBase128 with leading zeros".to_string()));
+        }
+        if value & 0xFE00_0000 != 0 {
+            return Err(Error::Parse("UIntBase128 overflow".to_string()));
+        }
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::Parse("UIntBase128 longer than 5 bytes".to_string()))
+}
+
+/// 255UInt16: a byte, or a marker byte followed by one or two more
+fn uint255(r: &mut Reader) -> Result<u16> {
+    const ONE_MORE_BYTE_CODE2: u8 = 254;
+    const ONE_MORE_BYTE_CODE1: u8 = 255;
+    const WORD_CODE: u8 = 253;
+    const LOWEST_U_CODE: u16 = 253;
+
+    Ok(match r.u8()? {
+        WORD_CODE => r.u16()?,
+        ONE_MORE_BYTE_CODE1 => r.u8()? as u16 + LOWEST_U_CODE,
+        ONE_MORE_BYTE_CODE2 => r.u8()? as u16 + LOWEST_U_CODE * 2,
+        code => code as u16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Brotli stream holding `data` in one uncompressed meta-block
+    fn brotli_stored(data: &[u8]) -> Vec<u8> {
+        assert!(!data.is_empty() && data.len() <= 0x1_0000);
+        // WBITS = 16 (1 bit), ISLAST = 0, MNIBBLES = 4, MLEN - 1 (16 bits),
+        // ISUNCOMPRESSED = 1, then padding to the byte boundary
+        let header = ((data.len() as u32 - 1) << 4) | 1 << 20;
+        let mut stream = header.to_le_bytes()[..3].to_vec();
+        stream.extend_from_slice(data);
+        stream.push(0x03); // ISLAST, ISLASTEMPTY
+        stream
+    }
+
+    /// A WOFF2 file; `directory` holds the encoded table entries (and the
+    /// collection header, if any) and `tables` the uncompressed stream
+    fn woff2(flavor: u32, num_tables: u16, directory: &[u8], tables: &[u8]) -> Vec<u8> {
+        let compressed = brotli_stored(tables);
+        let mut out = Vec::new();
+        out.extend_from_slice(b"wOF2");
+        out.extend_from_slice(&flavor.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes()); // length
+        out.extend_from_slice(&num_tables.to_be_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&0u32.to_be_bytes()); // totalSfntSize
+        out.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        out.extend_from_slice(&[0; 24]);
+        out.extend_from_slice(directory);
+        out.extend_from_slice(&compressed);
+        out
+    }
+
+    /// Transformed glyf stream of simple glyphs with one contour each;
+    /// `points` is the point count of each glyph, every point at (5, 0)
+    fn glyf_transform(points: &[u8]) -> Vec<u8> {
+        let n = points.len();
+        let n_contours: Vec<u8> = points.iter().flat_map(|_| [0, 1]).collect();
+        let total: usize = points.iter().map(|&p| p as usize).sum();
+        // Flag 11: dx = +b0, dy = 0; the first point moves to x = 5
+        let flags = vec![11u8; total];
+        let mut glyphs = Vec::new();
+        for &count in points {
+            for i in 0..count {
+                glyphs.push(if i == 0 { 5 } else { 0 });
+            }
+            glyphs.push(0); // instruction length
+        }
+        let bboxes = vec![0u8; n.div_ceil(32) * 4];
+
+        let mut out = vec![0, 0, 0, 0];
+        out.extend_from_slice(&(n as u16).to_be_bytes());
+        out.extend_from_slice(&1u16.to_be_bytes()); // long loca
+        let streams = [
+            &n_contours,
+            &points.to_vec(),
+            &flags,
+            &glyphs,
+            &vec![],
+            &bboxes,
+            &vec![],
+        ];
+        for stream in streams {
+            out.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        }
+        for stream in streams {
+            out.extend_from_slice(stream);
+        }
+        out
+    }
+
+    /// hhea with numberOfHMetrics = 1
+    fn hhea() -> Vec<u8> {
+        let mut hhea = vec![0u8; 36];
+        hhea[35] = 1;
+        hhea
+    }
+
+    /// Finds a table of the `font`-th member of a TTC
+    fn ttc_table(data: &[u8], font: usize, tag: &[u8; 4]) -> Vec<u8> {
+        let directory = Reader::at(data, 12 + font * 4).unwrap().u32().unwrap() as usize;
+        let num_tables = Reader::at(data, directory + 4).unwrap().u16().unwrap() as usize;
+        for i in 0..num_tables {
+            let mut r = Reader::at(data, directory + 12 + i * 16).unwrap();
+            let found = r.tag().unwrap();
+            let _checksum = r.u32().unwrap();
+            let offset = r.u32().unwrap() as usize;
+            let length = r.u32().unwrap() as usize;
+            if found == Tag::new(tag) {
+                return data[offset..offset + length].to_vec();
+            }
+        }
+        panic!("no {:?} table", tag);
+    }
+
+    #[test]
+    fn variable_length_integers() {
+        assert_eq!(base128(&mut Reader::new(&[0x3F])).unwrap(), 63);
+        assert_eq!(base128(&mut Reader::new(&[0x81, 0x00])).unwrap(), 128);
+        assert!(base128(&mut Reader::new(&[0x80, 0x01])).is_err());
+        assert!(base128(&mut Reader::new(&[0xFF; 6])).is_err());
+        assert_eq!(uint255(&mut Reader::new(&[252])).unwrap(), 252);
+        assert_eq!(uint255(&mut Reader::new(&[255, 0])).unwrap(), 253);
+        assert_eq!(uint255(&mut Reader::new(&[254, 0])).unwrap(), 506);
+        assert_eq!(
+            uint255(&mut Reader::new(&[253, 0x12, 0x34])).unwrap(),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn untransformed_tables_are_copied() {
+        // cmap (known tag 0) and a custom tag, 4 and 2 bytes
+        let mut directory = vec![0x00, 4, 0x3F];
+        directory.extend_from_slice(b"TEST");
+        directory.push(2);
+        let data = woff2(0x0001_0000, 2, &directory, &[1, 2, 3, 4, 5, 6]);
+
+        let sfnt = decode_woff2(&data).unwrap();
+        assert_eq!(&sfnt[..4], &[0, 1, 0, 0]);
+        assert_eq!(Reader::at(&sfnt, 4).unwrap().u16().unwrap(), 2);
+        assert_eq!(&sfnt[12..16], b"TEST");
+        assert_eq!(&sfnt[28..32], b"cmap");
+        // Table data follows the directory in tag order, 4-byte aligned
+        assert_eq!(&sfnt[44..46], &[5, 6]);
+        assert_eq!(&sfnt[48..52], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn stream_of_the_wrong_length_is_rejected() {
+        // The directory needs 8 bytes, the stream holds 6
+        assert!(decode_woff2(&woff2(0x0001_0000, 1, &[0x00, 8], &[0; 6])).is_err());
+        // ... or 10
+        assert!(decode_woff2(&woff2(0x0001_0000, 1, &[0x00, 8], &[0; 10])).is_err());
+    }
+
+    #[test]
+    fn glyf_transform_rebuilds_glyf_and_loca() {
+        let (glyf, loca, x_mins) = reconstruct_glyf(&glyf_transform(&[2])).unwrap();
+        assert_eq!(x_mins, vec![5]);
+        // Header: 1 contour, bbox (5, 0, 5, 0), end point 1, no instructions
+        assert_eq!(&glyf[..14], &[0, 1, 0, 5, 0, 0, 0, 5, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(glyf.len() % 4, 0);
+        assert_eq!(
+            loca,
+            [0u32, glyf.len() as u32].map(u32::to_be_bytes).concat()
+        );
+    }
+
+    #[test]
+    fn short_loca_offsets_must_fit() {
+        let mut data = glyf_transform(&[1, 1]);
+        data[7] = 0; // short loca
+        let (glyf, loca, _) = reconstruct_glyf(&data).unwrap();
+        let half = (glyf.len() / 4) as u16;
+        assert_eq!(loca, [0, half, half * 2].map(u16::to_be_bytes).concat());
+
+        // 16-byte glyphs: 8192 of them fill the 128 KiB short offsets can address
+        let mut data = glyf_transform(&[1; 8193]);
+        data[7] = 0;
+        assert!(reconstruct_glyf(&data).is_err());
+        data[7] = 1;
+        assert!(reconstruct_glyf(&data).is_ok());
+    }
+
+    #[test]
+    fn contour_without_points_is_an_error() {
+        assert!(reconstruct_glyf(&glyf_transform(&[0])).is_err());
+    }
+
+    #[test]
+    fn hmtx_transform_restores_side_bearings() {
+        // All lsbs omitted: they come from the glyphs' xMin
+        let hmtx = reconstruct_hmtx(&[0x03, 0x02, 0x58], 1, &[5, -3]).unwrap();
+        assert_eq!(hmtx, vec![0x02, 0x58, 0, 5, 0xFF, 0xFD]);
+        // Only the proportional ones: the monospaced lsb is stored
+        let hmtx = reconstruct_hmtx(&[0x01, 0x02, 0x58, 0, 7], 1, &[5, -3]).unwrap();
+        assert_eq!(hmtx, vec![0x02, 0x58, 0, 5, 0, 7]);
+        assert!(reconstruct_hmtx(&[0x01, 0x02, 0x58], 1, &[]).is_err());
+    }
+
+    #[test]
+    fn collection_fonts_share_a_transformed_glyf() {
+        let glyf = glyf_transform(&[1]);
+        let hmtx_a = [0x01, 0x02, 0x58]; // advance 600
+        let hmtx_b = [0x01, 0x01, 0xF4]; // advance 500
+
+        // hhea, glyf and loca (transformed), one hmtx (transformed) per font
+        let mut directory = vec![0x02, 36];
+        directory.extend_from_slice(&[0x0A, 20, glyf.len() as u8]);
+        directory.extend_from_slice(&[0x0B, 8, 0]);
+        directory.extend_from_slice(&[0x43, 4, 3]);
+        directory.extend_from_slice(&[0x43, 4, 3]);
+        directory.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        directory.push(2);
+        for hmtx in [3, 4] {
+            directory.push(4);
+            directory.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            directory.extend_from_slice(&[0, 1, 2, hmtx]);
+        }
+        let tables = [hhea(), glyf, hmtx_a.to_vec(), hmtx_b.to_vec()].concat();
+
+        let ttc = decode_woff2(&woff2(TTCF, 5, &directory, &tables)).unwrap();
+        assert_eq!(ttc_table(&ttc, 0, b"hmtx"), vec![0x02, 0x58, 0, 5]);
+        assert_eq!(ttc_table(&ttc, 1, b"hmtx"), vec![0x01, 0xF4, 0, 5]);
+        assert_eq!(ttc_table(&ttc, 0, b"glyf"), ttc_table(&ttc, 1, b"glyf"));
+    }
+}
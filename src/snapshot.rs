@@ -0,0 +1,403 @@
+//! Inventory snapshots and the differences between two of them
+//!
+//! A `Snapshot` is a compact, versioned record of an enumeration result:
+//! for each face its identity (PostScript name, family, style), where it
+//! lives, its version string, weight/width/italic, variation axes and a
+//! hash of the font file. Snapshots taken on two machines (or at two
+//! points in time) are compared with `diff_snapshots`, which matches faces
+//! by identity and reports additions, removals and changed fields grouped
+//! by family. Both snapshots and diffs serialize to JSON.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::export::{host_name, utc_timestamp};
+use crate::{EnumMode, Error, FontInfo, NameId, Result};
+
+/// Version of the snapshot format, bumped on incompatible changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// An enumeration result reduced to what `diff_snapshots` compares
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32, // SNAPSHOT_VERSION of the writer
+    pub source: EnumMode,
+    pub timestamp: String, // UTC, RFC 3339
+    pub host: String,
+    pub faces: Vec<FaceRecord>, // Sorted by family, style and location
+}
+
+/// One face of a snapshot
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaceRecord {
+    pub family: String,
+    pub style: String,
+    pub postscript_name: Option<String>, // Name ID 6 (Directory only)
+    pub version: Option<String>,         // Name ID 5, e.g. "Version 2.37"
+    pub location: String,                // File path, `#index` for collection faces
+    pub weight: i32,
+    pub width: i32,
+    pub italic: bool,
+    pub axes: String,         // e.g. "wght 100-900, wdth 75-100"
+    pub hash: Option<String>, // FNV-1a 64 of the font file, hex
+}
+
+impl FaceRecord {
+    /// What identifies the face across snapshots: the PostScript name if
+    /// known, else family and style
+    pub fn key(&self) -> String {
+        match &self.postscript_name {
+            Some(name) => name.clone(),
+            None => format!("{} {}", self.family, self.style),
+        }
+    }
+
+    /// The compared fields as `(name, value)` pairs
+    fn fields(&self) -> [(&'static str, String); 7] {
+        [
+            ("version", self.version.clone().unwrap_or_default()),
+            ("location", self.location.clone()),
+            ("weight", self.weight.to_string()),
+            ("width", self.width.to_string()),
+            ("italic", self.italic.to_string()),
+            ("axes", self.axes.clone()),
+            ("hash", self.hash.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+impl Snapshot {
+    /// Records fonts enumerated by `source` now, on this machine
+    ///
+    /// Every font file is read once to hash it; files that can't be read
+    /// (or API faces without a path) get no hash.
+    pub fn new(source: EnumMode, fonts: &[FontInfo]) -> Self {
+        let mut hashes: HashMap<&str, Option<String>> = HashMap::new();
+        let mut faces: Vec<FaceRecord> = fonts
+            .iter()
+            .map(|font| {
+                let hash = hashes
+                    .entry(font.file_path.as_str())
+                    .or_insert_with(|| file_hash(&font.file_path))
+                    .clone();
+                FaceRecord {
+                    family: font.family_name.clone(),
+                    style: font.style_name.clone(),
+                    postscript_name: font.names.best(NameId::POSTSCRIPT_NAME).map(str::to_string),
+                    version: font.names.best(NameId::VERSION).map(str::to_string),
+                    location: font.location(),
+                    weight: font.weight,
+                    width: font.width,
+                    italic: font.italic,
+                    axes: font.variable_axes_string(),
+                    hash,
+                }
+            })
+            .collect();
+        faces.sort_by(|a, b| {
+            (&a.family, &a.style, &a.location).cmp(&(&b.family, &b.style, &b.location))
+        });
+
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            source,
+            timestamp: utc_timestamp(SystemTime::now()),
+            host: host_name(),
+            faces,
+        }
+    }
+
+    /// Reads a snapshot written by `to_json`
+    pub fn from_json(text: &str) -> Result<Self> {
+        let snapshot: Snapshot = serde_json::from_str(text)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(Error::Unsupported(format!(
+                "snapshot format version {} (this build reads up to {})",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Serializes the snapshot as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// A field whose value differs between the two snapshots
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String, // "version", "location", "weight", "width", "italic", "axes" or "hash"
+    pub old: String,
+    pub new: String,
+}
+
+/// A face present in both snapshots with different fields
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaceChange {
+    pub key: String,
+    pub style: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// The differences within one family
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FamilyDiff {
+    pub family: String,
+    pub added: Vec<FaceRecord>,
+    pub removed: Vec<FaceRecord>,
+    pub changed: Vec<FaceChange>,
+}
+
+/// Everything that differs between two snapshots, by family
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub old_host: String,
+    pub old_timestamp: String,
+    pub new_host: String,
+    pub new_timestamp: String,
+    pub families: Vec<FamilyDiff>, // Only families with differences, sorted by name
+}
+
+impl SnapshotDiff {
+    /// True if both snapshots hold the same faces with the same fields
+    pub fn is_empty(&self) -> bool {
+        self.families.is_empty()
+    }
+
+    /// Numbers of faces added, removed and changed
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.families.iter().fold((0, 0, 0), |(a, r, c), f| {
+            (a + f.added.len(), r + f.removed.len(), c + f.changed.len())
+        })
+    }
+}
+
+/// Compares two snapshots
+///
+/// Faces are matched by `FaceRecord::key`. When several faces share a key
+/// (e.g. a .ttf and its .woff2 copy), those at the same location are
+/// paired first and the rest in order.
+pub fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> SnapshotDiff {
+    let mut by_key: BTreeMap<String, (Vec<&FaceRecord>, Vec<&FaceRecord>)> = BTreeMap::new();
+    for face in &old.faces {
+        by_key.entry(face.key()).or_default().0.push(face);
+    }
+    for face in &new.faces {
+        by_key.entry(face.key()).or_default().1.push(face);
+    }
+
+    let mut families: BTreeMap<String, FamilyDiff> = BTreeMap::new();
+
+    for (key, (mut olds, mut news)) in by_key {
+        let mut pairs = Vec::new();
+        olds.retain(
+            |o| match news.iter().position(|n| n.location == o.location) {
+                Some(i) => {
+                    pairs.push((*o, news.remove(i)));
+                    false
+                }
+                None => true,
+            },
+        );
+        let unpaired = olds.len().min(news.len());
+        pairs.extend(olds.drain(..unpaired).zip(news.drain(..unpaired)));
+
+        for (o, n) in pairs {
+            let changes: Vec<FieldChange> = o
+                .fields()
+                .into_iter()
+                .zip(n.fields())
+                .filter(|((_, a), (_, b))| a != b)
+                .map(|((field, old), (_, new))| FieldChange {
+                    field: field.to_string(),
+                    old,
+                    new,
+                })
+                .collect();
+            if !changes.is_empty() {
+                family_diff(&mut families, &n.family)
+                    .changed
+                    .push(FaceChange {
+                        key: key.clone(),
+                        style: n.style.clone(),
+                        changes,
+                    });
+            }
+        }
+        for o in olds {
+            family_diff(&mut families, &o.family)
+                .removed
+                .push(o.clone());
+        }
+        for n in news {
+            family_diff(&mut families, &n.family).added.push(n.clone());
+        }
+    }
+
+    SnapshotDiff {
+        old_host: old.host.clone(),
+        old_timestamp: old.timestamp.clone(),
+        new_host: new.host.clone(),
+        new_timestamp: new.timestamp.clone(),
+        families: families.into_values().collect(),
+    }
+}
+
+/// The entry of a family in the diff, created on first use
+fn family_diff<'a>(
+    families: &'a mut BTreeMap<String, FamilyDiff>,
+    family: &str,
+) -> &'a mut FamilyDiff {
+    families
+        .entry(family.to_string())
+        .or_insert_with(|| FamilyDiff {
+            family: family.to_string(),
+            ..FamilyDiff::default()
+        })
+}
+
+/// FNV-1a 64 of a file's bytes as 16 hex digits; None if it can't be read
+fn file_hash(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    let data = fs::read(path).ok()?;
+    let hash = data.iter().fold(0xCBF2_9CE4_8422_2325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    });
+    Some(format!("{:016x}", hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(postscript_name: &str, family: &str, style: &str, location: &str) -> FaceRecord {
+        FaceRecord {
+            family: family.to_string(),
+            style: style.to_string(),
+            postscript_name: Some(postscript_name.to_string()).filter(|n| !n.is_empty()),
+            version: Some("Version 1.00".to_string()),
+            location: location.to_string(),
+            weight: 400,
+            width: 5,
+            italic: false,
+            axes: String::new(),
+            hash: Some("0123456789abcdef".to_string()),
+        }
+    }
+
+    fn snapshot(host: &str, faces: Vec<FaceRecord>) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            source: EnumMode::Directory,
+            timestamp: "2024-05-01T12:00:00Z".to_string(),
+            host: host.to_string(),
+            faces,
+        }
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_differences() {
+        let faces = vec![record("Arial-Regular", "Arial", "Regular", "arial.ttf")];
+        let diff = diff_snapshots(&snapshot("a", faces.clone()), &snapshot("b", faces));
+        assert!(diff.is_empty());
+        assert_eq!((diff.old_host.as_str(), diff.new_host.as_str()), ("a", "b"));
+    }
+
+    #[test]
+    fn additions_removals_and_changes_by_family() {
+        let old = snapshot(
+            "a",
+            vec![
+                record("Arial-Regular", "Arial", "Regular", "arial.ttf"),
+                record("Arial-Bold", "Arial", "Bold", "arialbd.ttf"),
+                record("", "Courier", "Regular", "cour.fon"),
+            ],
+        );
+        let mut updated = record("Arial-Regular", "Arial", "Regular", "fonts/arial.ttf");
+        updated.version = Some("Version 2.00".to_string());
+        let new = snapshot(
+            "b",
+            vec![
+                updated,
+                record("Verdana", "Verdana", "Regular", "verdana.ttf"),
+                // Matched by family and style without a PostScript name
+                record("", "Courier", "Regular", "cour.fon"),
+            ],
+        );
+
+        let diff = diff_snapshots(&old, &new);
+        assert_eq!(diff.counts(), (1, 1, 1));
+        let families: Vec<&str> = diff.families.iter().map(|f| f.family.as_str()).collect();
+        assert_eq!(families, ["Arial", "Verdana"]);
+
+        let arial = &diff.families[0];
+        assert_eq!(arial.removed[0].key(), "Arial-Bold");
+        assert_eq!(arial.changed[0].key, "Arial-Regular");
+        let changes: Vec<(&str, &str, &str)> = arial.changed[0]
+            .changes
+            .iter()
+            .map(|c| (c.field.as_str(), c.old.as_str(), c.new.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("version", "Version 1.00", "Version 2.00"),
+                ("location", "arial.ttf", "fonts/arial.ttf")
+            ]
+        );
+        assert_eq!(diff.families[1].added[0].key(), "Verdana");
+    }
+
+    #[test]
+    fn copies_sharing_a_key_pair_by_location() {
+        let old = snapshot(
+            "a",
+            vec![
+                record("Inter-Regular", "Inter", "Regular", "Inter.ttf"),
+                record("Inter-Regular", "Inter", "Regular", "Inter.woff2"),
+            ],
+        );
+        let mut woff2 = record("Inter-Regular", "Inter", "Regular", "Inter.woff2");
+        woff2.hash = Some("fedcba9876543210".to_string());
+        let new = snapshot("b", vec![woff2]);
+
+        let diff = diff_snapshots(&old, &new);
+        let inter = &diff.families[0];
+        assert_eq!(inter.removed[0].location, "Inter.ttf");
+        assert_eq!(inter.changed[0].changes[0].field, "hash");
+    }
+
+    #[test]
+    fn json_round_trip_and_newer_versions() {
+        let snapshot = snapshot("a", vec![record("A-Regular", "A", "Regular", "a.ttf")]);
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(Snapshot::from_json(&json).unwrap(), snapshot);
+
+        let newer = json.replacen(
+            &format!("\"version\": {}", SNAPSHOT_VERSION),
+            &format!("\"version\": {}", SNAPSHOT_VERSION + 1),
+            1,
+        );
+        assert!(matches!(
+            Snapshot::from_json(&newer),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn file_hashes() {
+        let path = std::env::temp_dir().join(format!("fontenum-hash-{}", std::process::id()));
+        fs::write(&path, b"a").unwrap();
+        let hash = file_hash(&path.to_string_lossy());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(hash.as_deref(), Some("af63dc4c8601ec8c"));
+        assert_eq!(file_hash(""), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Directory scanning - parses font files directly, on any platform
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{sort_by_family_and_style, EnumMode, FontSource};
+use crate::{sfnt, FontInfo, Result};
+
+/// File extensions recognized as font files (compared case-insensitively)
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc", "woff", "woff2"];
+
+/// Enumerates fonts by walking directories and parsing the files found
+///
+/// Every field of `FontInfo` is read from the sfnt tables themselves
+/// (name, OS/2, head, post, fvar), so the same inventory can be produced on
+/// any platform. WOFF and WOFF2 web fonts are decoded and listed too.
+/// Subdirectories are scanned recursively; files that cannot be parsed are
+/// skipped.
+#[derive(Clone, Debug, Default)]
+pub struct DirectorySource {
+    dirs: Vec<PathBuf>,
+}
+
+impl DirectorySource {
+    /// Creates a source that scans the given directories
+    pub fn new<I, P>(dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        DirectorySource {
+            dirs: dirs.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Creates a source over the platform's standard font directories
+    ///
+    /// Directories that do not exist on this machine are left out.
+    pub fn system() -> Self {
+        let dirs = system_font_dirs()
+            .into_iter()
+            .filter(|d| d.is_dir())
+            .collect();
+        DirectorySource { dirs }
+    }
+
+    /// The directories this source scans
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+}
+
+impl FontSource for DirectorySource {
+    fn mode(&self) -> EnumMode {
+        EnumMode::Directory
+    }
+
+    fn enumerate(&self) -> Result<Vec<FontInfo>> {
+        let mut fonts = Vec::new();
+        for path in &font_files(&self.dirs)? {
+            if let Ok(faces) = load_font_file(path) {
+                fonts.extend(faces);
+            }
+        }
+
+        sort_by_family_and_style(&mut fonts);
+        Ok(fonts)
+    }
+}
+
+/// Reads and parses a single font file, returning one `FontInfo` per face
+pub fn load_font_file(path: &Path) -> Result<Vec<FontInfo>> {
+    let data = fs::read(path)?;
+    sfnt::read_fonts(&data, &path.to_string_lossy())
+}
+
+/// Every font file below the given directories, sorted and without duplicates
+///
+/// A missing root is an error; unreadable subdirectories are skipped.
+pub(crate) fn font_files(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        fs::read_dir(dir)?;
+        collect_font_files(dir, &mut files);
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Recursively collects font files below `dir`
+///
+/// Symlinks to directories are not followed, so links back up the tree
+/// can't loop; symlinked font files are collected.
+fn collect_font_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // file_type() describes the entry itself, not a symlink's target
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            collect_font_files(&path, files);
+        } else if is_font_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// True if the path has one of the recognized font extensions
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| FONT_EXTENSIONS.iter().any(|f| e.eq_ignore_ascii_case(f)))
+        .unwrap_or(false)
+}
+
+/// Standard font directories for the current platform
+fn system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if cfg!(windows) {
+        let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        dirs.push(Path::new(&windir).join("Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(
+                Path::new(&local)
+                    .join("Microsoft")
+                    .join("Windows")
+                    .join("Fonts"),
+            );
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).join("Library").join("Fonts"));
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).join(".local").join("share").join("fonts"));
+            dirs.push(Path::new(&home).join(".fonts"));
+        }
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_are_not_followed() {
+        let root = std::env::temp_dir().join(format!("fontenum-{}", std::process::id()));
+        let fonts = root.join("fonts");
+        fs::create_dir_all(&fonts).unwrap();
+        fs::write(fonts.join("a.ttf"), b"").unwrap();
+        fs::write(fonts.join("notes.txt"), b"").unwrap();
+        std::os::unix::fs::symlink(&root, fonts.join("loop")).unwrap();
+        std::os::unix::fs::symlink(fonts.join("a.ttf"), fonts.join("b.TTF")).unwrap();
+
+        let files = font_files(std::slice::from_ref(&root));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(files.unwrap(), [fonts.join("a.ttf"), fonts.join("b.TTF")]);
+    }
+}
@@ -0,0 +1,231 @@
+//! DirectWrite font enumeration (IDWriteFontCollection)
+
+use std::ffi::c_void;
+use windows::{core::*, Win32::Foundation::BOOL, Win32::Graphics::DirectWrite::*};
+
+use super::dwrite_strings::{get_face_names, get_family_names};
+use super::{sort_by_family_and_style, EnumMode, FontSource};
+use crate::sfnt::color::{self, COLR_HEADER_LEN, CPAL_HEADER_LEN};
+use crate::sfnt::layout::{GPOS, GSUB};
+use crate::sfnt::monospace::HMTX;
+use crate::sfnt::{head, outline};
+use crate::{
+    ColorFormats, Coverage, Error, FontInfo, Layout, Metrics, Monospace, OutlineFormat, Tag,
+};
+
+/// Enumerates fonts using the DirectWrite IDWriteFontCollection API
+///
+/// DirectWrite provides better support for:
+/// - OpenType features
+/// - Complex script shaping
+/// - Font fallback
+/// - Accurate style names
+///
+/// Available on Windows Vista and later.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DirectWriteSource;
+
+impl FontSource for DirectWriteSource {
+    fn mode(&self) -> EnumMode {
+        EnumMode::DirectWrite
+    }
+
+    fn enumerate(&self) -> crate::Result<Vec<FontInfo>> {
+        unsafe {
+            let mut fonts: Vec<FontInfo> = Vec::new();
+
+            // Create DirectWrite factory
+            let factory: IDWriteFactory = DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)?;
+
+            // Get the system font collection
+            let mut collection: Option<IDWriteFontCollection> = None;
+            factory.GetSystemFontCollection(&mut collection, false)?;
+            let collection = collection
+                .ok_or_else(|| Error::Unsupported("system font collection".to_string()))?;
+
+            let family_count = collection.GetFontFamilyCount();
+
+            // Iterate through each font family
+            for i in 0..family_count {
+                if let Ok(family) = collection.GetFontFamily(i) {
+                    let family_name = get_family_names(&family);
+
+                    // Each family can contain multiple fonts (Regular, Bold, Italic, etc.)
+                    let font_count = family.GetFontCount();
+                    for j in 0..font_count {
+                        if let Ok(font) = family.GetFont(j) {
+                            let style_name = get_face_names(&font);
+
+                            // Check if font is monospaced (requires IDWriteFont1)
+                            let is_mono = font
+                                .cast::<IDWriteFont1>()
+                                .map(|f1| f1.IsMonospacedFont().as_bool())
+                                .unwrap_or(false);
+
+                            let face1 = font
+                                .CreateFontFace()
+                                .and_then(|face| face.cast::<IDWriteFontFace1>())
+                                .ok();
+
+                            let monospace = face1
+                                .as_ref()
+                                .map(|f| get_monospace(f, Some(is_mono)))
+                                .unwrap_or(Monospace {
+                                    api: Some(is_mono),
+                                    ..Default::default()
+                                });
+
+                            fonts.push(FontInfo {
+                                family_name: family_name.clone(),
+                                style_name,
+                                weight: font.GetWeight().0,
+                                width: font.GetStretch().0,
+                                italic: font.GetStyle() != DWRITE_FONT_STYLE_NORMAL,
+                                fixed_pitch: monospace.is_monospace(),
+                                monospace,
+                                coverage: face1
+                                    .as_ref()
+                                    .map(|f| get_coverage(f))
+                                    .unwrap_or_default(),
+                                metrics: face1.as_ref().map(|f| get_metrics(f)),
+                                color: face1
+                                    .as_ref()
+                                    .map(|f| get_color_formats(f))
+                                    .unwrap_or_default(),
+                                outline: face1.as_ref().and_then(|f| get_outline_format(f)),
+                                glyph_count: face1.as_ref().map(|f| f.GetGlyphCount()),
+                                layout: face1.as_ref().map(|f| get_layout(f)).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Sort by family name, then by style name
+            sort_by_family_and_style(&mut fonts);
+
+            Ok(fonts)
+        }
+    }
+}
+
+/// Reads the Unicode ranges a font face maps (IDWriteFontFace1::GetUnicodeRanges)
+pub(crate) unsafe fn get_coverage(face: &IDWriteFontFace1) -> Coverage {
+    // First call with no buffer returns E_NOT_SUFFICIENT_BUFFER and the count
+    let mut count: u32 = 0;
+    let _ = face.GetUnicodeRanges(None, &mut count);
+    if count == 0 {
+        return Coverage::default();
+    }
+
+    let mut ranges = vec![DWRITE_UNICODE_RANGE::default(); count as usize];
+    if face
+        .GetUnicodeRanges(Some(&mut ranges), &mut count)
+        .is_err()
+    {
+        return Coverage::default();
+    }
+    Coverage::from_ranges(
+        ranges
+            .iter()
+            .take(count as usize)
+            .map(|r| (r.first, r.last)),
+    )
+}
+
+/// Reads a font face's metrics (IDWriteFontFace1::GetMetrics)
+///
+/// DirectWrite reports the ascent/descent it lays text out with (the OS/2
+/// win or typo values) rather than hhea, and has no italic angle; the typo
+/// and win fields are left empty.
+pub(crate) unsafe fn get_metrics(face: &IDWriteFontFace1) -> Metrics {
+    let mut m = DWRITE_FONT_METRICS1::default();
+    face.GetMetrics(&mut m);
+    Metrics {
+        units_per_em: m.Base.designUnitsPerEm,
+        ascender: design_units(m.Base.ascent),
+        descender: -design_units(m.Base.descent),
+        line_gap: m.Base.lineGap,
+        x_height: Some(design_units(m.Base.xHeight)),
+        cap_height: Some(design_units(m.Base.capHeight)),
+        underline_position: m.Base.underlinePosition,
+        underline_thickness: design_units(m.Base.underlineThickness),
+        y_max: Some(m.glyphBoxTop),
+        y_min: Some(m.glyphBoxBottom),
+        ..Default::default()
+    }
+}
+
+/// An unsigned DirectWrite metric as the signed value the sfnt tables use,
+/// clamped rather than wrapped
+fn design_units(value: u16) -> i16 {
+    i16::try_from(value).unwrap_or(i16::MAX)
+}
+
+/// Detects color glyph support from the face's COLR, CPAL, SVG, sbix and CBDT tables
+pub(crate) unsafe fn get_color_formats(face: &IDWriteFontFace) -> ColorFormats {
+    let colr = get_table_prefix(face, color::COLR, COLR_HEADER_LEN);
+    let cpal = get_table_prefix(face, color::CPAL, CPAL_HEADER_LEN);
+    let has = |tag| get_table_prefix(face, tag, 0).is_some();
+    ColorFormats::from_tables(colr.as_deref(), cpal.as_deref(), has).unwrap_or_default()
+}
+
+/// Detects the outline format from which of glyf (with outlines), CFF, CFF2 and the bitmap tables the face has
+pub(crate) unsafe fn get_outline_format(face: &IDWriteFontFace) -> Option<OutlineFormat> {
+    OutlineFormat::detect(
+        |tag| get_table_prefix(face, tag, 0).is_some(),
+        || {
+            let head = get_table_prefix(face, head::TAG, outline::INDEX_TO_LOC_FORMAT + 2);
+            let loca = get_table_prefix(face, outline::LOCA, usize::MAX);
+            outline::has_glyf_outlines(head.as_deref(), loca.as_deref())
+        },
+    )
+}
+
+/// Lists the scripts, languages and features of the face's GSUB and GPOS tables
+pub(crate) unsafe fn get_layout(face: &IDWriteFontFace) -> Layout {
+    let gsub = get_table_prefix(face, GSUB, usize::MAX);
+    let gpos = get_table_prefix(face, GPOS, usize::MAX);
+    Layout::from_tables(gsub.as_deref(), gpos.as_deref())
+}
+
+/// Collects the monospace signals from the face's hhea, maxp, hmtx, post and OS/2 tables
+///
+/// `api` is the system's own answer (IsMonospacedFont), where available.
+pub(crate) unsafe fn get_monospace(face: &IDWriteFontFace, api: Option<bool>) -> Monospace {
+    let table = |tag: &[u8; 4]| get_table_prefix(face, Tag::new(tag), usize::MAX);
+    let monospace = Monospace::from_raw_tables(
+        table(b"hhea").as_deref(),
+        table(b"maxp").as_deref(),
+        get_table_prefix(face, HMTX, usize::MAX).as_deref(),
+        table(b"post").as_deref(),
+        table(b"OS/2").as_deref(),
+    );
+    Monospace { api, ..monospace }
+}
+
+/// Copies up to `len` bytes from the start of a font table, if the face has it
+unsafe fn get_table_prefix(face: &IDWriteFontFace, tag: Tag, len: usize) -> Option<Vec<u8>> {
+    let mut data: *mut c_void = std::ptr::null_mut();
+    let mut size: u32 = 0;
+    let mut context: *mut c_void = std::ptr::null_mut();
+    let mut exists = BOOL::default();
+
+    // DirectWrite tags are the 4 tag bytes read little-endian (DWRITE_MAKE_OPENTYPE_TAG)
+    face.TryGetFontTable(
+        u32::from_le_bytes(tag.0),
+        &mut data,
+        &mut size,
+        &mut context,
+        &mut exists,
+    )
+    .ok()?;
+    if !exists.as_bool() {
+        return None;
+    }
+    let len = len.min(size as usize);
+    let bytes = std::slice::from_raw_parts(data as *const u8, len).to_vec();
+    face.ReleaseFontTable(context);
+    Some(bytes)
+}
@@ -0,0 +1,62 @@
+//! DirectWrite string extraction utilities
+
+use windows::{core::*, Win32::Foundation::*, Win32::Graphics::DirectWrite::*};
+
+/// Extracts the family name from a DirectWrite font family
+pub(crate) fn get_family_names(family: &IDWriteFontFamily) -> String {
+    unsafe {
+        if let Ok(names) = family.GetFamilyNames() {
+            return get_string_from_localized(&names);
+        }
+        String::new()
+    }
+}
+
+/// Extracts the face/style name from a DirectWrite font
+pub(crate) fn get_face_names(font: &IDWriteFont) -> String {
+    unsafe {
+        if let Ok(names) = font.GetFaceNames() {
+            return get_string_from_localized(&names);
+        }
+        String::new()
+    }
+}
+
+/// Extracts a string from IDWriteLocalizedStrings, preferring English
+pub(crate) fn get_string_from_localized(strings: &IDWriteLocalizedStrings) -> String {
+    unsafe {
+        let mut index: u32 = 0;
+        let mut exists = BOOL::default();
+
+        // Try to find English (US) version first
+        let _ = strings.FindLocaleName(w!("en-us"), &mut index, &mut exists);
+        if !exists.as_bool() {
+            index = 0; // Fall back to first available
+        }
+
+        if let Ok(length) = strings.GetStringLength(index) {
+            let mut buffer = vec![0u16; (length + 1) as usize];
+            if strings.GetString(index, &mut buffer).is_ok() {
+                return String::from_utf16_lossy(&buffer)
+                    .trim_end_matches('\0')
+                    .to_string();
+            }
+        }
+        String::new()
+    }
+}
+
+/// Extracts a string from IDWriteStringList by index
+pub(crate) fn get_string_from_string_list(strings: &IDWriteStringList, index: u32) -> String {
+    unsafe {
+        if let Ok(length) = strings.GetStringLength(index) {
+            let mut buffer = vec![0u16; (length + 1) as usize];
+            if strings.GetString(index, &mut buffer).is_ok() {
+                return String::from_utf16_lossy(&buffer)
+                    .trim_end_matches('\0')
+                    .to_string();
+            }
+        }
+        String::new()
+    }
+}
@@ -0,0 +1,224 @@
+//! DirectWrite FontSet enumeration (IDWriteFontSet, Windows 10+)
+
+use std::ffi::c_void;
+use windows::{core::*, Win32::Foundation::BOOL, Win32::Graphics::DirectWrite::*};
+
+use super::directwrite::{
+    get_color_formats, get_coverage, get_layout, get_metrics, get_monospace, get_outline_format,
+};
+use super::dwrite_strings::{get_string_from_localized, get_string_from_string_list};
+use super::{sort_by_family_and_style, EnumMode, FontSource};
+use crate::{Error, FontInfo, Tag, VariationAxis};
+
+/// Enumerates fonts using the DirectWrite IDWriteFontSet API
+///
+/// The FontSet API (Windows 10+) provides access to:
+/// - Font file paths
+/// - Variable font axis information (weight ranges, width ranges, etc.)
+/// - More detailed font properties
+///
+/// This is the most comprehensive font enumeration API available.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FontSetSource;
+
+impl FontSource for FontSetSource {
+    fn mode(&self) -> EnumMode {
+        EnumMode::FontSet
+    }
+
+    fn enumerate(&self) -> crate::Result<Vec<FontInfo>> {
+        unsafe {
+            let mut fonts: Vec<FontInfo> = Vec::new();
+
+            // Create DirectWrite factory (version 3 required for FontSet API)
+            let factory: IDWriteFactory3 = DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)
+                .map_err(|_| {
+                    Error::Unsupported(
+                        "DirectWrite factory 3 (the FontSet API requires Windows 10 or later)"
+                            .to_string(),
+                    )
+                })?;
+
+            // Get the system font set
+            let font_set: IDWriteFontSet = factory.GetSystemFontSet()?;
+
+            let font_count = font_set.GetFontCount();
+
+            // Iterate through each font in the set
+            for i in 0..font_count {
+                let mut info = FontInfo::default();
+
+                // Get font face reference for accessing file info
+                if let Ok(font_ref) = font_set.GetFontFaceReference(i) {
+                    info.file_path = get_file_path(&font_ref);
+                    info.face_index = get_face_index(&font_ref);
+                    read_variable_axes(&font_ref, &mut info);
+                    if let Ok(face) = font_ref.CreateFontFace() {
+                        info.coverage = get_coverage(&face);
+                        info.metrics = Some(get_metrics(&face));
+                        info.color = get_color_formats(&face);
+                        info.outline = get_outline_format(&face);
+                        info.glyph_count = Some(face.GetGlyphCount());
+                        info.layout = get_layout(&face);
+                        info.monospace = get_monospace(&face, None);
+                        info.fixed_pitch = info.monospace.is_monospace();
+                    }
+                }
+
+                // --- Extract font properties from the font set ---
+                if let Ok(prop) = font_set.GetPropertyValues(DWRITE_FONT_PROPERTY_ID_FAMILY_NAME) {
+                    if i < prop.GetCount() {
+                        info.family_name = get_string_from_string_list(&prop, i);
+                    }
+                }
+
+                if let Ok(prop) = font_set.GetPropertyValues(DWRITE_FONT_PROPERTY_ID_FACE_NAME) {
+                    if i < prop.GetCount() {
+                        info.style_name = get_string_from_string_list(&prop, i);
+                    }
+                }
+
+                if let Ok(prop) = font_set.GetPropertyValues(DWRITE_FONT_PROPERTY_ID_WEIGHT) {
+                    if i < prop.GetCount() {
+                        let s = get_string_from_string_list(&prop, i);
+                        info.weight = s.parse().unwrap_or(400);
+                    }
+                }
+
+                if let Ok(prop) = font_set.GetPropertyValues(DWRITE_FONT_PROPERTY_ID_STRETCH) {
+                    if i < prop.GetCount() {
+                        let s = get_string_from_string_list(&prop, i);
+                        info.width = s.parse().unwrap_or(5); // 1-9, same scale as usWidthClass
+                    }
+                }
+
+                if let Ok(prop) = font_set.GetPropertyValues(DWRITE_FONT_PROPERTY_ID_STYLE) {
+                    if i < prop.GetCount() {
+                        let s = get_string_from_string_list(&prop, i);
+                        let style: i32 = s.parse().unwrap_or(0);
+                        info.italic = style != 0; // 0 = normal, 1 = italic, 2 = oblique
+                    }
+                }
+
+                if !info.family_name.is_empty() {
+                    fonts.push(info);
+                }
+            }
+
+            sort_by_family_and_style(&mut fonts);
+
+            Ok(fonts)
+        }
+    }
+}
+
+/// Extracts the file path of a font face reference
+///
+/// Only fonts loaded through the local file loader have a path;
+/// returns an empty string otherwise.
+unsafe fn get_file_path(font_ref: &IDWriteFontFaceReference) -> String {
+    if let Ok(font_file) = font_ref.GetFontFile() {
+        if let Ok(loader) = font_file.GetLoader() {
+            // Only local fonts have file paths
+            if let Ok(local_loader) = loader.cast::<IDWriteLocalFontFileLoader>() {
+                let mut ref_key: *mut c_void = std::ptr::null_mut();
+                let mut ref_key_size: u32 = 0;
+                if font_file
+                    .GetReferenceKey(&mut ref_key, &mut ref_key_size)
+                    .is_ok()
+                {
+                    if let Ok(path_len) =
+                        local_loader.GetFilePathLengthFromKey(ref_key, ref_key_size)
+                    {
+                        let mut path_buf = vec![0u16; (path_len + 1) as usize];
+                        if local_loader
+                            .GetFilePathFromKey(ref_key, ref_key_size, &mut path_buf)
+                            .is_ok()
+                        {
+                            return String::from_utf16_lossy(&path_buf)
+                                .trim_end_matches('\0')
+                                .to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    String::new()
+}
+
+/// Returns the face index for members of a .ttc/.otc collection
+///
+/// Single-face files return None, matching the Directory source.
+unsafe fn get_face_index(font_ref: &IDWriteFontFaceReference) -> Option<u32> {
+    let font_file = font_ref.GetFontFile().ok()?;
+    let mut is_supported = BOOL::default();
+    let mut file_type = DWRITE_FONT_FILE_TYPE::default();
+    let mut face_count: u32 = 0;
+    font_file
+        .Analyze(&mut is_supported, &mut file_type, None, &mut face_count)
+        .ok()?;
+
+    let is_collection = file_type == DWRITE_FONT_FILE_TYPE_OPENTYPE_COLLECTION
+        || file_type == DWRITE_FONT_FILE_TYPE_TRUETYPE_COLLECTION;
+    is_collection.then(|| font_ref.GetFontFaceIndex())
+}
+
+/// Extracts variable font axis information into `info`
+///
+/// Only axes whose range is not a single value are recorded. Named
+/// instances are not exposed by IDWriteFontResource; the Directory source
+/// reads them from the fvar table.
+unsafe fn read_variable_axes(font_ref: &IDWriteFontFaceReference, info: &mut FontInfo) {
+    if let Ok(font_face3) = font_ref.CreateFontFace() {
+        if let Ok(font_face5) = font_face3.cast::<IDWriteFontFace5>() {
+            if let Ok(font_resource) = font_face5.GetFontResource() {
+                let axis_count = font_resource.GetFontAxisCount();
+                if axis_count > 0 {
+                    let mut axis_ranges =
+                        vec![DWRITE_FONT_AXIS_RANGE::default(); axis_count as usize];
+                    let mut axis_defaults =
+                        vec![DWRITE_FONT_AXIS_VALUE::default(); axis_count as usize];
+                    if font_resource.GetFontAxisRanges(&mut axis_ranges).is_err() {
+                        return;
+                    }
+                    let has_defaults = font_resource
+                        .GetDefaultFontAxisValues(&mut axis_defaults)
+                        .is_ok();
+
+                    for (i, range) in axis_ranges.iter().enumerate() {
+                        // Variable axis has different min/max values
+                        if range.minValue == range.maxValue {
+                            continue;
+                        }
+                        // Axis tags are stored little-endian (e.g., "wght", "wdth")
+                        let tag = Tag(range.axisTag.0.to_le_bytes());
+                        let attributes = font_resource.GetFontAxisAttributes(i as u32);
+                        let name = font_resource
+                            .GetAxisNames(i as u32)
+                            .map(|names| get_string_from_localized(&names))
+                            .unwrap_or_default();
+
+                        info.is_variable = true;
+                        info.variable_axes.push(VariationAxis {
+                            tag,
+                            name: if name.is_empty() {
+                                tag.to_string()
+                            } else {
+                                name
+                            },
+                            min_value: range.minValue,
+                            default_value: if has_defaults {
+                                axis_defaults[i].value
+                            } else {
+                                range.minValue
+                            },
+                            max_value: range.maxValue,
+                            hidden: (attributes.0 & DWRITE_FONT_AXIS_ATTRIBUTES_HIDDEN.0) != 0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! GDI font enumeration (EnumFontFamiliesEx)
+
+use windows::Win32::{Foundation::*, Graphics::Gdi::*};
+
+use super::{EnumMode, FontSource};
+use crate::{FontInfo, Monospace, Result};
+
+/// Enumerates fonts using the GDI EnumFontFamiliesEx API
+///
+/// This is the oldest font enumeration API, available on all Windows versions.
+/// Limitations:
+/// - No access to font file paths
+/// - No variable font axis information
+/// - Limited style name accuracy for some fonts
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GdiSource;
+
+impl FontSource for GdiSource {
+    fn mode(&self) -> EnumMode {
+        EnumMode::Gdi
+    }
+
+    fn enumerate(&self) -> Result<Vec<FontInfo>> {
+        unsafe {
+            let mut fonts: Vec<FontInfo> = Vec::new();
+
+            // Enumerate against the screen DC - no window is required
+            let hdc = GetDC(None);
+
+            // Set up LOGFONT to enumerate all fonts (DEFAULT_CHARSET = 1)
+            let mut lf = LOGFONTW {
+                lfCharSet: FONT_CHARSET(1),
+                ..Default::default()
+            };
+
+            // Enumerate all font families
+            let _ = EnumFontFamiliesExW(
+                hdc,
+                &mut lf,
+                Some(enum_font_proc),
+                LPARAM(&mut fonts as *mut _ as isize),
+                0,
+            );
+
+            let _ = ReleaseDC(None, hdc);
+
+            // Sort alphabetically by family name
+            fonts.sort_by(|a, b| a.family_name.cmp(&b.family_name));
+
+            Ok(fonts)
+        }
+    }
+}
+
+/// Callback function for GDI font enumeration
+///
+/// Called once for each font face found by EnumFontFamiliesExW.
+/// Extracts font information and adds unique fonts to the collection.
+unsafe extern "system" fn enum_font_proc(
+    lpelfe: *const LOGFONTW,
+    _lpntme: *const TEXTMETRICW,
+    _font_type: u32,
+    lparam: LPARAM,
+) -> i32 {
+    let fonts = &mut *(lparam.0 as *mut Vec<FontInfo>);
+    let lf = &*lpelfe;
+    let elfex = &*(lpelfe as *const ENUMLOGFONTEXW);
+
+    // Extract font names from wide strings
+    let family_name = String::from_utf16_lossy(&lf.lfFaceName)
+        .trim_end_matches('\0')
+        .to_string();
+    let style_name = String::from_utf16_lossy(&elfex.elfStyle)
+        .trim_end_matches('\0')
+        .to_string();
+
+    // Skip duplicates (same family + style)
+    let exists = fonts
+        .iter()
+        .any(|f| f.family_name == family_name && f.style_name == style_name);
+
+    if !exists {
+        // Check if font is fixed-pitch (monospace)
+        // FIXED_PITCH is value 1 in the low 2 bits of lfPitchAndFamily
+        let pitch_and_family: u8 = std::mem::transmute(lf.lfPitchAndFamily);
+        let is_fixed = (pitch_and_family & 0x03) == 1;
+
+        fonts.push(FontInfo {
+            family_name,
+            style_name,
+            weight: lf.lfWeight,
+            italic: lf.lfItalic != 0,
+            fixed_pitch: is_fixed,
+            monospace: Monospace {
+                api: Some(is_fixed),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+
+    1 // Return 1 to continue enumeration
+}
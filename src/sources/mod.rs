@@ -0,0 +1,123 @@
+//! Font enumeration back ends
+//!
+//! Each back end implements `FontSource`. The Windows sources wrap the
+//! three system APIs and are only compiled on Windows:
+//! - `gdi` - EnumFontFamiliesEx (legacy)
+//! - `directwrite` - IDWriteFontCollection (modern)
+//! - `fontset` - IDWriteFontSet (Windows 10+)
+//!
+//! `directory` parses font files itself and works on every platform.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, FontInfo, Result};
+
+mod directory;
+#[cfg(windows)]
+mod directwrite;
+#[cfg(windows)]
+mod dwrite_strings;
+#[cfg(windows)]
+mod fontset;
+#[cfg(windows)]
+mod gdi;
+
+pub(crate) use directory::font_files;
+pub use directory::{load_font_file, DirectorySource};
+#[cfg(windows)]
+pub use directwrite::DirectWriteSource;
+#[cfg(windows)]
+pub use fontset::FontSetSource;
+#[cfg(windows)]
+pub use gdi::GdiSource;
+
+/// Enumeration mode - identifies which API produced a font list
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnumMode {
+    #[default]
+    None, // No enumeration performed yet
+    Gdi,         // EnumFontFamiliesEx (legacy)
+    DirectWrite, // IDWriteFontCollection (modern)
+    FontSet,     // IDWriteFontSet (Windows 10+)
+    Directory,   // Font files parsed directly (any platform)
+}
+
+impl EnumMode {
+    /// Short display name, as used in the status bar
+    pub fn label(self) -> &'static str {
+        match self {
+            EnumMode::Gdi => "GDI",
+            EnumMode::DirectWrite => "DirectWrite",
+            EnumMode::FontSet => "FontSet",
+            EnumMode::Directory => "Directory",
+            EnumMode::None => "No",
+        }
+    }
+
+    /// Parses a mode name as accepted on the command line
+    ///
+    /// Accepts `gdi`, `directwrite` (or `dwrite`), `fontset` and
+    /// `directory` (or `dir`), case-insensitively.
+    pub fn from_name(name: &str) -> Option<EnumMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "gdi" => Some(EnumMode::Gdi),
+            "directwrite" | "dwrite" => Some(EnumMode::DirectWrite),
+            "fontset" => Some(EnumMode::FontSet),
+            "directory" | "dir" => Some(EnumMode::Directory),
+            _ => None,
+        }
+    }
+}
+
+/// Creates the source for an enumeration mode
+///
+/// `dirs` is only used by `EnumMode::Directory`; when empty, the platform's
+/// standard font directories are scanned. The Windows API modes return
+/// `Error::Unsupported` on other platforms.
+pub fn open_source(mode: EnumMode, dirs: &[PathBuf]) -> Result<Box<dyn FontSource>> {
+    match mode {
+        #[cfg(windows)]
+        EnumMode::Gdi => Ok(Box::new(GdiSource)),
+        #[cfg(windows)]
+        EnumMode::DirectWrite => Ok(Box::new(DirectWriteSource)),
+        #[cfg(windows)]
+        EnumMode::FontSet => Ok(Box::new(FontSetSource)),
+        EnumMode::Directory if dirs.is_empty() => Ok(Box::new(DirectorySource::system())),
+        EnumMode::Directory => Ok(Box::new(DirectorySource::new(dirs.iter().cloned()))),
+        other => Err(Error::Unsupported(format!(
+            "{} enumeration on this platform",
+            other.label()
+        ))),
+    }
+}
+
+/// A back end that can enumerate font faces
+///
+/// Implementations return fonts already sorted for display.
+pub trait FontSource {
+    /// Which enumeration mode this source represents
+    fn mode(&self) -> EnumMode;
+
+    /// Enumerates all fonts visible to this source
+    fn enumerate(&self) -> Result<Vec<FontInfo>>;
+
+    /// Enumerates fonts as an iterator
+    ///
+    /// The default implementation collects `enumerate()` first; sources
+    /// that can produce fonts lazily may override it.
+    fn iter(&self) -> Result<Box<dyn Iterator<Item = FontInfo> + '_>> {
+        Ok(Box::new(self.enumerate()?.into_iter()))
+    }
+}
+
+/// Sorts fonts by family name, then by style name
+pub(crate) fn sort_by_family_and_style(fonts: &mut [FontInfo]) {
+    fonts.sort_by(|a, b| {
+        a.family_name
+            .cmp(&b.family_name)
+            .then(a.style_name.cmp(&b.style_name))
+    });
+}
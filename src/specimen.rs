@@ -0,0 +1,500 @@
+//! Self-contained HTML specimen of an inventory
+//!
+//! One page lists every family with a section per face: sample lines at
+//! several sizes, a metadata table, the variation axes with a sample per
+//! named instance, and a summary of the character coverage. Each file is
+//! loaded with its own `@font-face` rule, shared by the rows of its named
+//! instances, so the page shows the exact file rather than whatever the
+//! browser resolves the family name to:
+//! - `SpecimenFonts::Link` points at the files with `file://` URLs
+//!   (small, but only works on the machine with the fonts)
+//! - `SpecimenFonts::Inline` embeds each file once as a base64 `data:`
+//!   URL, so the page opens offline anywhere
+//!
+//! Faces without a file path (GDI, DirectWrite) fall back to `local()`, as
+//! do faces after the first of a collection: browsers only load face 0 of
+//! a .ttc, so a URL would show the wrong face.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::SystemTime;
+
+use crate::export::{host_name, utc_timestamp};
+use crate::fontface::{css_string, format_hint};
+use crate::{Column, Container, FontInfo, NameId, NamedInstance, Result};
+
+/// How the specimen references the font files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpecimenFonts {
+    #[default]
+    Link, // file:// URLs
+    Inline, // base64 data: URLs
+}
+
+/// Default sample text for fonts with Latin letters
+const PANGRAM: &str = "The quick brown fox jumps over the lazy dog";
+
+/// Sample sizes in CSS pixels
+const SIZES: &[u32] = &[12, 18, 24, 36, 48];
+
+/// Metadata rows of each face
+const DETAILS: &[Column] = &[
+    Column::PostScriptName,
+    Column::Version,
+    Column::Weight,
+    Column::Width,
+    Column::Italic,
+    Column::Fixed,
+    Column::Outline,
+    Column::Container,
+    Column::Glyphs,
+    Column::Color,
+    Column::Embedding,
+    Column::FilePath,
+];
+
+/// Writes an HTML page for `fonts`, grouped by family
+///
+/// With `SpecimenFonts::Inline`, font files that can't be read are linked
+/// instead.
+pub fn write_specimen(
+    out: &mut impl Write,
+    fonts: &[FontInfo],
+    embed: SpecimenFonts,
+) -> Result<()> {
+    let mut families: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, font) in fonts.iter().enumerate() {
+        families
+            .entry(font.family_name.as_str())
+            .or_default()
+            .push(i);
+    }
+    for faces in families.values_mut() {
+        faces.sort_by_key(|&i| (fonts[i].width, fonts[i].italic, fonts[i].weight));
+    }
+
+    // One rule per file: the rows of a variable font's named instances (or
+    // a file listed twice) share it, so inline mode embeds each file once
+    let mut sources: HashMap<String, usize> = HashMap::new();
+    let mut rules = Vec::new(); // The first face of each rule
+    let source_of: Vec<usize> = fonts
+        .iter()
+        .enumerate()
+        .map(|(i, font)| {
+            *sources.entry(source_key(font)).or_insert_with(|| {
+                rules.push(i);
+                rules.len() - 1
+            })
+        })
+        .collect();
+
+    // Written as it goes: inlined fonts make for pages of many megabytes
+    write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Font specimen</title>\n<style>\n{}",
+        STYLE
+    )?;
+    for (rule, &i) in rules.iter().enumerate() {
+        writeln!(
+            out,
+            "@font-face {{ font-family: \"file-{}\"; src: {}; }}",
+            rule,
+            font_src(&fonts[i], embed)
+        )?;
+    }
+    out.write_all(b"</style>\n</head>\n<body>\n")?;
+
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<h1>Font specimen</h1>\n<p class=\"meta\">{} faces in {} families, {} on {}</p>",
+        fonts.len(),
+        families.len(),
+        utc_timestamp(SystemTime::now()),
+        escape(&host_name())
+    );
+
+    html.push_str("<nav><ul>\n");
+    for (family, faces) in &families {
+        let _ = writeln!(
+            html,
+            "<li><a href=\"#{}\">{}</a> ({})</li>",
+            anchor(family),
+            escape(family),
+            faces.len()
+        );
+    }
+    html.push_str("</ul></nav>\n");
+    out.write_all(html.as_bytes())?;
+
+    for (family, faces) in &families {
+        html.clear();
+        let _ = writeln!(
+            html,
+            "<section id=\"{}\">\n<h2>{}</h2>",
+            anchor(family),
+            escape(family)
+        );
+        for &i in faces {
+            write_face(&mut html, source_of[i], &fonts[i]);
+        }
+        html.push_str("</section>\n");
+        out.write_all(html.as_bytes())?;
+    }
+    out.write_all(b"</body>\n</html>\n")?;
+    Ok(())
+}
+
+/// One face: heading, samples, metadata, axes and coverage
+///
+/// `rule` is the `@font-face` rule of the face's file.
+fn write_face(html: &mut String, rule: usize, font: &FontInfo) {
+    // The @font-face rule has no weight or style descriptors, so setting
+    // them here would make the browser synthesize bold or oblique
+    let mut style = format!("font-family: &quot;file-{}&quot;", rule);
+    if let Some(instance) = &font.instance {
+        let _ = write!(
+            style,
+            "; font-variation-settings: {}",
+            variation_settings(instance)
+        );
+    }
+    let sample = escape(&sample_text(font));
+
+    let _ = writeln!(html, "<article>\n<h3>{}</h3>", escape(&font.style_name));
+    for size in SIZES {
+        let _ = writeln!(
+            html,
+            "<p class=\"sample\" style=\"{}; font-size: {}px\"><span class=\"size\">{}</span>{}</p>",
+            style, size, size, sample
+        );
+    }
+
+    html.push_str("<table>\n");
+    for column in DETAILS {
+        let value = column.value(font);
+        if !value.is_empty() {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                column.header(),
+                escape(&value)
+            );
+        }
+    }
+    html.push_str("</table>\n");
+
+    let axes: Vec<_> = font
+        .variable_axes
+        .iter()
+        .filter(|a| a.is_variable())
+        .collect();
+    if !axes.is_empty() {
+        html.push_str("<h4>Axes</h4>\n<table>\n");
+        html.push_str(
+            "<tr><th>Tag</th><th>Name</th><th>Min</th><th>Default</th><th>Max</th></tr>\n",
+        );
+        for axis in &axes {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&axis.tag.to_string()),
+                escape(&axis.name),
+                axis.min_value,
+                axis.default_value,
+                axis.max_value
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    for instance in font
+        .named_instances
+        .iter()
+        .filter(|_| font.instance.is_none())
+    {
+        let _ = writeln!(
+            html,
+            "<p class=\"sample\" style=\"{}; font-size: 24px; font-variation-settings: {}\">\
+             <span class=\"size\">{}</span>{}</p>",
+            style,
+            variation_settings(instance),
+            escape(&instance.name),
+            sample
+        );
+    }
+
+    let _ = writeln!(
+        html,
+        "<h4>Coverage</h4>\n<p>{}</p>\n</article>",
+        escape(&coverage_summary(font))
+    );
+}
+
+/// An instance's coordinates as a `font-variation-settings` value, quoted
+/// for a `style` attribute
+fn variation_settings(instance: &NamedInstance) -> String {
+    let settings: Vec<String> = instance
+        .coordinates
+        .iter()
+        .map(|(tag, value)| format!("&quot;{}&quot; {}", escape(&tag.to_string()), value))
+        .collect();
+    settings.join(", ")
+}
+
+/// The font's own sample text (name ID 19), a pangram for Latin fonts, else
+/// the first letters it covers
+fn sample_text(font: &FontInfo) -> String {
+    if let Some(sample) = font.names.best(NameId::SAMPLE_TEXT) {
+        return sample.to_string();
+    }
+    if font.coverage.is_empty() || PANGRAM.chars().all(|c| font.coverage.contains(c)) {
+        return PANGRAM.to_string();
+    }
+    font.coverage
+        .ranges()
+        .iter()
+        .flat_map(|&(first, last)| first..=last)
+        .filter_map(char::from_u32)
+        .filter(|c| c.is_alphanumeric())
+        .take(40)
+        .collect()
+}
+
+/// Character count, scripts with layout rules and OS/2 Unicode ranges
+fn coverage_summary(font: &FontInfo) -> String {
+    if font.coverage.is_empty() {
+        return "Not reported by this source".to_string();
+    }
+    let mut parts = vec![format!("{} characters", font.coverage.len())];
+    let scripts = Column::Scripts.value(font);
+    if !scripts.is_empty() {
+        parts.push(format!("scripts: {}", scripts));
+    }
+    if let Some(os2) = &font.os2 {
+        let ranges = os2.unicode_ranges();
+        if !ranges.is_empty() {
+            parts.push(format!("Unicode ranges: {}", ranges.join(", ")));
+        }
+    }
+    parts.join("; ")
+}
+
+/// True if the face is loaded by name rather than from its file
+fn uses_local(font: &FontInfo) -> bool {
+    font.file_path.is_empty() || font.face_index.is_some_and(|i| i > 0)
+}
+
+/// `local()` with the face's full name
+fn local_src(font: &FontInfo) -> String {
+    let name = font.names.best(NameId::FULL_NAME).map(str::to_string);
+    let name = name.unwrap_or_else(|| format!("{} {}", font.family_name, font.style_name));
+    format!("local(\"{}\")", css_string(&name))
+}
+
+/// What identifies a face's `@font-face` rule: its file, or its `local()`
+fn source_key(font: &FontInfo) -> String {
+    if uses_local(font) {
+        local_src(font)
+    } else {
+        font.file_path.clone()
+    }
+}
+
+/// The `src` descriptor of a face's `@font-face` rule
+fn font_src(font: &FontInfo, embed: SpecimenFonts) -> String {
+    if uses_local(font) {
+        return local_src(font);
+    }
+
+    let format = format_hint(font);
+    if embed == SpecimenFonts::Inline {
+        if let Ok(data) = std::fs::read(&font.file_path) {
+            let mime = match font.container {
+                Container::Sfnt => "font/sfnt",
+                Container::Woff => "font/woff",
+                Container::Woff2 => "font/woff2",
+            };
+            return format!(
+                "url(\"data:{};base64,{}\") format(\"{}\")",
+                mime,
+                base64(&data),
+                format
+            );
+        }
+    }
+    format!(
+        "url(\"{}\") format(\"{}\")",
+        file_url(&font.file_path),
+        format
+    )
+}
+
+/// A `file://` URL for a local path, e.g. `file:///C:/Windows/Fonts/arial.ttf`
+fn file_url(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut url = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    });
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                url.push(b as char)
+            }
+            _ => {
+                let _ = write!(url, "%{:02X}", b);
+            }
+        }
+    }
+    url
+}
+
+/// Standard base64 with padding
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escapes text for HTML element content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// An element id for a family, e.g. `dejavu-sans`
+fn anchor(family: &str) -> String {
+    let id: String = family
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("family-{}", id)
+}
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+nav ul { columns: 3; }
+section { border-top: 2px solid #ccc; margin-top: 2em; }
+article { margin: 1.5em 0 2.5em; }
+.meta { color: #666; }
+.sample { margin: 0.2em 0; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+.size { display: inline-block; width: 4em; font: 11px system-ui, sans-serif; color: #999; }
+table { border-collapse: collapse; font-size: 13px; margin: 0.5em 0; }
+th, td { text-align: left; padding: 2px 12px 2px 0; vertical-align: top; }
+th { color: #666; font-weight: normal; }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font(family: &str, path: &str, face_index: Option<u32>) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            style_name: "Regular".to_string(),
+            file_path: path.to_string(),
+            face_index,
+            weight: 400,
+            width: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn names_cannot_close_the_style_element() {
+        let fonts = [font("</style><script>x</script>", "", None)];
+        let mut out = Vec::new();
+        write_specimen(&mut out, &fonts, SpecimenFonts::Link).unwrap();
+        let html = String::from_utf8(out).unwrap();
+        assert!(!html.contains("</style><script>"));
+        assert!(
+            html.contains("local(\"\\3C /style\\3E \\3C script\\3E x\\3C /script\\3E  Regular\")")
+        );
+    }
+
+    #[test]
+    fn later_collection_faces_use_local() {
+        let first = font("MS Gothic", "C:\\Windows\\Fonts\\msgothic.ttc", Some(0));
+        let second = font("MS PGothic", "C:\\Windows\\Fonts\\msgothic.ttc", Some(1));
+        assert_eq!(
+            font_src(&first, SpecimenFonts::Link),
+            "url(\"file:///C:/Windows/Fonts/msgothic.ttc\") format(\"collection\")"
+        );
+        assert_eq!(
+            font_src(&second, SpecimenFonts::Link),
+            "local(\"MS PGothic Regular\")"
+        );
+    }
+
+    #[test]
+    fn each_file_is_embedded_once() {
+        let path =
+            std::env::temp_dir().join(format!("fontenum-specimen-{}.ttf", std::process::id()));
+        std::fs::write(&path, b"font data").unwrap();
+        let path = path.to_string_lossy();
+        let mut bold = font("Variable", &path, None);
+        bold.instance = Some(NamedInstance {
+            name: "Bold".to_string(),
+            ..Default::default()
+        });
+        let fonts = [font("Variable", &path, None), bold, font("Other", "", None)];
+
+        let mut out = Vec::new();
+        let written = write_specimen(&mut out, &fonts, SpecimenFonts::Inline);
+        std::fs::remove_file(&*path).unwrap();
+        written.unwrap();
+        let html = String::from_utf8(out).unwrap();
+        assert_eq!(html.matches(&base64(b"font data")).count(), 1);
+        assert_eq!(html.matches("@font-face").count(), 2);
+        assert_eq!(
+            html.matches("font-family: &quot;file-0&quot;").count(),
+            2 * SIZES.len()
+        );
+        assert!(html.contains("font-family: &quot;file-1&quot;"));
+    }
+
+    #[test]
+    fn file_urls_and_base64() {
+        assert_eq!(
+            file_url("/usr/share/fonts/a b.ttf"),
+            "file:///usr/share/fonts/a%20b.ttf"
+        );
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
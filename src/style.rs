@@ -0,0 +1,432 @@
+//! Style-linking consistency checks
+//!
+//! GDI and most Windows applications group faces into families of at most
+//! four RIBBI styles (Regular, Italic, Bold, Bold Italic) using the legacy
+//! family name (name ID 1) and the bold/italic bits. When those bits
+//! disagree with the subfamily name, with head.macStyle or with each other,
+//! faces are shown under the wrong style or hidden behind one another.
+//!
+//! - `check_face` compares name ID 2, fsSelection, macStyle, usWeightClass
+//!   and the italic angle of one face
+//! - `check_families` looks for RIBBI slots claimed by more than one face
+//!   and families of several faces without a Regular
+//!
+//! Both need the name, OS/2 and head data only the directory source reads;
+//! faces from other sources produce no findings.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::sfnt::head::{MAC_STYLE_BOLD, MAC_STYLE_ITALIC};
+use crate::validate::{Check, Finding, Severity};
+use crate::{FontInfo, NameId, Tag};
+
+const OS2: Tag = Tag::new(b"OS/2");
+const HEAD: Tag = Tag::new(b"head");
+const NAME: Tag = Tag::new(b"name");
+const POST: Tag = Tag::new(b"post");
+
+/// The bold and italic bits that decide a face's RIBBI slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Slot {
+    bold: bool,
+    italic: bool,
+}
+
+impl Slot {
+    /// The slot from fsSelection, falling back to macStyle without an OS/2 table
+    fn of(font: &FontInfo) -> Option<Slot> {
+        match (&font.os2, font.mac_style) {
+            (Some(os2), _) => Some(Slot {
+                bold: os2.fs_selection.is_bold(),
+                italic: os2.fs_selection.is_italic(),
+            }),
+            (None, Some(mac_style)) => Some(Slot {
+                bold: mac_style & MAC_STYLE_BOLD != 0,
+                italic: mac_style & MAC_STYLE_ITALIC != 0,
+            }),
+            (None, None) => None,
+        }
+    }
+
+    /// The subfamily name that matches these bits
+    fn name(self) -> &'static str {
+        match (self.bold, self.italic) {
+            (false, false) => "Regular",
+            (false, true) => "Italic",
+            (true, false) => "Bold",
+            (true, true) => "Bold Italic",
+        }
+    }
+}
+
+/// Cross-checks the style bits and names of one face
+pub fn check_face(font: &FontInfo) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut warn = |table, message: String| {
+        findings.push(Finding::new(
+            Severity::Warning,
+            Check::StyleLinking,
+            Some(table),
+            message,
+        ));
+    };
+
+    let subfamily = font.names.best(NameId::SUBFAMILY);
+    if let Some(subfamily) = subfamily {
+        if subfamily_slot(subfamily).is_none() {
+            warn(
+                NAME,
+                format!(
+                    "subfamily (ID 2) '{}' is not Regular, Italic, Bold or Bold Italic; \
+                     other styles belong in the family name and IDs 16/17",
+                    subfamily
+                ),
+            );
+        }
+    }
+
+    if let Some(os2) = &font.os2 {
+        let fs = os2.fs_selection;
+        if let Some(named) = subfamily.and_then(subfamily_slot) {
+            let bits = Slot {
+                bold: fs.is_bold(),
+                italic: fs.is_italic(),
+            };
+            if named != bits {
+                warn(
+                    OS2,
+                    format!(
+                        "fsSelection links the face as {} but the subfamily (ID 2) is '{}'",
+                        bits.name(),
+                        subfamily.unwrap_or_default()
+                    ),
+                );
+            }
+        }
+        if fs.is_regular() && (fs.is_bold() || fs.is_italic()) {
+            findings.push(Finding::new(
+                Severity::Error,
+                Check::StyleLinking,
+                Some(OS2),
+                "fsSelection REGULAR is set together with BOLD or ITALIC".to_string(),
+            ));
+        }
+        if fs.is_bold() && os2.weight_class < 600 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Check::StyleLinking,
+                Some(OS2),
+                format!(
+                    "fsSelection BOLD is set but usWeightClass is only {}",
+                    os2.weight_class
+                ),
+            ));
+        }
+        if !(1..=1000).contains(&os2.weight_class) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Check::StyleLinking,
+                Some(OS2),
+                format!("usWeightClass {} is outside 1-1000", os2.weight_class),
+            ));
+        }
+
+        if let Some(mac_style) = font.mac_style {
+            let mac_bold = mac_style & MAC_STYLE_BOLD != 0;
+            let mac_italic = mac_style & MAC_STYLE_ITALIC != 0;
+            if mac_bold != fs.is_bold() {
+                findings.push(mismatch(HEAD, "bold", mac_bold, fs.is_bold()));
+            }
+            if mac_italic != fs.is_italic() {
+                findings.push(mismatch(HEAD, "italic", mac_italic, fs.is_italic()));
+            }
+        }
+    }
+
+    if let (Some(slot), Some(metrics)) = (Slot::of(font), &font.metrics) {
+        let slanted = slot.italic
+            || font
+                .os2
+                .as_ref()
+                .map(|o| o.fs_selection.is_oblique())
+                .unwrap_or(false);
+        if metrics.italic_angle != 0.0 && !slanted {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Check::StyleLinking,
+                Some(POST),
+                format!(
+                    "italicAngle is {} but the face is not italic",
+                    metrics.italic_angle
+                ),
+            ));
+        } else if metrics.italic_angle == 0.0 && slot.italic {
+            findings.push(Finding::new(
+                Severity::Info,
+                Check::StyleLinking,
+                Some(POST),
+                "italic face with an italicAngle of 0 (upright italic?)".to_string(),
+            ));
+        }
+    }
+
+    findings
+}
+
+/// Finds RIBBI linking conflicts across the faces of each legacy family
+///
+/// Faces are grouped by name ID 1 and container, so a .ttf and its .woff2
+/// copy don't clash. Faces with the same PostScript name are copies of one
+/// face (installed in two directories, or both as a .ttf and in a .ttc)
+/// and count once. Returns each finding with the index of the face it
+/// concerns.
+pub fn check_families(fonts: &[FontInfo]) -> Vec<(usize, Finding)> {
+    let mut families: BTreeMap<(String, &str), Vec<usize>> = BTreeMap::new();
+    let mut copies = BTreeSet::new();
+    for (i, font) in fonts.iter().enumerate() {
+        let Some(family) = font.names.best(NameId::FAMILY) else {
+            continue;
+        };
+        let postscript = font.names.best(NameId::POSTSCRIPT_NAME);
+        if postscript.is_some_and(|name| !copies.insert((name, font.container.name()))) {
+            continue;
+        }
+        families
+            .entry((family.to_string(), font.container.name()))
+            .or_default()
+            .push(i);
+    }
+
+    let mut findings = Vec::new();
+    for ((family, _), members) in &families {
+        let mut slots: BTreeMap<Slot, Vec<usize>> = BTreeMap::new();
+        for &i in members {
+            if let Some(slot) = Slot::of(&fonts[i]) {
+                slots.entry(slot).or_default().push(i);
+            }
+        }
+
+        for (slot, faces) in &slots {
+            for &i in faces.iter().skip(1) {
+                findings.push((
+                    i,
+                    Finding::new(
+                        Severity::Error,
+                        Check::FamilyLinking,
+                        None,
+                        format!(
+                            "links as {} in family '{}', like {}; only one of them is reachable",
+                            slot.name(),
+                            family,
+                            fonts[faces[0]].location()
+                        ),
+                    ),
+                ));
+            }
+        }
+
+        // Reported once, on the family's first face; a lone face is not a family
+        let regular = Slot {
+            bold: false,
+            italic: false,
+        };
+        let first = slots
+            .values()
+            .next()
+            .filter(|_| members.len() > 1 && !slots.contains_key(&regular));
+        if let Some(faces) = first {
+            findings.push((
+                faces[0],
+                Finding::new(
+                    Severity::Warning,
+                    Check::FamilyLinking,
+                    None,
+                    format!("family '{}' has no Regular face to link to", family),
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+/// The RIBBI slot a legacy subfamily name stands for, if it is one of the four
+fn subfamily_slot(subfamily: &str) -> Option<Slot> {
+    match subfamily.to_ascii_lowercase().as_str() {
+        "regular" | "normal" | "roman" | "book" => Some(Slot {
+            bold: false,
+            italic: false,
+        }),
+        "italic" | "oblique" => Some(Slot {
+            bold: false,
+            italic: true,
+        }),
+        "bold" => Some(Slot {
+            bold: true,
+            italic: false,
+        }),
+        "bold italic" | "bold oblique" | "bolditalic" => Some(Slot {
+            bold: true,
+            italic: true,
+        }),
+        _ => None,
+    }
+}
+
+/// A head.macStyle bit that disagrees with fsSelection
+fn mismatch(table: Tag, bit: &str, mac_style: bool, fs_selection: bool) -> Finding {
+    let on_off = |set: bool| if set { "set" } else { "clear" };
+    Finding::new(
+        Severity::Warning,
+        Check::StyleLinking,
+        Some(table),
+        format!(
+            "macStyle {} is {} but fsSelection {} is {}",
+            bit,
+            on_off(mac_style),
+            bit.to_uppercase(),
+            on_off(fs_selection)
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FsSelection, Metrics, NameTable, Os2};
+
+    fn face(family: &str, subfamily: &str, fs_selection: u16, weight: u16) -> FontInfo {
+        FontInfo {
+            family_name: family.to_string(),
+            style_name: subfamily.to_string(),
+            file_path: format!("{}-{}.ttf", family, subfamily),
+            names: NameTable::english(&[(NameId::FAMILY, family), (NameId::SUBFAMILY, subfamily)]),
+            os2: Some(Os2 {
+                weight_class: weight,
+                fs_selection: FsSelection(fs_selection),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn checks(findings: &[Finding]) -> Vec<(Severity, Tag)> {
+        findings
+            .iter()
+            .map(|f| (f.severity, f.table.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn consistent_faces_have_no_findings() {
+        let regular = face("Test", "Regular", FsSelection::REGULAR, 400);
+        assert_eq!(check_face(&regular), []);
+        let mut bold_italic = face("Test", "Bold Italic", 0x21, 700);
+        bold_italic.mac_style = Some(MAC_STYLE_BOLD | MAC_STYLE_ITALIC);
+        bold_italic.metrics = Some(Metrics {
+            italic_angle: -12.0,
+            ..Default::default()
+        });
+        assert_eq!(check_face(&bold_italic), []);
+        // Faces without OS/2 or names (API sources) are not checked
+        assert_eq!(check_face(&FontInfo::default()), []);
+    }
+
+    #[test]
+    fn bits_that_disagree_with_the_names() {
+        // Named Bold, linked as Regular, with REGULAR and ITALIC both set
+        let mut font = face(
+            "Test",
+            "Bold",
+            FsSelection::REGULAR | FsSelection::ITALIC,
+            400,
+        );
+        font.mac_style = Some(MAC_STYLE_BOLD);
+        assert_eq!(
+            checks(&check_face(&font)),
+            [
+                (Severity::Warning, OS2),
+                (Severity::Error, OS2),
+                (Severity::Warning, HEAD),
+                (Severity::Warning, HEAD),
+            ]
+        );
+
+        let font = face("Test", "Bold", FsSelection::BOLD, 400);
+        let findings = check_face(&font);
+        assert_eq!(checks(&findings), [(Severity::Warning, OS2)]);
+        assert!(findings[0].message.contains("usWeightClass is only 400"));
+
+        let font = face("Test", "Light", FsSelection::REGULAR, 1200);
+        assert_eq!(
+            checks(&check_face(&font)),
+            [(Severity::Warning, NAME), (Severity::Warning, OS2)]
+        );
+    }
+
+    #[test]
+    fn italic_angle_and_slant() {
+        let mut upright = face("Test", "Regular", FsSelection::REGULAR, 400);
+        upright.metrics = Some(Metrics {
+            italic_angle: -10.0,
+            ..Default::default()
+        });
+        assert_eq!(checks(&check_face(&upright)), [(Severity::Warning, POST)]);
+
+        let mut italic = face("Test", "Italic", FsSelection::ITALIC, 400);
+        italic.metrics = Some(Metrics::default());
+        assert_eq!(checks(&check_face(&italic)), [(Severity::Info, POST)]);
+    }
+
+    #[test]
+    fn family_slots() {
+        let fonts = [
+            face("Test", "Bold", FsSelection::BOLD, 700),
+            face("Test", "Black", FsSelection::BOLD, 900),
+            face("Other", "Regular", FsSelection::REGULAR, 400),
+            face("Test", "Italic", FsSelection::ITALIC, 400),
+        ];
+        let findings = check_families(&fonts);
+        let found: Vec<(usize, Severity)> =
+            findings.iter().map(|(i, f)| (*i, f.severity)).collect();
+        // Black takes Bold's slot; the family has no Regular
+        assert_eq!(found, [(1, Severity::Error), (3, Severity::Warning)]);
+        assert!(findings[0].1.message.contains("Test-Bold.ttf"));
+
+        // A .woff2 copy of a face is not a conflict
+        let mut copy = fonts[0].clone();
+        copy.container = crate::Container::Woff2;
+        let regular = face("Test", "Regular", FsSelection::REGULAR, 400);
+        assert_eq!(check_families(&[fonts[0].clone(), copy, regular]), []);
+    }
+
+    #[test]
+    fn copies_of_a_face_are_checked_once() {
+        let named = |mut font: FontInfo, postscript: &str, path: &str| {
+            let family = font.names.best(NameId::FAMILY).unwrap().to_string();
+            let subfamily = font.style_name.clone();
+            font.names = NameTable::english(&[
+                (NameId::FAMILY, &family),
+                (NameId::SUBFAMILY, &subfamily),
+                (NameId::POSTSCRIPT_NAME, postscript),
+            ]);
+            font.file_path = path.to_string();
+            font
+        };
+        let regular = face("Test", "Regular", FsSelection::REGULAR, 400);
+        let bold = face("Test", "Bold", FsSelection::BOLD, 700);
+        let fonts = [
+            named(regular.clone(), "Test-Regular", "/usr/share/fonts/Test.ttf"),
+            named(bold.clone(), "Test-Bold", "/usr/share/fonts/TestBold.ttf"),
+            named(regular.clone(), "Test-Regular", "/home/me/.fonts/Test.ttf"),
+            named(bold.clone(), "Test-Bold", "/home/me/.fonts/Test.ttc"),
+        ];
+        assert_eq!(check_families(&fonts), []);
+
+        // Different faces that share a slot still clash
+        let fonts = [
+            named(regular.clone(), "Test-Regular", "Test.ttf"),
+            named(regular, "Test-Book", "TestBook.ttf"),
+        ];
+        assert_eq!(check_families(&fonts).len(), 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Font validation - structural checks of font files
+//!
+//! Enumeration skips files it cannot parse; validation reports why. Each
+//! face gets a `FontReport` listing its `Finding`s:
+//! - the file can be decoded and its table directory read
+//! - every table lies inside the file, is 4-byte aligned, and tables are
+//!   listed in tag order without duplicates or overlaps
+//! - table checksums and head.checkSumAdjustment match the data
+//! - the tables required for the face's outline format are present
+//! - loca has one offset per glyph (plus one), ascending and inside glyf
+//! - the style-linking bits agree with the names, per face and across each
+//!   family (see `style`)
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::font_info::face_location;
+use crate::sources::font_files;
+use crate::{sfnt, style, FontInfo, Result, Tag};
+
+/// How serious a finding is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,    // Worth knowing, nothing to fix
+    Warning, // Violates the spec but most software copes
+    Error,   // Likely to make the font fail to load or render
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// The check that produced a finding
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Check {
+    Decode,             // The file or a face could not be parsed at all
+    TableBounds,        // A table extends past the end of the file
+    TableAlignment,     // A table does not start on a 4-byte boundary
+    TableOrder,         // The directory is not sorted by tag
+    DuplicateTable,     // A tag appears more than once
+    TableOverlap,       // Two tables share bytes
+    TableChecksum,      // A directory checksum doesn't match the table data
+    ChecksumAdjustment, // head.checkSumAdjustment doesn't match the file
+    HeadMagic,          // head.magicNumber is not 0x5F0F3CF5
+    MissingTable,       // A table required for the outline format is absent
+    Loca,               // loca doesn't match maxp or glyf
+    StyleLinking,       // Subfamily name, fsSelection, macStyle, weight or italic angle disagree
+    FamilyLinking,      // Faces of a family claim the same RIBBI style, or there is no Regular
+}
+
+impl Check {
+    /// Short kebab-case name, as shown in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Decode => "decode",
+            Check::TableBounds => "table-bounds",
+            Check::TableAlignment => "table-alignment",
+            Check::TableOrder => "table-order",
+            Check::DuplicateTable => "duplicate-table",
+            Check::TableOverlap => "table-overlap",
+            Check::TableChecksum => "table-checksum",
+            Check::ChecksumAdjustment => "checksum-adjustment",
+            Check::HeadMagic => "head-magic",
+            Check::MissingTable => "missing-table",
+            Check::Loca => "loca",
+            Check::StyleLinking => "style-linking",
+            Check::FamilyLinking => "family-linking",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One problem (or notable fact) found in a font
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: Check,
+    pub table: Option<Tag>, // The table concerned, if any
+    pub message: String,
+}
+
+impl Finding {
+    pub(crate) fn new(
+        severity: Severity,
+        check: Check,
+        table: Option<Tag>,
+        message: String,
+    ) -> Self {
+        Finding {
+            severity,
+            check,
+            table,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    /// Formats as `error table-checksum 'glyf': message`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.severity, self.check)?;
+        if let Some(table) = self.table {
+            write!(f, " '{}'", table)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The findings for one face of a font file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontReport {
+    pub file_path: String,
+    pub face_index: Option<u32>, // Face within a .ttc/.otc collection
+    pub name: Option<String>,    // "Family Style", if the face could be named
+    pub findings: Vec<Finding>,
+}
+
+impl FontReport {
+    /// The file path, with `#index` appended for faces of a collection
+    pub fn location(&self) -> String {
+        face_location(&self.file_path, self.face_index)
+    }
+
+    /// The most serious finding, or None for a clean font
+    pub fn severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    /// Number of findings of one severity
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+}
+
+/// Validates the faces in a font file's bytes
+///
+/// Always returns at least one report: a file that cannot be decoded at
+/// all yields a single report with a `Check::Decode` error.
+pub fn validate_font_data(data: &[u8], file_path: &str) -> Vec<FontReport> {
+    let mut faces = validate_faces(data, file_path);
+    check_families(&mut faces);
+    faces.into_iter().map(|(report, _)| report).collect()
+}
+
+/// Validates font files and every font file below directories
+///
+/// Files are checked whatever their extension; directories are walked the
+/// same way as `DirectorySource`. Unreadable files are reported as
+/// `Check::Decode` errors; only a missing path fails the whole run.
+pub fn validate_paths(paths: &[PathBuf]) -> Result<Vec<FontReport>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(font_files(std::slice::from_ref(path))?);
+        } else {
+            fs::metadata(path)?;
+            files.push(path.clone());
+        }
+    }
+    // The same file named twice (or inside a named directory) is checked once
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+
+    let mut faces = Vec::new();
+    for path in &files {
+        let file_path = path.to_string_lossy();
+        match fs::read(path) {
+            Ok(data) => faces.extend(validate_faces(&data, &file_path)),
+            Err(e) => faces.push((
+                FontReport {
+                    file_path: file_path.to_string(),
+                    findings: vec![Finding::new(
+                        Severity::Error,
+                        Check::Decode,
+                        None,
+                        e.to_string(),
+                    )],
+                    ..Default::default()
+                },
+                None,
+            )),
+        }
+    }
+    check_families(&mut faces);
+    Ok(faces.into_iter().map(|(report, _)| report).collect())
+}
+
+/// Structural and per-face style checks, keeping each parsed face for `check_families`
+fn validate_faces(data: &[u8], file_path: &str) -> Vec<(FontReport, Option<FontInfo>)> {
+    let mut faces = sfnt::validate_faces(data, file_path);
+    for (report, font) in &mut faces {
+        if let Some(font) = font {
+            report.findings.extend(style::check_face(font));
+        }
+    }
+    faces
+}
+
+/// Adds the family-level style-linking findings to the reports they concern
+fn check_families(faces: &mut [(FontReport, Option<FontInfo>)]) {
+    let (indices, fonts): (Vec<usize>, Vec<FontInfo>) = faces
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, font))| Some((i, font.clone()?)))
+        .unzip();
+    for (i, finding) in style::check_families(&fonts) {
+        faces[indices[i]].0.findings.push(finding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, check: Check) -> Finding {
+        Finding::new(severity, check, Some(Tag::new(b"glyf")), "bad".to_string())
+    }
+
+    #[test]
+    fn undecodable_data_gets_one_report() {
+        let reports = validate_font_data(b"not a font", "junk.ttf");
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].location(), "junk.ttf");
+        assert_eq!(reports[0].severity(), Some(Severity::Error));
+        assert_eq!(reports[0].findings[0].check, Check::Decode);
+    }
+
+    #[test]
+    fn reports_summarize_their_findings() {
+        let report = FontReport {
+            file_path: "fonts.ttc".to_string(),
+            face_index: Some(2),
+            findings: vec![
+                finding(Severity::Warning, Check::TableAlignment),
+                finding(Severity::Info, Check::FamilyLinking),
+                finding(Severity::Warning, Check::Loca),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(report.location(), "fonts.ttc#2");
+        assert_eq!(report.severity(), Some(Severity::Warning));
+        assert_eq!(report.count(Severity::Warning), 2);
+        assert_eq!(report.count(Severity::Error), 0);
+        assert_eq!(FontReport::default().severity(), None);
+        assert_eq!(
+            report.findings[0].to_string(),
+            "warning table-alignment 'glyf': bad"
+        );
+    }
+
+    #[test]
+    fn paths_are_checked_once_and_must_exist() {
+        let root = std::env::temp_dir().join(format!("fontenum-validate-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let file = root.join("broken.otf");
+        fs::write(&file, b"OTTO").unwrap();
+
+        let reports = validate_paths(&[root.clone(), file.clone()]);
+        let missing = validate_paths(&[root.join("missing.ttf")]);
+        fs::remove_dir_all(&root).unwrap();
+        let reports = reports.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file_path, file.to_string_lossy());
+        assert_eq!(reports[0].findings[0].check, Check::Decode);
+        assert!(missing.is_err());
+    }
+}